@@ -0,0 +1,558 @@
+use crate::utils::ReportFile;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Suffix of the test result file
+pub const RESULT_FILE_SUFFIX: &str = "-result.json";
+/// Suffix of the container file (fixtures of a group of tests)
+pub const CONTAINER_FILE_SUFFIX: &str = "-container.json";
+/// Part of the attachment file name, allure writes them as `<uuid>-attachment.<ext>`
+pub const ATTACHMENT_FILE_MARKER: &str = "-attachment";
+
+/// Check that file is an allure test result
+pub fn is_result_file(file_name: &str) -> bool {
+    file_name.ends_with(RESULT_FILE_SUFFIX)
+}
+
+/// Check that file is an allure container
+pub fn is_container_file(file_name: &str) -> bool {
+    file_name.ends_with(CONTAINER_FILE_SUFFIX)
+}
+
+/// Check that file is an allure attachment
+pub fn is_attachment_file(file_name: &str) -> bool {
+    file_name
+        .rsplit('/')
+        .next()
+        .is_some_and(|name| name.contains(ATTACHMENT_FILE_MARKER))
+}
+
+/// Statuses that are always shown in the count of results
+const MAIN_STATUSES: [&str; 5] = ["passed", "failed", "broken", "skipped", "unknown"];
+
+/// Count results by the `status` field, main statuses go first even with zero count
+///
+/// Result without a status or with unreadable content is counted as `unknown`
+pub fn count_result_statuses(report_files: &[ReportFile]) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = MAIN_STATUSES
+        .iter()
+        .map(|status| (status.to_string(), 0))
+        .collect();
+    for report_file in report_files
+        .iter()
+        .filter(|report_file| is_result_file(&report_file.name_in_archive))
+    {
+        let status = report_file
+            .read()
+            .ok()
+            .and_then(|content| serde_json::from_slice::<Value>(&content).ok())
+            .and_then(|value| value["status"].as_str().map(str::to_lowercase))
+            .unwrap_or_else(|| "unknown".to_string());
+        match counts.iter_mut().find(|(name, _)| *name == status) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((status, 1)),
+        }
+    }
+    counts
+}
+
+/// Attachment of result or container
+#[derive(Debug, PartialEq)]
+pub struct AttachmentReference {
+    /// Path to the `source` field, e.g. `steps[0].attachments[1].source`
+    pub field: String,
+    pub source: Option<String>,
+}
+
+/// Collect `source` of all attachments in result or container
+///
+/// Attachments are searched in the item itself, its steps and fixtures
+pub fn attachment_sources(value: &Value) -> Vec<String> {
+    attachment_references(value)
+        .into_iter()
+        .filter_map(|reference| reference.source)
+        .collect()
+}
+
+/// Collect all attachments in result or container with their fields
+pub fn attachment_references(value: &Value) -> Vec<AttachmentReference> {
+    let mut references = Vec::new();
+    collect_attachment_references(value, "", &mut references);
+    references
+}
+
+fn collect_attachment_references(
+    value: &Value,
+    field_prefix: &str,
+    references: &mut Vec<AttachmentReference>,
+) {
+    if let Some(attachments) = value.get("attachments").and_then(Value::as_array) {
+        for (index, attachment) in attachments.iter().enumerate() {
+            references.push(AttachmentReference {
+                field: format!("{field_prefix}attachments[{index}].source"),
+                source: attachment
+                    .get("source")
+                    .and_then(Value::as_str)
+                    .map(String::from),
+            });
+        }
+    }
+    for key in ["steps", "befores", "afters"] {
+        if let Some(children) = value.get(key).and_then(Value::as_array) {
+            for (index, child) in children.iter().enumerate() {
+                collect_attachment_references(
+                    child,
+                    &format!("{field_prefix}{key}[{index}]."),
+                    references,
+                );
+            }
+        }
+    }
+}
+
+/// Replace `source` of attachments in result or container
+///
+/// `replace` returns a new source or None to keep the current one.
+/// Returns true if any source was replaced
+pub fn replace_attachment_sources<F>(value: &mut Value, replace: &F) -> bool
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut is_replaced = false;
+    if let Some(attachments) = value.get_mut("attachments").and_then(Value::as_array_mut) {
+        for attachment in attachments {
+            let new_source = attachment
+                .get("source")
+                .and_then(Value::as_str)
+                .and_then(replace);
+            if let Some(new_source) = new_source {
+                attachment["source"] = Value::String(new_source);
+                is_replaced = true;
+            }
+        }
+    }
+    for key in ["steps", "befores", "afters"] {
+        if let Some(children) = value.get_mut(key).and_then(Value::as_array_mut) {
+            for child in children {
+                is_replaced |= replace_attachment_sources(child, replace);
+            }
+        }
+    }
+    is_replaced
+}
+
+#[derive(Debug, PartialEq)]
+pub enum IssueLevel {
+    Error,
+    Warning,
+}
+
+/// Problem found in the report file
+#[derive(Debug, PartialEq)]
+pub struct ValidationIssue {
+    pub level: IssueLevel,
+    /// Path of the file inside the report directory
+    pub file: String,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn error(file: &str, message: String) -> Self {
+        Self {
+            level: IssueLevel::Error,
+            file: file.to_string(),
+            message,
+        }
+    }
+
+    fn warning(file: &str, message: String) -> Self {
+        Self {
+            level: IssueLevel::Warning,
+            file: file.to_string(),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.level {
+            IssueLevel::Error => "error",
+            IssueLevel::Warning => "warning",
+        };
+        write!(f, "{}: {}: {}", level, self.file, self.message)
+    }
+}
+
+/// Result of the report validation
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub results_count: usize,
+    pub containers_count: usize,
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    pub fn errors_count(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.level == IssueLevel::Error)
+            .count()
+    }
+
+    pub fn warnings_count(&self) -> usize {
+        self.issues.len() - self.errors_count()
+    }
+}
+
+/// Check results, containers and their attachments before upload
+///
+/// Attachment that is missing among `report_files`, but exists on disk,
+/// is considered excluded by the archive filter and is not reported
+pub fn validate_report_files(report_files: &[ReportFile]) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let file_names: HashSet<&str> = report_files
+        .iter()
+        .map(|report_file| report_file.name_in_archive.as_str())
+        .collect();
+    let mut referenced_files = HashSet::new();
+    let mut result_by_uuid: HashMap<String, &str> = HashMap::new();
+    for report_file in report_files {
+        let name = report_file.name_in_archive.as_str();
+        let required_fields: &[&str] = if is_result_file(name) {
+            report.results_count += 1;
+            &["uuid", "historyId"]
+        } else if is_container_file(name) {
+            report.containers_count += 1;
+            &["uuid"]
+        } else {
+            continue;
+        };
+        let value: Value = match report_file
+            .read()
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_slice(&content).map_err(|e| e.to_string()))
+        {
+            Ok(value) => value,
+            Err(e) => {
+                report
+                    .issues
+                    .push(ValidationIssue::error(name, format!("invalid JSON: {}", e)));
+                continue;
+            }
+        };
+        for field in required_fields {
+            let is_filled =
+                matches!(value.get(field).and_then(Value::as_str), Some(v) if !v.is_empty());
+            if !is_filled {
+                report.issues.push(ValidationIssue::error(
+                    name,
+                    format!("missing field `{}`", field),
+                ));
+            }
+        }
+        if let Some(uuid) = value.get("uuid").and_then(Value::as_str) {
+            if is_result_file(name) && !uuid.is_empty() {
+                if let Some(other_name) = result_by_uuid.insert(uuid.to_string(), name) {
+                    report.issues.push(ValidationIssue::error(
+                        name,
+                        format!("duplicate uuid `{}`, already used in {}", uuid, other_name),
+                    ));
+                }
+            }
+        }
+        let dir_in_archive = name.rsplit_once('/').map(|(dir, _)| dir);
+        for reference in attachment_references(&value) {
+            let Some(source) = reference.source else {
+                report.issues.push(ValidationIssue::error(
+                    name,
+                    format!("missing field `{}`", reference.field),
+                ));
+                continue;
+            };
+            let attachment_name = match dir_in_archive {
+                Some(dir) => format!("{dir}/{source}"),
+                None => source.clone(),
+            };
+            let is_excluded = report_file
+                .path
+                .parent()
+                .is_some_and(|dir| dir.join(&source).is_file());
+            if !file_names.contains(attachment_name.as_str()) && !is_excluded {
+                report.issues.push(ValidationIssue::error(
+                    name,
+                    format!(
+                        "attachment file `{}` from field `{}` not found",
+                        source, reference.field
+                    ),
+                ));
+            }
+            referenced_files.insert(attachment_name);
+        }
+    }
+    for name in file_names {
+        if is_attachment_file(name) && !referenced_files.contains(name) {
+            report.issues.push(ValidationIssue::warning(
+                name,
+                "attachment is not referenced by any result or container".to_string(),
+            ));
+        }
+    }
+    report
+        .issues
+        .sort_by(|first, second| first.file.cmp(&second.file));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{collect_report_files, ArchiveFilter};
+    use rstest::rstest;
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_count_result_statuses() {
+        let report_file = |name: &str, content: &str| ReportFile {
+            path: PathBuf::from(name),
+            name_in_archive: name.to_string(),
+            size: content.len() as u64,
+            content: Some(content.as_bytes().to_vec()),
+        };
+        let report_files = vec![
+            report_file("1-result.json", r#"{"status": "passed"}"#),
+            report_file("2-result.json", r#"{"status": "failed"}"#),
+            report_file("3-result.json", r#"{"status": "passed"}"#),
+            report_file("4-result.json", r#"{"status": "Custom"}"#),
+            report_file("5-result.json", "{}"),
+            report_file("6-result.json", "not json"),
+            report_file("1-container.json", r#"{"status": "broken"}"#),
+        ];
+        assert_eq!(
+            count_result_statuses(&report_files),
+            vec![
+                ("passed".to_string(), 2),
+                ("failed".to_string(), 1),
+                ("broken".to_string(), 0),
+                ("skipped".to_string(), 0),
+                ("unknown".to_string(), 2),
+                ("custom".to_string(), 1),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case("1234-result.json", true)]
+    #[case("worker/1234-result.json", true)]
+    #[case("1234-container.json", false)]
+    #[case("1234-attachment.txt", false)]
+    fn test_is_result_file(#[case] file_name: &str, #[case] expected: bool) {
+        assert_eq!(is_result_file(file_name), expected);
+    }
+
+    #[rstest]
+    #[case("1234-container.json", true)]
+    #[case("1234-result.json", false)]
+    fn test_is_container_file(#[case] file_name: &str, #[case] expected: bool) {
+        assert_eq!(is_container_file(file_name), expected);
+    }
+
+    #[test]
+    fn test_attachment_sources_nested() {
+        let value: Value = serde_json::from_str(
+            r#"{
+                "attachments": [{"name": "log", "source": "a-attachment.txt"}],
+                "steps": [
+                    {
+                        "attachments": [{"source": "b-attachment.png"}],
+                        "steps": [{"attachments": [{"source": "c-attachment.json"}]}]
+                    }
+                ],
+                "befores": [{"attachments": [{"source": "d-attachment.txt"}]}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            attachment_sources(&value),
+            vec![
+                "a-attachment.txt",
+                "b-attachment.png",
+                "c-attachment.json",
+                "d-attachment.txt"
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case("1234-attachment.txt", true)]
+    #[case("worker/1234-attachment", true)]
+    #[case("worker-attachment/1234.txt", false)]
+    #[case("environment.properties", false)]
+    fn test_is_attachment_file(#[case] file_name: &str, #[case] expected: bool) {
+        assert_eq!(is_attachment_file(file_name), expected);
+    }
+
+    #[test]
+    fn test_attachment_references_fields() {
+        let value: Value = serde_json::from_str(
+            r#"{
+                "steps": [
+                    {"attachments": [{"source": "a-attachment.txt"}, {"name": "no source"}]}
+                ],
+                "afters": [{"attachments": [{"source": "b-attachment.txt"}]}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            attachment_references(&value),
+            vec![
+                AttachmentReference {
+                    field: "steps[0].attachments[0].source".to_string(),
+                    source: Some("a-attachment.txt".to_string()),
+                },
+                AttachmentReference {
+                    field: "steps[0].attachments[1].source".to_string(),
+                    source: None,
+                },
+                AttachmentReference {
+                    field: "afters[0].attachments[0].source".to_string(),
+                    source: Some("b-attachment.txt".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replace_attachment_sources() {
+        let mut value: Value = serde_json::from_str(
+            r#"{
+                "attachments": [{"source": "a-attachment.txt"}, {"source": "b-attachment.txt"}],
+                "afters": [{"attachments": [{"source": "a-attachment.txt"}]}]
+            }"#,
+        )
+        .unwrap();
+        let is_replaced = replace_attachment_sources(&mut value, &|source: &str| {
+            (source == "a-attachment.txt").then(|| "a-attachment-1.txt".to_string())
+        });
+        assert!(is_replaced);
+        assert_eq!(
+            attachment_sources(&value),
+            vec![
+                "a-attachment-1.txt",
+                "b-attachment.txt",
+                "a-attachment-1.txt"
+            ]
+        );
+        assert!(!replace_attachment_sources(&mut value, &|_: &str| None));
+    }
+
+    /// Создает отчет во временной директории и собирает его файлы
+    fn report_files(files: &[(&str, &str)]) -> (PathBuf, Vec<ReportFile>) {
+        let report_dir =
+            std::env::temp_dir().join(format!("wot_validate_{}", uuid::Uuid::new_v4()));
+        for (name, content) in files {
+            let path = report_dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        let filter = ArchiveFilter::new(&[], &["**/*.mp4".to_string()]).unwrap();
+        let report_files = collect_report_files(&report_dir, &filter).unwrap();
+        (report_dir, report_files)
+    }
+
+    #[test]
+    fn test_validate_report_files_valid() {
+        let (report_dir, report_files) = report_files(&[
+            (
+                "1-result.json",
+                r#"{"uuid": "1", "historyId": "h1", "attachments": [{"source": "a-attachment.txt"}]}"#,
+            ),
+            ("a-attachment.txt", "log"),
+            (
+                "worker/2-result.json",
+                r#"{"uuid": "2", "historyId": "h2", "steps": [{"attachments": [{"source": "b-attachment.mp4"}]}]}"#,
+            ),
+            // Видео исключено фильтром, но есть на диске
+            ("worker/b-attachment.mp4", "video"),
+            (
+                "3-container.json",
+                r#"{"uuid": "3", "befores": [{"attachments": [{"source": "c-attachment.txt"}]}]}"#,
+            ),
+            ("c-attachment.txt", "log"),
+            ("environment.properties", "os=linux"),
+        ]);
+
+        let report = validate_report_files(&report_files);
+        fs::remove_dir_all(&report_dir).unwrap();
+
+        assert_eq!(report.results_count, 2);
+        assert_eq!(report.containers_count, 1);
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn test_validate_report_files_issues() {
+        let (report_dir, report_files) = report_files(&[
+            ("1-result.json", r#"{"uuid": "1", "#),
+            (
+                "2-result.json",
+                r#"{"uuid": "", "steps": [{"attachments": [{"source": "missing-attachment.txt"}, {"name": "log"}]}]}"#,
+            ),
+            ("3-container.json", r#"{"children": []}"#),
+            ("orphan-attachment.txt", "log"),
+        ]);
+
+        let report = validate_report_files(&report_files);
+        fs::remove_dir_all(&report_dir).unwrap();
+
+        let issues: Vec<String> = report
+            .issues
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                "error: 1-result.json: invalid JSON: EOF while parsing a value at line 1 column 14",
+                "error: 2-result.json: missing field `uuid`",
+                "error: 2-result.json: missing field `historyId`",
+                "error: 2-result.json: attachment file `missing-attachment.txt` from field `steps[0].attachments[0].source` not found",
+                "error: 2-result.json: missing field `steps[0].attachments[1].source`",
+                "error: 3-container.json: missing field `uuid`",
+                "warning: orphan-attachment.txt: attachment is not referenced by any result or container",
+            ]
+        );
+        assert_eq!(report.errors_count(), 6);
+        assert_eq!(report.warnings_count(), 1);
+    }
+
+    #[test]
+    fn test_validate_report_files_duplicate_uuid() {
+        let (report_dir, report_files) = report_files(&[
+            ("1-result.json", r#"{"uuid": "1", "historyId": "h1"}"#),
+            (
+                "worker/2-result.json",
+                r#"{"uuid": "1", "historyId": "h2"}"#,
+            ),
+        ]);
+
+        let report = validate_report_files(&report_files);
+        fs::remove_dir_all(&report_dir).unwrap();
+
+        assert_eq!(
+            report.issues,
+            vec![ValidationIssue::error(
+                "worker/2-result.json",
+                "duplicate uuid `1`, already used in 1-result.json".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_attachment_sources_empty() {
+        let value: Value = serde_json::from_str(r#"{"uuid": "1", "attachments": []}"#).unwrap();
+        assert!(attachment_sources(&value).is_empty());
+    }
+}
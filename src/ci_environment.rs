@@ -0,0 +1,463 @@
+use crate::external_api::testops_api::models::external_link::ExternalLink;
+use crate::utils::ReportFile;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Allure file with the info about the system that ran the tests
+pub const EXECUTOR_FILE: &str = "executor.json";
+/// Allure file with the environment of the launch
+pub const ENVIRONMENT_FILE: &str = "environment.properties";
+
+/// CI system where the report is uploaded from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CiProvider {
+    GitlabCi,
+    GithubActions,
+    Jenkins,
+    Teamcity,
+}
+
+impl CiProvider {
+    /// Name for the humans, shown in executor.json and in the environment
+    pub fn name(&self) -> &'static str {
+        match self {
+            CiProvider::GitlabCi => "GitLab CI",
+            CiProvider::GithubActions => "GitHub Actions",
+            CiProvider::Jenkins => "Jenkins",
+            CiProvider::Teamcity => "TeamCity",
+        }
+    }
+
+    /// Executor type, allure uses it to choose the icon
+    fn executor_type(&self) -> &'static str {
+        match self {
+            CiProvider::GitlabCi => "gitlab",
+            CiProvider::GithubActions => "github",
+            CiProvider::Jenkins => "jenkins",
+            CiProvider::Teamcity => "teamcity",
+        }
+    }
+}
+
+/// Build metadata read from the variables of the CI system
+#[derive(Debug, Clone, PartialEq)]
+pub struct CiEnvironment {
+    pub provider: CiProvider,
+    pub job_name: Option<String>,
+    pub job_url: Option<String>,
+    pub pipeline_id: Option<String>,
+    pub pipeline_url: Option<String>,
+    pub branch: Option<String>,
+    pub commit: Option<String>,
+    pub commit_url: Option<String>,
+}
+
+/// Content of executor.json
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Executor<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    executor_type: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    build_name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    build_url: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    build_order: Option<u64>,
+}
+
+impl CiEnvironment {
+    /// Detect CI from the environment variables of the current process
+    pub fn detect() -> Option<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Detect CI from the variables, empty values are treated as missing
+    ///
+    /// TeamCity doesn't export the branch and the build url by default,
+    /// they are read from `TEAMCITY_BUILD_BRANCH` and `TEAMCITY_BUILD_URL` if the build sets them
+    pub fn from_vars<F>(var: F) -> Option<Self>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let var = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+        if var("GITLAB_CI").is_some() {
+            let commit = var("CI_COMMIT_SHA");
+            return Some(Self {
+                provider: CiProvider::GitlabCi,
+                job_name: var("CI_JOB_NAME"),
+                job_url: var("CI_JOB_URL"),
+                pipeline_id: var("CI_PIPELINE_ID"),
+                pipeline_url: var("CI_PIPELINE_URL"),
+                branch: var("CI_COMMIT_REF_NAME"),
+                commit_url: var("CI_PROJECT_URL")
+                    .zip(commit.as_ref())
+                    .map(|(project_url, commit)| format!("{}/-/commit/{}", project_url, commit)),
+                commit,
+            });
+        }
+        if var("GITHUB_ACTIONS").is_some() {
+            let repository_url = var("GITHUB_SERVER_URL")
+                .zip(var("GITHUB_REPOSITORY"))
+                .map(|(server_url, repository)| format!("{}/{}", server_url, repository));
+            let pipeline_id = var("GITHUB_RUN_ID");
+            let pipeline_url = repository_url.as_ref().zip(pipeline_id.as_ref()).map(
+                |(repository_url, run_id)| format!("{}/actions/runs/{}", repository_url, run_id),
+            );
+            let commit = var("GITHUB_SHA");
+            return Some(Self {
+                provider: CiProvider::GithubActions,
+                job_name: var("GITHUB_WORKFLOW").map(|workflow| match var("GITHUB_JOB") {
+                    Some(job) => format!("{} / {}", workflow, job),
+                    None => workflow,
+                }),
+                job_url: pipeline_url.clone(),
+                pipeline_id,
+                pipeline_url,
+                // В pull request GITHUB_REF_NAME указывает на merge ref, а не на ветку
+                branch: var("GITHUB_HEAD_REF").or_else(|| var("GITHUB_REF_NAME")),
+                commit_url: repository_url
+                    .zip(commit.as_ref())
+                    .map(|(repository_url, commit)| {
+                        format!("{}/commit/{}", repository_url, commit)
+                    }),
+                commit,
+            });
+        }
+        if var("JENKINS_URL").is_some() {
+            return Some(Self {
+                provider: CiProvider::Jenkins,
+                job_name: var("JOB_NAME"),
+                job_url: var("BUILD_URL"),
+                pipeline_id: var("BUILD_NUMBER"),
+                pipeline_url: var("BUILD_URL"),
+                branch: var("BRANCH_NAME").or_else(|| {
+                    var("GIT_BRANCH").map(|branch| {
+                        branch
+                            .strip_prefix("origin/")
+                            .map(str::to_string)
+                            .unwrap_or(branch)
+                    })
+                }),
+                commit: var("GIT_COMMIT"),
+                commit_url: None,
+            });
+        }
+        if var("TEAMCITY_VERSION").is_some() {
+            return Some(Self {
+                provider: CiProvider::Teamcity,
+                job_name: var("TEAMCITY_BUILDCONF_NAME"),
+                job_url: var("TEAMCITY_BUILD_URL"),
+                pipeline_id: var("BUILD_NUMBER"),
+                pipeline_url: var("TEAMCITY_BUILD_URL"),
+                branch: var("TEAMCITY_BUILD_BRANCH"),
+                commit: var("BUILD_VCS_NUMBER"),
+                commit_url: None,
+            });
+        }
+        None
+    }
+
+    /// Links to the job, the pipeline and the commit, the same url is added once
+    pub fn links(&self) -> Vec<ExternalLink> {
+        let mut links: Vec<ExternalLink> = Vec::new();
+        let candidates = [
+            ("Job", &self.job_url),
+            ("Pipeline", &self.pipeline_url),
+            ("Commit", &self.commit_url),
+        ];
+        for (name, url) in candidates {
+            if let Some(url) = url {
+                if !links.iter().any(|link| &link.url == url) {
+                    links.push(ExternalLink::new(name, url));
+                }
+            }
+        }
+        links
+    }
+
+    /// Tags with the branch, the short commit sha and the pipeline id
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags = Vec::new();
+        if let Some(branch) = &self.branch {
+            tags.push(format!("branch:{}", branch));
+        }
+        if let Some(commit) = &self.commit {
+            tags.push(format!("commit:{}", short_sha(commit)));
+        }
+        if let Some(pipeline_id) = &self.pipeline_id {
+            tags.push(format!("pipeline:{}", pipeline_id));
+        }
+        tags
+    }
+
+    /// Add executor.json and environment.properties to the root of the archive if they are missing
+    ///
+    /// `source_path` is used only to show where the files came from
+    pub fn add_report_files(
+        &self,
+        report_files: &mut Vec<ReportFile>,
+        source_path: &str,
+    ) -> Result<(), serde_json::Error> {
+        let has_file = |report_files: &[ReportFile], name: &str| {
+            report_files
+                .iter()
+                .any(|report_file| report_file.name_in_archive == name)
+        };
+        let mut generated = Vec::new();
+        if !has_file(report_files, EXECUTOR_FILE) {
+            generated.push((EXECUTOR_FILE, serde_json::to_vec_pretty(&self.executor())?));
+        }
+        if !has_file(report_files, ENVIRONMENT_FILE) {
+            generated.push((ENVIRONMENT_FILE, self.environment_properties().into_bytes()));
+        }
+        for (name, content) in generated {
+            report_files.push(ReportFile {
+                path: PathBuf::from(source_path).join(name),
+                name_in_archive: name.to_string(),
+                size: content.len() as u64,
+                content: Some(content),
+            });
+        }
+        report_files.sort_by(|a, b| a.name_in_archive.cmp(&b.name_in_archive));
+        Ok(())
+    }
+
+    fn executor(&self) -> Executor<'_> {
+        Executor {
+            name: self.provider.name(),
+            executor_type: self.provider.executor_type(),
+            build_name: self.job_name.as_deref(),
+            build_url: self.job_url.as_deref(),
+            build_order: self
+                .pipeline_id
+                .as_ref()
+                .and_then(|pipeline_id| pipeline_id.parse().ok()),
+        }
+    }
+
+    /// Java properties with the CI, the branch, the commit and the pipeline
+    fn environment_properties(&self) -> String {
+        let properties = [
+            ("CI", Some(self.provider.name())),
+            ("Job", self.job_name.as_deref()),
+            ("Branch", self.branch.as_deref()),
+            ("Commit", self.commit.as_deref()),
+            ("Pipeline", self.pipeline_id.as_deref()),
+        ];
+        properties
+            .iter()
+            .filter_map(|(key, value)| {
+                value.map(|value| format!("{}={}\n", key, escape_property(value)))
+            })
+            .collect()
+    }
+}
+
+/// First 8 characters of the commit sha
+fn short_sha(commit: &str) -> &str {
+    commit.get(..8).unwrap_or(commit)
+}
+
+/// Escape value for java properties file
+fn escape_property(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        match char {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '=' | ':' | '#' | '!' => {
+                escaped.push('\\');
+                escaped.push(char);
+            }
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn detect(vars: &[(&str, &str)]) -> Option<CiEnvironment> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        CiEnvironment::from_vars(|name| vars.get(name).cloned())
+    }
+
+    fn gitlab() -> CiEnvironment {
+        detect(&[
+            ("GITLAB_CI", "true"),
+            ("CI_JOB_NAME", "tests"),
+            ("CI_JOB_URL", "https://gitlab.example.com/qa/api/-/jobs/42"),
+            ("CI_PIPELINE_ID", "1001"),
+            (
+                "CI_PIPELINE_URL",
+                "https://gitlab.example.com/qa/api/-/pipelines/1001",
+            ),
+            ("CI_COMMIT_REF_NAME", "main"),
+            ("CI_COMMIT_SHA", "0123456789abcdef0123456789abcdef01234567"),
+            ("CI_PROJECT_URL", "https://gitlab.example.com/qa/api"),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_detect_gitlab() {
+        let ci = gitlab();
+        assert_eq!(ci.provider, CiProvider::GitlabCi);
+        assert_eq!(
+            ci.links(),
+            vec![
+                ExternalLink::new("Job", "https://gitlab.example.com/qa/api/-/jobs/42"),
+                ExternalLink::new(
+                    "Pipeline",
+                    "https://gitlab.example.com/qa/api/-/pipelines/1001"
+                ),
+                ExternalLink::new(
+                    "Commit",
+                    "https://gitlab.example.com/qa/api/-/commit/0123456789abcdef0123456789abcdef01234567"
+                ),
+            ]
+        );
+        assert_eq!(
+            ci.tags(),
+            vec!["branch:main", "commit:01234567", "pipeline:1001"]
+        );
+    }
+
+    #[test]
+    /// В pull request ветка берется из GITHUB_HEAD_REF, ссылка на job и pipeline совпадает
+    fn test_detect_github_actions() {
+        let ci = detect(&[
+            ("GITHUB_ACTIONS", "true"),
+            ("GITHUB_SERVER_URL", "https://github.com"),
+            ("GITHUB_REPOSITORY", "qa/api"),
+            ("GITHUB_RUN_ID", "777"),
+            ("GITHUB_WORKFLOW", "CI"),
+            ("GITHUB_JOB", "tests"),
+            ("GITHUB_REF_NAME", "15/merge"),
+            ("GITHUB_HEAD_REF", "feature/login"),
+            ("GITHUB_SHA", "abcdef1"),
+        ])
+        .unwrap();
+        assert_eq!(ci.provider, CiProvider::GithubActions);
+        assert_eq!(ci.job_name.as_deref(), Some("CI / tests"));
+        assert_eq!(
+            ci.links(),
+            vec![
+                ExternalLink::new("Job", "https://github.com/qa/api/actions/runs/777"),
+                ExternalLink::new("Commit", "https://github.com/qa/api/commit/abcdef1"),
+            ]
+        );
+        assert_eq!(
+            ci.tags(),
+            vec!["branch:feature/login", "commit:abcdef1", "pipeline:777"]
+        );
+    }
+
+    #[test]
+    fn test_detect_jenkins() {
+        let ci = detect(&[
+            ("JENKINS_URL", "https://jenkins.example.com/"),
+            ("JOB_NAME", "api-tests"),
+            ("BUILD_URL", "https://jenkins.example.com/job/api-tests/12/"),
+            ("BUILD_NUMBER", "12"),
+            ("GIT_BRANCH", "origin/develop"),
+            ("GIT_COMMIT", "fedcba9876543210"),
+        ])
+        .unwrap();
+        assert_eq!(ci.provider, CiProvider::Jenkins);
+        assert_eq!(
+            ci.links(),
+            vec![ExternalLink::new(
+                "Job",
+                "https://jenkins.example.com/job/api-tests/12/"
+            )]
+        );
+        assert_eq!(
+            ci.tags(),
+            vec!["branch:develop", "commit:fedcba98", "pipeline:12"]
+        );
+    }
+
+    #[test]
+    /// Без ссылки на сборку TeamCity добавляются только теги
+    fn test_detect_teamcity() {
+        let ci = detect(&[
+            ("TEAMCITY_VERSION", "2024.03"),
+            ("TEAMCITY_BUILDCONF_NAME", "Api tests"),
+            ("BUILD_NUMBER", "345"),
+            ("BUILD_VCS_NUMBER", "1234abcd5678"),
+        ])
+        .unwrap();
+        assert_eq!(ci.provider, CiProvider::Teamcity);
+        assert!(ci.links().is_empty());
+        assert_eq!(ci.tags(), vec!["commit:1234abcd", "pipeline:345"]);
+    }
+
+    #[test]
+    fn test_detect_without_ci() {
+        assert_eq!(detect(&[("HOME", "/root"), ("GITLAB_CI", "")]), None);
+    }
+
+    #[test]
+    fn test_add_report_files() {
+        let mut report_files = Vec::new();
+        gitlab()
+            .add_report_files(&mut report_files, "allure-results")
+            .unwrap();
+        let names: Vec<&str> = report_files
+            .iter()
+            .map(|report_file| report_file.name_in_archive.as_str())
+            .collect();
+        assert_eq!(names, vec![ENVIRONMENT_FILE, EXECUTOR_FILE]);
+        assert_eq!(
+            String::from_utf8(report_files[0].read().unwrap()).unwrap(),
+            "CI=GitLab CI\nJob=tests\nBranch=main\nCommit=0123456789abcdef0123456789abcdef01234567\nPipeline=1001\n"
+        );
+        let executor: serde_json::Value =
+            serde_json::from_slice(&report_files[1].read().unwrap()).unwrap();
+        assert_eq!(
+            executor,
+            serde_json::json!({
+                "name": "GitLab CI",
+                "type": "gitlab",
+                "buildName": "tests",
+                "buildUrl": "https://gitlab.example.com/qa/api/-/jobs/42",
+                "buildOrder": 1001,
+            })
+        );
+    }
+
+    #[test]
+    /// Файлы из отчета не перезаписываются
+    fn test_add_report_files_keeps_existing() {
+        let mut report_files = vec![ReportFile {
+            path: PathBuf::from("allure-results/executor.json"),
+            name_in_archive: EXECUTOR_FILE.to_string(),
+            size: 2,
+            content: Some(b"{}".to_vec()),
+        }];
+        gitlab()
+            .add_report_files(&mut report_files, "allure-results")
+            .unwrap();
+        assert_eq!(report_files.len(), 2);
+        assert_eq!(report_files[0].name_in_archive, ENVIRONMENT_FILE);
+        assert_eq!(report_files[1].read().unwrap(), b"{}");
+    }
+
+    #[test]
+    fn test_escape_property() {
+        assert_eq!(
+            escape_property("a=b:c\\d\ne#f!"),
+            "a\\=b\\:c\\\\d\\ne\\#f\\!"
+        );
+    }
+}
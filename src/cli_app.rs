@@ -1,7 +1,24 @@
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
+use crate::command_logic::config::{
+    add_profile, check_config, init_config, list_profiles, migrate_config, remove_profile,
+    set_config_value, show_config, show_repo_defaults, use_profile,
+};
+use crate::command_logic::project::ProjectRef;
+use crate::config::{validate_profile_name, Profile};
+use crate::converters::ReportFormat;
+use crate::external_api::network::NetworkSettings;
+use crate::external_api::testops_api::models::external_link::ExternalLink;
 use crate::external_api::{testops_api::TestopsApi, ApiError};
-use crate::{import_testcase_by_id, send_report};
+use crate::repo_defaults::RepoDefaults;
+use crate::utils::parse_byte_size;
+use crate::{
+    check_launch_gate, convert_report, import_testcase_by_id, list_projects, project_stats,
+    send_report, show_project, validate_report,
+};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Parser)]
@@ -16,24 +33,528 @@ use std::time::{SystemTime, UNIX_EPOCH};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    /// Profile of the TestOps instance from the config, by default the current one
+    #[arg(long, global = true, env = "WOT_PROFILE", value_parser = validate_profile_name)]
+    pub profile: Option<String>,
+    /// Path to the config file instead of the one in the home directory
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+}
+
+impl Cli {
+    /// The command works only with local files, the config and TestOps are not needed
+    pub fn is_offline(&self) -> bool {
+        match &self.command {
+            Commands::Report(report) => matches!(report.command, Some(ReportCommands::Validate(_))),
+            Commands::Convert(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Fill the arguments of the command from .wot.toml found from the directory up
+    pub fn apply_repo_defaults(&mut self, current_dir: &Path) -> Result<(), ApiError> {
+        let env_defaults = RepoDefaults::from_env(|name| std::env::var(name).ok())?;
+        match &mut self.command {
+            Commands::Report(report) if report.command.is_none() => {
+                report.upload.apply_defaults(&env_defaults);
+                report
+                    .upload
+                    .apply_defaults(&RepoDefaults::discover(current_dir)?);
+            }
+            Commands::Testcase(args) => args.apply_defaults(&RepoDefaults::discover(current_dir)?),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Network settings that override the config for one run
+#[derive(Args)]
+#[command(next_help_heading = "Network options")]
+pub struct NetworkArgs {
+    /// Max time to establish a connection in seconds
+    #[arg(long, global = true, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub connect_timeout: Option<u64>,
+    /// Max time to get the response of a request in seconds
+    #[arg(long, global = true, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub read_timeout: Option<u64>,
+    /// Max time to upload one archive in seconds
+    #[arg(long, global = true, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub upload_timeout: Option<u64>,
+    /// Proxy url for all requests
+    #[arg(long, global = true, value_name = "URL")]
+    pub proxy: Option<String>,
+    /// Comma separated hosts that are requested without the proxy
+    #[arg(long, global = true, value_name = "HOSTS")]
+    pub no_proxy: Option<String>,
+    /// PEM file with extra CA certificates
+    #[arg(long, global = true, value_name = "PATH")]
+    pub ca_bundle: Option<PathBuf>,
+    /// Don't verify the TLS certificate of the server
+    #[arg(long, global = true)]
+    pub insecure: bool,
+}
+
+impl NetworkArgs {
+    /// Settings from the config with the values given in the command line
+    pub fn override_settings(&self, mut settings: NetworkSettings) -> NetworkSettings {
+        if let Some(connect_timeout) = self.connect_timeout {
+            settings.connect_timeout_secs = connect_timeout;
+        }
+        if let Some(read_timeout) = self.read_timeout {
+            settings.read_timeout_secs = read_timeout;
+        }
+        if let Some(upload_timeout) = self.upload_timeout {
+            settings.upload_timeout_secs = upload_timeout;
+        }
+        if let Some(proxy) = &self.proxy {
+            settings.proxy = Some(proxy.clone());
+        }
+        if let Some(no_proxy) = &self.no_proxy {
+            settings.no_proxy = Some(no_proxy.clone());
+        }
+        if let Some(ca_bundle) = &self.ca_bundle {
+            settings.ca_bundle = Some(ca_bundle.clone());
+        }
+        settings.insecure |= self.insecure;
+        settings
+    }
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Uploading a report to TestOps
-    Report(ReportArgs),
+    Report(Box<ReportCommand>),
     /// Action with testcase
     Testcase(TestcaseArgs),
+    /// Action with launch
+    Launch(LaunchArgs),
+    /// Convert reports of other tools to allure results
+    Convert(ConvertArgs),
+    /// Action with project
+    Project(ProjectArgs),
+    /// Manage the config
+    Config(ConfigArgs),
+    /// Create the config without prompts
+    Init(InitArgs),
 }
 
 #[derive(Args)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct ReportCommand {
+    #[command(subcommand)]
+    pub command: Option<ReportCommands>,
+    #[command(flatten)]
+    pub upload: ReportArgs,
+}
+
+#[derive(Subcommand)]
+pub enum ReportCommands {
+    /// Check allure results in the directories without uploading
+    Validate(ValidateArgs),
+}
+
+#[derive(Args)]
+pub struct ValidateArgs {
+    /// Paths to directories, merged into one report
+    #[arg(required = true, value_name = "DIRECTORY_PATH")]
+    pub directory_paths: Vec<String>,
+}
+
+#[derive(Args)]
+#[command(group(
+    clap::ArgGroup::new("report_gate")
+        .args(["max_failed", "min_pass_rate", "fail_on"])
+        .multiple(true)
+        .requires("wait")
+))]
 pub struct ReportArgs {
-    /// Path to directory
-    #[arg(long, short, required = true)]
-    pub directory_path: String,
-    /// Allure project id
-    #[arg(long, short, requires = "directory_path", value_parser = validate_u32_more_then_zero)]
-    pub project_id: u32,
+    /// Path to directory (can be repeated to merge several reports into one launch),
+    /// by default `report.directories` from .wot.toml
+    #[arg(long = "directory-path", short = 'd', value_name = "DIRECTORY_PATH")]
+    pub directory_paths: Vec<String>,
+    /// Allure project id or name, the name may be partial if it matches one project,
+    /// by default WOT_PROJECT or `project` from .wot.toml
+    #[arg(
+        long,
+        short,
+        alias = "project-id",
+        value_name = "NAME_OR_ID",
+        value_parser = parse_project
+    )]
+    pub project: Option<ProjectRef>,
+    /// Pack only files matching the glob (relative to the directory, can be repeated)
+    #[arg(long, value_name = "GLOB")]
+    pub include: Vec<String>,
+    /// Skip files matching the glob (relative to the directory, can be repeated)
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+    /// Max size of files in one upload, larger reports are sent in several batches
+    #[arg(long, value_name = "SIZE", default_value = "512MB", value_parser = parse_byte_size)]
+    pub batch_size: u64,
+    /// Format of the test results in the directories
+    #[arg(long, value_enum, default_value_t = ReportFormat::Allure)]
+    pub format: ReportFormat,
+    /// Upload without checking allure results
+    #[arg(long)]
+    pub skip_validation: bool,
+    /// Upload without confirmation, required when stdin is not a terminal
+    #[arg(long, short)]
+    pub yes: bool,
+    /// Show the files and the launch that would be uploaded, without uploading
+    #[arg(long, conflicts_with = "wait")]
+    pub dry_run: bool,
+    /// Upload results into an existing launch of the project
+    #[arg(
+        long,
+        value_parser = validate_u32_more_then_zero,
+        conflicts_with_all = ["launch_name", "tags", "links", "autoclose"]
+    )]
+    pub launch_id: Option<u32>,
+    /// Launch name with optional {date}, {branch}, {commit} and {pipeline} placeholders,
+    /// by default "Run from <current date and time>"
+    #[arg(long, value_name = "NAME")]
+    pub launch_name: Option<String>,
+    /// Launch tag (can be repeated), replaces tags from .wot.toml
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+    /// Launch link in the form name=url (can be repeated)
+    #[arg(long = "link", value_name = "NAME=URL", value_parser = parse_launch_link)]
+    pub links: Vec<ExternalLink>,
+    /// Close the launch automatically after processing
+    #[arg(long)]
+    pub autoclose: bool,
+    /// Don't add CI job links, tags, executor.json and environment.properties
+    #[arg(long)]
+    pub no_ci_metadata: bool,
+    /// Wait until TestOps processes the results and print a summary
+    #[arg(long)]
+    pub wait: bool,
+    /// Max time to wait for processing in seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = 600, requires = "wait")]
+    pub wait_timeout: u64,
+    #[command(flatten)]
+    pub gate: GateRules,
+}
+
+/// Quality gate rules for the launch results
+#[derive(Args, Debug, Default)]
+pub struct GateRules {
+    /// Max allowed number of failed results
+    #[arg(long, value_name = "COUNT")]
+    pub max_failed: Option<u32>,
+    /// Min percent of passed results
+    #[arg(long, value_name = "PERCENT", value_parser = parse_pass_rate)]
+    pub min_pass_rate: Option<f64>,
+    /// Fail if the launch has results with the status (can be repeated)
+    #[arg(long, value_name = "STATUS", value_parser = ["failed", "broken", "skipped", "unknown"])]
+    pub fail_on: Vec<String>,
+}
+
+impl GateRules {
+    pub fn is_empty(&self) -> bool {
+        self.max_failed.is_none() && self.min_pass_rate.is_none() && self.fail_on.is_empty()
+    }
+}
+
+#[derive(Args)]
+pub struct ConvertArgs {
+    #[command(subcommand)]
+    pub command: ConvertCommands,
+}
+
+#[derive(Subcommand)]
+pub enum ConvertCommands {
+    /// Convert JUnit XML reports
+    Junit(ConvertInputArgs),
+    /// Convert Cucumber JSON reports
+    Cucumber(ConvertInputArgs),
+    /// Convert Visual Studio TRX reports
+    Trx(ConvertInputArgs),
+    /// Convert NUnit 3 XML reports
+    Nunit(ConvertInputArgs),
+}
+
+impl ConvertCommands {
+    pub fn format(&self) -> ReportFormat {
+        match self {
+            ConvertCommands::Junit(_) => ReportFormat::Junit,
+            ConvertCommands::Cucumber(_) => ReportFormat::Cucumber,
+            ConvertCommands::Trx(_) => ReportFormat::Trx,
+            ConvertCommands::Nunit(_) => ReportFormat::Nunit,
+        }
+    }
+
+    pub fn input(&self) -> &ConvertInputArgs {
+        match self {
+            ConvertCommands::Junit(input)
+            | ConvertCommands::Cucumber(input)
+            | ConvertCommands::Trx(input)
+            | ConvertCommands::Nunit(input) => input,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct ConvertInputArgs {
+    /// Report files or directories with them
+    #[arg(required = true, value_name = "PATH")]
+    pub paths: Vec<String>,
+    /// Directory to save allure results
+    #[arg(long, short, default_value = "allure-results")]
+    pub output_dir: String,
+}
+
+#[derive(Args)]
+pub struct ProjectArgs {
+    #[command(subcommand)]
+    pub command: ProjectCommands,
+}
+
+#[derive(Subcommand)]
+pub enum ProjectCommands {
+    /// List projects of the instance
+    List(ProjectListArgs),
+    /// Show project details
+    Show(ProjectShowArgs),
+    /// Show test case and launch statistics of the project
+    Stats(ProjectStatsArgs),
+}
+
+#[derive(Args)]
+pub struct InitArgs {
+    /// Url of the TestOps instance
+    #[arg(long)]
+    pub url: String,
+    /// File with the TestOps API token, `-` to read it from stdin
+    #[arg(long, value_name = "PATH")]
+    pub token_file: String,
+    /// Overwrite the url and the token of the existing config
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Args)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommands,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Manage profiles of TestOps instances
+    Profiles(ProfilesArgs),
+    /// Print the config, tokens are masked
+    Show,
+    /// Change a setting, e.g. `network.proxy` or `profiles.staging.testops_base_url`
+    Set(ConfigSetArgs),
+    /// Validate the url and the token and make a test request to TestOps
+    Check,
+    /// Rewrite an old config in the current format
+    Migrate,
+    /// Show defaults from .wot.toml and where each value comes from
+    Defaults(DefaultsArgs),
+}
+
+/// Flags of report and testcase commands, to show how they override the defaults
+#[derive(Args)]
+pub struct DefaultsArgs {
+    /// Project id or name
+    #[arg(long, short, value_name = "NAME_OR_ID", value_parser = parse_project)]
+    pub project: Option<ProjectRef>,
+    /// Path to directory with results (can be repeated)
+    #[arg(long = "directory-path", short = 'd', value_name = "DIRECTORY_PATH")]
+    pub directory_paths: Vec<String>,
+    /// Launch name
+    #[arg(long, value_name = "NAME")]
+    pub launch_name: Option<String>,
+    /// Launch tag (can be repeated)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+    /// Pytest mark of the generated test
+    #[arg(long, value_name = "MARK")]
+    pub mark: Option<String>,
+    /// Directory for the generated tests
+    #[arg(long, value_name = "DIRECTORY_PATH")]
+    pub output_dir: Option<String>,
+}
+
+impl DefaultsArgs {
+    /// Values given by the flags, in the shape of the defaults
+    pub fn flag_values(&self) -> RepoDefaults {
+        RepoDefaults {
+            path: None,
+            project: self.project.clone(),
+            directories: self.directory_paths.clone(),
+            launch_name: self.launch_name.clone(),
+            tags: self.tags.clone(),
+            testcase_mark: self.mark.clone(),
+            testcase_output_dir: self.output_dir.clone(),
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct ConfigSetArgs {
+    /// Dotted key of the setting
+    pub key: String,
+    /// New value, json or a plain string, empty to remove an optional setting
+    pub value: String,
+}
+
+#[derive(Args)]
+pub struct ProfilesArgs {
+    #[command(subcommand)]
+    pub command: ProfileCommands,
+}
+
+#[derive(Subcommand)]
+pub enum ProfileCommands {
+    /// Add a profile, the token is asked if not given
+    Add(ProfileAddArgs),
+    /// List profiles, the one in use is marked with *
+    List,
+    /// Remove a profile
+    Remove(ProfileNameArgs),
+    /// Use the profile by default
+    Use(ProfileNameArgs),
+}
+
+#[derive(Args)]
+pub struct ProfileAddArgs {
+    /// Profile name
+    #[arg(value_parser = validate_profile_name)]
+    pub name: String,
+    /// Url of the TestOps instance
+    #[arg(long)]
+    pub url: String,
+    /// TestOps API token
+    #[arg(long)]
+    pub token: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ProfileNameArgs {
+    /// Profile name
+    #[arg(value_parser = validate_profile_name)]
+    pub name: String,
+}
+
+/// Format of the command output
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    // Без doc-комментариев, иначе `--help` и `-h` выводят значения по-разному
+    #[default]
+    Table,
+    Json,
+}
+
+#[derive(Args)]
+pub struct ProjectListArgs {
+    /// Request projects from TestOps instead of the cached list
+    #[arg(long)]
+    pub refresh: bool,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args)]
+pub struct ProjectShowArgs {
+    /// Project id or name
+    #[arg(value_name = "NAME_OR_ID", value_parser = parse_project)]
+    pub project: ProjectRef,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args)]
+pub struct ProjectStatsArgs {
+    /// Project id or name
+    #[arg(value_name = "NAME_OR_ID", value_parser = parse_project)]
+    pub project: ProjectRef,
+    /// Count launches created in the last DAYS days
+    #[arg(
+        long,
+        value_name = "DAYS",
+        default_value_t = 30,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub days: u32,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args)]
+pub struct LaunchArgs {
+    #[command(subcommand)]
+    pub command: LaunchCommands,
+}
+
+#[derive(Subcommand)]
+pub enum LaunchCommands {
+    /// Check the launch results against quality gate rules
+    Gate(LaunchGateArgs),
+}
+
+#[derive(Args)]
+pub struct LaunchGateArgs {
+    /// Launch id
+    #[arg(value_parser = validate_u32_more_then_zero)]
+    pub launch_id: u32,
+    #[command(flatten)]
+    pub gate: GateRules,
+}
+
+impl ReportArgs {
+    #[cfg(test)]
+    pub fn new_test(directory_path: &str, project_id: u32) -> Self {
+        Self {
+            directory_paths: vec![directory_path.to_string()],
+            project: Some(ProjectRef::Id(project_id)),
+            include: vec![],
+            exclude: vec![],
+            batch_size: 512 * 1024 * 1024,
+            format: ReportFormat::Allure,
+            skip_validation: false,
+            dry_run: false,
+            yes: false,
+            launch_id: None,
+            launch_name: None,
+            tags: vec![],
+            links: vec![],
+            autoclose: false,
+            // Тесты не должны зависеть от CI, в котором запускаются
+            no_ci_metadata: true,
+            wait: false,
+            wait_timeout: 600,
+            gate: GateRules::default(),
+        }
+    }
+
+    /// Fill the values not given in the command line from .wot.toml
+    ///
+    /// Launch name and tags are skipped for an existing launch, they can't be used with it
+    pub fn apply_defaults(&mut self, defaults: &RepoDefaults) {
+        if self.directory_paths.is_empty() {
+            self.directory_paths = defaults.directories.clone();
+        }
+        if self.project.is_none() {
+            self.project = defaults.project.clone();
+        }
+        if self.launch_id.is_none() {
+            if self.launch_name.is_none() {
+                self.launch_name = defaults.launch_name.clone();
+            }
+            if self.tags.is_empty() {
+                self.tags = defaults.tags.clone();
+            }
+        }
+    }
 }
 
 #[derive(Args)]
@@ -44,6 +565,12 @@ pub struct TestcaseArgs {
     /// Use the file name entered by the user
     #[arg(long, short, requires = "import_testcase_id", value_parser = validate_test_file_name)]
     pub filename: Option<String>,
+    /// Pytest mark of the test, by default `testcase.mark` from .wot.toml
+    #[arg(long, value_name = "MARK")]
+    pub mark: Option<String>,
+    /// Directory for the test file, by default `testcase.output_dir` from .wot.toml
+    #[arg(long, value_name = "DIRECTORY_PATH")]
+    pub output_dir: Option<String>,
 }
 
 impl TestcaseArgs {
@@ -61,11 +588,23 @@ impl TestcaseArgs {
         format!("test_{}_{}.py", timestamp, self.import_testcase_id)
     }
 
+    /// Fill the values not given in the command line from .wot.toml
+    pub fn apply_defaults(&mut self, defaults: &RepoDefaults) {
+        if self.mark.is_none() {
+            self.mark = defaults.testcase_mark.clone();
+        }
+        if self.output_dir.is_none() {
+            self.output_dir = defaults.testcase_output_dir.clone();
+        }
+    }
+
     #[cfg(test)]
     pub fn new_test(import_testcase_id: u32, filename: Option<String>) -> Self {
         Self {
             import_testcase_id,
             filename,
+            mark: None,
+            output_dir: None,
         }
     }
 }
@@ -97,34 +636,201 @@ fn validate_test_file_name(value: &str) -> Result<String, ApiError> {
     Ok(value.to_string())
 }
 
+fn parse_pass_rate(value: &str) -> Result<f64, ApiError> {
+    let pass_rate: f64 = value
+        .parse()
+        .map_err(|_| ApiError::InvalidPassRate(value.to_string()))?;
+    if !(0.0..=100.0).contains(&pass_rate) {
+        return Err(ApiError::InvalidPassRate(value.to_string()));
+    }
+    Ok(pass_rate)
+}
+
+/// Project id if the value is a number, otherwise project name
+pub fn parse_project(value: &str) -> Result<ProjectRef, ApiError> {
+    let value = value.trim();
+    if value.is_empty() || value.chars().all(|c| c.is_ascii_digit()) {
+        return validate_u32_more_then_zero(value).map(ProjectRef::Id);
+    }
+    Ok(ProjectRef::Name(value.to_string()))
+}
+
+fn parse_launch_link(value: &str) -> Result<ExternalLink, ApiError> {
+    let (name, url) = value
+        .split_once('=')
+        .map(|(name, url)| (name.trim(), url.trim()))
+        .ok_or_else(|| ApiError::InvalidLaunchLink(value.to_string()))?;
+    if name.is_empty() || reqwest::Url::parse(url).is_err() {
+        return Err(ApiError::InvalidLaunchLink(value.to_string()));
+    }
+    Ok(ExternalLink::new(name, url))
+}
+
 pub async fn handle_command(
     cli: Cli,
     testops_api: &TestopsApi,
     stdin: std::io::Stdin,
     stdout: std::io::Stdout,
-) {
+) -> ExitCode {
     match &cli.command {
-        Commands::Report(value) => {
-            match send_report(
-                &value.directory_path,
-                value.project_id,
-                testops_api,
-                stdin.lock(),
-                stdout,
-            )
-            .await
-            {
-                Ok(value) => println!("{}", value),
-                Err(e) => eprintln!("Failed to send report: {}", e),
-            };
-        }
+        Commands::Report(value) => match &value.command {
+            Some(ReportCommands::Validate(_)) => {
+                unreachable!("offline commands are handled before the config is loaded")
+            }
+            None => {
+                let is_interactive = stdin.is_terminal();
+                match send_report(
+                    &value.upload,
+                    is_interactive,
+                    testops_api,
+                    stdin.lock(),
+                    stdout,
+                )
+                .await
+                {
+                    Ok(value) => println!("{}", value),
+                    Err(e) => {
+                        eprintln!("Failed to send report: {}", e);
+                        return ExitCode::from(e.exit_code());
+                    }
+                };
+            }
+        },
         Commands::Testcase(value) => {
             match import_testcase_by_id(value, testops_api).await {
                 Ok(value) => println!("{}", value),
-                Err(e) => eprintln!("Failed to import testcase by id: {}", e),
+                Err(e) => {
+                    eprintln!("Failed to import testcase by id: {}", e);
+                    return ExitCode::from(e.exit_code());
+                }
             };
         }
+        Commands::Convert(_) => {
+            unreachable!("offline commands are handled before the config is loaded")
+        }
+        Commands::Launch(value) => match &value.command {
+            LaunchCommands::Gate(args) => {
+                match check_launch_gate(args, testops_api, stdout).await {
+                    Ok(value) => println!("{}", value),
+                    Err(e) => {
+                        eprintln!("Failed to check launch: {}", e);
+                        return ExitCode::from(e.exit_code());
+                    }
+                };
+            }
+        },
+        Commands::Project(value) => match &value.command {
+            ProjectCommands::List(args) => {
+                match list_projects(args, testops_api).await {
+                    Ok(value) => println!("{}", value),
+                    Err(e) => {
+                        eprintln!("Failed to list projects: {}", e);
+                        return ExitCode::from(e.exit_code());
+                    }
+                };
+            }
+            ProjectCommands::Show(args) => {
+                match show_project(args, testops_api).await {
+                    Ok(value) => println!("{}", value),
+                    Err(e) => {
+                        eprintln!("Failed to show project: {}", e);
+                        return ExitCode::from(e.exit_code());
+                    }
+                };
+            }
+            ProjectCommands::Stats(args) => {
+                match project_stats(args, testops_api).await {
+                    Ok(value) => println!("{}", value),
+                    Err(e) => {
+                        eprintln!("Failed to get project statistics: {}", e);
+                        return ExitCode::from(e.exit_code());
+                    }
+                };
+            }
+        },
+        Commands::Config(_) | Commands::Init(_) => {
+            unreachable!("config commands are handled before the api client is created")
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/// Check the config of the profile with a request to TestOps
+pub async fn handle_check_command(profile: &Profile, testops_api: &TestopsApi) -> ExitCode {
+    match check_config(profile, testops_api).await {
+        Ok(value) => println!("{}", value),
+        Err(e) => {
+            eprintln!("Config check failed: {}", e);
+            return ExitCode::from(e.exit_code());
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/// Run the command that works only with local files, without the config and TestOps
+pub fn handle_offline_command(cli: &Cli, stdout: std::io::Stdout) -> ExitCode {
+    match &cli.command {
+        Commands::Report(value) => match &value.command {
+            Some(ReportCommands::Validate(args)) => match validate_report(args, stdout) {
+                Ok(value) => println!("{}", value),
+                Err(e) => {
+                    eprintln!("Failed to validate report: {}", e);
+                    return ExitCode::from(e.exit_code());
+                }
+            },
+            None => unreachable!("report upload needs the api client"),
+        },
+        Commands::Convert(value) => match convert_report(&value.command) {
+            Ok(value) => println!("{}", value),
+            Err(e) => {
+                eprintln!("Failed to convert report: {}", e);
+                return ExitCode::from(e.exit_code());
+            }
+        },
+        _ => unreachable!("only offline commands are handled without the config"),
+    }
+    ExitCode::SUCCESS
+}
+
+/// Run the config command, it doesn't need a connection to TestOps
+pub fn handle_config_command(
+    cli: &Cli,
+    config_path: &Path,
+    stdin: std::io::Stdin,
+    stdout: std::io::Stdout,
+) -> ExitCode {
+    let result = match &cli.command {
+        Commands::Init(args) => init_config(args, config_path, stdin.lock()),
+        Commands::Config(config_args) => match &config_args.command {
+            ConfigCommands::Profiles(value) => match &value.command {
+                ProfileCommands::Add(args) => add_profile(args, config_path, stdin.lock(), stdout),
+                ProfileCommands::List => list_profiles(config_path, cli.profile.as_deref()),
+                ProfileCommands::Remove(args) => remove_profile(&args.name, config_path),
+                ProfileCommands::Use(args) => use_profile(&args.name, config_path),
+            },
+            ConfigCommands::Show => show_config(config_path),
+            ConfigCommands::Set(args) => set_config_value(&args.key, &args.value, config_path),
+            ConfigCommands::Migrate => migrate_config(config_path),
+            ConfigCommands::Defaults(args) => std::env::current_dir()
+                .map_err(ApiError::from)
+                .and_then(|current_dir| {
+                    let env_defaults = RepoDefaults::from_env(|name| std::env::var(name).ok())?;
+                    show_repo_defaults(&current_dir, &args.flag_values(), &env_defaults)
+                }),
+            ConfigCommands::Check => {
+                unreachable!("config check needs the api client")
+            }
+        },
+        _ => unreachable!("only config commands are handled without the api client"),
+    };
+    match result {
+        Ok(value) => println!("{}", value),
+        Err(e) => {
+            eprintln!("Config command failed: {}", e);
+            return ExitCode::from(e.exit_code());
+        }
     }
+    ExitCode::SUCCESS
 }
 
 #[cfg(test)]
@@ -139,37 +845,132 @@ mod tests {
     const LEN_AND_PREFIX_ERROR: &str = "len must be between 6 and 120 and start with \"test_\"";
     const MAIN_HELP: &str = r#"CLI application for Allure TestOps <https://qameta.io/>. wot - WrapperOverTestops
 
-Usage: wot <COMMAND>
+Usage: wot [OPTIONS] <COMMAND>
 
 Commands:
   report    Uploading a report to TestOps
   testcase  Action with testcase
+  launch    Action with launch
+  convert   Convert reports of other tools to allure results
+  project   Action with project
+  config    Manage the config
+  init      Create the config without prompts
   help      Print this message or the help of the given subcommand(s)
 
 Options:
-  -h, --help     Print help
-  -V, --version  Print version
+      --profile <PROFILE>  Profile of the TestOps instance from the config, by default the current one [env: WOT_PROFILE=]
+      --config <PATH>      Path to the config file instead of the one in the home directory
+  -h, --help               Print help
+  -V, --version            Print version
+
+Network options:
+      --connect-timeout <SECONDS>  Max time to establish a connection in seconds
+      --read-timeout <SECONDS>     Max time to get the response of a request in seconds
+      --upload-timeout <SECONDS>   Max time to upload one archive in seconds
+      --proxy <URL>                Proxy url for all requests
+      --no-proxy <HOSTS>           Comma separated hosts that are requested without the proxy
+      --ca-bundle <PATH>           PEM file with extra CA certificates
+      --insecure                   Don't verify the TLS certificate of the server
 "#;
 
     const REPORT_HELP: &str = r#"Uploading a report to TestOps
 
-Usage: wot report --directory-path <DIRECTORY_PATH> --project-id <PROJECT_ID>
+Usage: wot report [OPTIONS]
+       wot report <COMMAND>
+
+Commands:
+  validate  Check allure results in the directories without uploading
+  help      Print this message or the help of the given subcommand(s)
 
 Options:
-  -d, --directory-path <DIRECTORY_PATH>  Path to directory
-  -p, --project-id <PROJECT_ID>          Allure project id
-  -h, --help                             Print help
-  -V, --version                          Print version
+  -d, --directory-path <DIRECTORY_PATH>
+          Path to directory (can be repeated to merge several reports into one launch), by default `report.directories` from .wot.toml
+  -p, --project <NAME_OR_ID>
+          Allure project id or name, the name may be partial if it matches one project, by default WOT_PROJECT or `project` from .wot.toml
+      --include <GLOB>
+          Pack only files matching the glob (relative to the directory, can be repeated)
+      --exclude <GLOB>
+          Skip files matching the glob (relative to the directory, can be repeated)
+      --batch-size <SIZE>
+          Max size of files in one upload, larger reports are sent in several batches [default: 512MB]
+      --format <FORMAT>
+          Format of the test results in the directories [default: allure] [possible values: allure, junit, cucumber, trx, nunit]
+      --profile <PROFILE>
+          Profile of the TestOps instance from the config, by default the current one [env: WOT_PROFILE=]
+      --skip-validation
+          Upload without checking allure results
+      --config <PATH>
+          Path to the config file instead of the one in the home directory
+  -y, --yes
+          Upload without confirmation, required when stdin is not a terminal
+      --dry-run
+          Show the files and the launch that would be uploaded, without uploading
+      --launch-id <LAUNCH_ID>
+          Upload results into an existing launch of the project
+      --launch-name <NAME>
+          Launch name with optional {date}, {branch}, {commit} and {pipeline} placeholders, by default "Run from <current date and time>"
+      --tag <TAG>
+          Launch tag (can be repeated), replaces tags from .wot.toml
+      --link <NAME=URL>
+          Launch link in the form name=url (can be repeated)
+      --autoclose
+          Close the launch automatically after processing
+      --no-ci-metadata
+          Don't add CI job links, tags, executor.json and environment.properties
+      --wait
+          Wait until TestOps processes the results and print a summary
+      --wait-timeout <SECONDS>
+          Max time to wait for processing in seconds [default: 600]
+      --max-failed <COUNT>
+          Max allowed number of failed results
+      --min-pass-rate <PERCENT>
+          Min percent of passed results
+      --fail-on <STATUS>
+          Fail if the launch has results with the status (can be repeated) [possible values: failed, broken, skipped, unknown]
+  -h, --help
+          Print help
+  -V, --version
+          Print version
+
+Network options:
+      --connect-timeout <SECONDS>  Max time to establish a connection in seconds
+      --read-timeout <SECONDS>     Max time to get the response of a request in seconds
+      --upload-timeout <SECONDS>   Max time to upload one archive in seconds
+      --proxy <URL>                Proxy url for all requests
+      --no-proxy <HOSTS>           Comma separated hosts that are requested without the proxy
+      --ca-bundle <PATH>           PEM file with extra CA certificates
+      --insecure                   Don't verify the TLS certificate of the server
 "#;
     const TESTCASE_HELP: &str = r#"Action with testcase
 
 Usage: wot testcase [OPTIONS] --import-testcase-id <IMPORT_TESTCASE_ID>
 
 Options:
-  -i, --import-testcase-id <IMPORT_TESTCASE_ID>  Import testcase
-  -f, --filename <FILENAME>                      Use the file name entered by the user
-  -h, --help                                     Print help
-  -V, --version                                  Print version
+  -i, --import-testcase-id <IMPORT_TESTCASE_ID>
+          Import testcase
+  -f, --filename <FILENAME>
+          Use the file name entered by the user
+      --mark <MARK>
+          Pytest mark of the test, by default `testcase.mark` from .wot.toml
+      --output-dir <DIRECTORY_PATH>
+          Directory for the test file, by default `testcase.output_dir` from .wot.toml
+      --profile <PROFILE>
+          Profile of the TestOps instance from the config, by default the current one [env: WOT_PROFILE=]
+      --config <PATH>
+          Path to the config file instead of the one in the home directory
+  -h, --help
+          Print help
+  -V, --version
+          Print version
+
+Network options:
+      --connect-timeout <SECONDS>  Max time to establish a connection in seconds
+      --read-timeout <SECONDS>     Max time to get the response of a request in seconds
+      --upload-timeout <SECONDS>   Max time to upload one archive in seconds
+      --proxy <URL>                Proxy url for all requests
+      --no-proxy <HOSTS>           Comma separated hosts that are requested without the proxy
+      --ca-bundle <PATH>           PEM file with extra CA certificates
+      --insecure                   Don't verify the TLS certificate of the server
 "#;
 
     #[fixture]
@@ -177,6 +978,14 @@ Options:
         assert_cmd::Command::cargo_bin("wot").expect("Failed to find wot binary")
     }
 
+    /// Аргументы загрузки отчета из разобранной команды
+    fn upload_args(args: Cli) -> ReportArgs {
+        match args.command {
+            Commands::Report(command) => command.upload,
+            _ => panic!("Expected report command"),
+        }
+    }
+
     #[rstest]
     #[case("")]
     #[case("test_")]
@@ -261,15 +1070,758 @@ Options:
     fn test_report_command_positive(#[case] dir_path: String, #[case] flag: String) {
         let args = Cli::try_parse_from(["wot", "report", &flag, &dir_path, "-p", "777"])
             .expect("Failed to parse arguments");
-        match args.command {
-            Commands::Report(value) => {
-                assert_eq!(value.directory_path, dir_path);
-                assert_eq!(value.project_id, 777);
+        let value = upload_args(args);
+        assert_eq!(value.directory_paths, vec![dir_path]);
+        assert_eq!(value.project, Some(ProjectRef::Id(777)));
+    }
+
+    #[test]
+    fn test_report_command_include_exclude() {
+        let args = Cli::try_parse_from([
+            "wot",
+            "report",
+            "-d",
+            "some_dir",
+            "-p",
+            "777",
+            "--include",
+            "**/*.json",
+            "--include",
+            "attachments/**",
+            "--exclude",
+            "**/*.mp4",
+        ])
+        .expect("Failed to parse arguments");
+        let value = upload_args(args);
+        assert_eq!(value.include, vec!["**/*.json", "attachments/**"]);
+        assert_eq!(value.exclude, vec!["**/*.mp4"]);
+    }
+
+    #[rstest]
+    #[case(vec![], 512 * 1024 * 1024)]
+    #[case(vec!["--batch-size", "100MB"], 100 * 1024 * 1024)]
+    fn test_report_command_batch_size(#[case] extra_args: Vec<&str>, #[case] expected: u64) {
+        let mut cli_args = vec!["wot", "report", "-d", "some_dir", "-p", "777"];
+        cli_args.extend(extra_args);
+        let args = Cli::try_parse_from(cli_args).expect("Failed to parse arguments");
+        let value = upload_args(args);
+        assert_eq!(value.batch_size, expected);
+    }
+
+    #[rstest]
+    #[case(vec![], false)]
+    #[case(vec!["-y"], true)]
+    #[case(vec!["--yes"], true)]
+    fn test_report_command_yes(#[case] extra_args: Vec<&str>, #[case] expected: bool) {
+        let mut cli_args = vec!["wot", "report", "-d", "some_dir", "-p", "777"];
+        cli_args.extend(extra_args);
+        let args = Cli::try_parse_from(cli_args).expect("Failed to parse arguments");
+        let value = upload_args(args);
+        assert_eq!(value.yes, expected);
+    }
+
+    #[rstest]
+    #[case(vec![], false)]
+    #[case(vec!["--no-ci-metadata"], true)]
+    fn test_report_command_no_ci_metadata(#[case] extra_args: Vec<&str>, #[case] expected: bool) {
+        let mut cli_args = vec!["wot", "report", "-d", "some_dir", "-p", "777"];
+        cli_args.extend(extra_args);
+        let args = Cli::try_parse_from(cli_args).expect("Failed to parse arguments");
+        assert_eq!(upload_args(args).no_ci_metadata, expected);
+    }
+
+    #[test]
+    /// Сетевые флаги глобальные и переопределяют только заданные значения конфига
+    fn test_network_args_override_settings() {
+        let args = Cli::try_parse_from([
+            "wot",
+            "project",
+            "list",
+            "--connect-timeout",
+            "3",
+            "--proxy",
+            "http://proxy.local:3128",
+            "--ca-bundle",
+            "ca.pem",
+            "--insecure",
+        ])
+        .expect("Failed to parse arguments");
+        let config_settings = NetworkSettings {
+            read_timeout_secs: 30,
+            no_proxy: Some("localhost".to_string()),
+            ..NetworkSettings::default()
+        };
+        assert_eq!(
+            args.network.override_settings(config_settings),
+            NetworkSettings {
+                connect_timeout_secs: 3,
+                read_timeout_secs: 30,
+                upload_timeout_secs: 600,
+                proxy: Some("http://proxy.local:3128".to_string()),
+                no_proxy: Some("localhost".to_string()),
+                ca_bundle: Some(PathBuf::from("ca.pem")),
+                insecure: true,
             }
-            _ => {}
+        );
+    }
+
+    #[rstest]
+    #[case("--connect-timeout")]
+    #[case("--read-timeout")]
+    #[case("--upload-timeout")]
+    fn test_network_args_zero_timeout(#[case] flag: &str) {
+        assert!(Cli::try_parse_from(["wot", "project", "list", flag, "0"]).is_err());
+    }
+
+    #[test]
+    fn test_profile_flag() {
+        let args = Cli::try_parse_from(["wot", "project", "list", "--profile", "staging"])
+            .expect("Failed to parse arguments");
+        assert_eq!(args.profile.as_deref(), Some("staging"));
+        assert!(
+            Cli::try_parse_from(["wot", "--profile", "my profile", "project", "list"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_config_profiles_add_command() {
+        let args = Cli::try_parse_from([
+            "wot",
+            "config",
+            "profiles",
+            "add",
+            "staging",
+            "--url",
+            "https://staging.example.com",
+        ])
+        .expect("Failed to parse arguments");
+        let Commands::Config(config_args) = args.command else {
+            panic!("expected config command");
+        };
+        let ConfigCommands::Profiles(profiles_args) = config_args.command else {
+            panic!("expected profiles command");
+        };
+        let ProfileCommands::Add(add_args) = profiles_args.command else {
+            panic!("expected add command");
+        };
+        assert_eq!(add_args.name, "staging");
+        assert_eq!(add_args.url, "https://staging.example.com");
+        assert_eq!(add_args.token, None);
+    }
+
+    #[test]
+    fn test_config_set_command() {
+        let args = Cli::try_parse_from(["wot", "config", "set", "network.proxy", ""])
+            .expect("Failed to parse arguments");
+        let Commands::Config(config_args) = args.command else {
+            panic!("expected config command");
+        };
+        let ConfigCommands::Set(set_args) = config_args.command else {
+            panic!("expected set command");
+        };
+        assert_eq!(set_args.key, "network.proxy");
+        assert_eq!(set_args.value, "");
+        // Без значения команда не разбирается
+        assert!(Cli::try_parse_from(["wot", "config", "set", "network.proxy"]).is_err());
+    }
+
+    #[rstest]
+    /// config show работает без TestOps и не выводит токен
+    fn test_config_show(mut cli_command: assert_cmd::Command) {
+        let config_dir = std::env::temp_dir().join(format!("wot_config_{}", uuid::Uuid::new_v4()));
+        let config_path = config_dir.join("wot.json");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::write(
+            &config_path,
+            r#"{"testops_base_url": "https://testops.example.com", "testops_api_token": "c4e42f15-5b22-46ae-b2a1-10b5e2ffcb14"}"#,
+        )
+        .unwrap();
+        cli_command
+            .args(["config", "show", "--config"])
+            .arg(&config_path)
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(
+                r#""testops_api_token": "****cb14""#,
+            ))
+            .stdout(predicates::str::contains(r#""version": 1"#));
+        std::fs::remove_dir_all(&config_dir).unwrap();
+    }
+
+    #[rstest]
+    /// Профиль из WOT_PROFILE используется без флага, неизвестный профиль - ошибка
+    fn test_profile_from_env(mut cli_command: assert_cmd::Command) {
+        let home_dir = std::env::temp_dir().join(format!("wot_home_{}", uuid::Uuid::new_v4()));
+        let config_dir = home_dir.join(".config/wot");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::write(
+            config_dir.join("test_config.json"),
+            r#"{
+                "testops_base_url": "https://prod.example.com",
+                "testops_api_token": "token",
+                "profiles": {
+                    "staging": {
+                        "testops_base_url": "https://staging.example.com",
+                        "testops_api_token": "token"
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        cli_command
+            .env("HOME", &home_dir)
+            .env("WOT_PROFILE", "staging")
+            .args(["config", "profiles", "list"])
+            .write_stdin("")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(
+                "*  staging  https://staging.example.com",
+            ));
+        assert_cmd::Command::cargo_bin("wot")
+            .expect("Failed to find wot binary")
+            .env("HOME", &home_dir)
+            .env("WOT_PROFILE", "local")
+            .args(["project", "list"])
+            .write_stdin("")
+            .assert()
+            .code(1)
+            .stderr(predicates::str::contains("Profile <local> not found"));
+        std::fs::remove_dir_all(&home_dir).unwrap();
+    }
+
+    #[test]
+    fn test_report_command_dry_run() {
+        let args =
+            Cli::try_parse_from(["wot", "report", "-d", "some_dir", "-p", "777", "--dry-run"])
+                .expect("Failed to parse arguments");
+        assert!(upload_args(args).dry_run);
+        // Без загрузки ждать обработки запуска нечего
+        let result = Cli::try_parse_from([
+            "wot",
+            "report",
+            "-d",
+            "some_dir",
+            "-p",
+            "777",
+            "--dry-run",
+            "--wait",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    /// Без конфига и без терминала визард не запускается, а команда падает с отдельным кодом
+    fn test_missing_config_without_terminal(mut cli_command: assert_cmd::Command) {
+        let home_dir = std::env::temp_dir().join(format!("wot_home_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&home_dir).unwrap();
+        cli_command
+            .env("HOME", &home_dir)
+            .env_remove("WOT_TESTOPS_URL")
+            .env_remove("WOT_TESTOPS_TOKEN")
+            .args(["report", "-d", "some_dir", "-p", "1", "--yes"])
+            .write_stdin("")
+            .assert()
+            .code(3)
+            .stderr(predicates::str::contains("Config file not found at path"));
+        std::fs::remove_dir_all(&home_dir).unwrap();
+    }
+
+    #[rstest]
+    /// Url и токен из переменных окружения заменяют конфиг
+    fn test_config_from_env_without_file(mut cli_command: assert_cmd::Command) {
+        let home_dir = std::env::temp_dir().join(format!("wot_home_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&home_dir).unwrap();
+        cli_command
+            .env("HOME", &home_dir)
+            .env("WOT_TESTOPS_URL", "https://testops.example.com")
+            .env("WOT_TESTOPS_TOKEN", "c4e42f15-5b22-46ae-b2a1-10b5e2ffcb14")
+            .args(["report", "validate", home_dir.to_str().unwrap()])
+            .write_stdin("")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Report is valid"));
+        std::fs::remove_dir_all(&home_dir).unwrap();
+    }
+
+    #[rstest]
+    /// wot init создает конфиг по пути из --config без вопросов
+    fn test_init_with_config_path(mut cli_command: assert_cmd::Command) {
+        let config_dir = std::env::temp_dir().join(format!("wot_init_{}", uuid::Uuid::new_v4()));
+        let config_path = config_dir.join("wot.json");
+        cli_command
+            .args([
+                "init",
+                "--url",
+                "https://testops.example.com",
+                "--token-file",
+                "-",
+                "--config",
+                config_path.to_str().unwrap(),
+            ])
+            .write_stdin("c4e42f15-5b22-46ae-b2a1-10b5e2ffcb14\n")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Config created at"));
+        assert_cmd::Command::cargo_bin("wot")
+            .expect("Failed to find wot binary")
+            .args(["config", "profiles", "list", "--config"])
+            .arg(&config_path)
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("https://testops.example.com"));
+        std::fs::remove_dir_all(&config_dir).unwrap();
+    }
+
+    #[test]
+    fn test_init_command() {
+        let args = Cli::try_parse_from([
+            "wot",
+            "init",
+            "--url",
+            "https://testops.example.com",
+            "--token-file",
+            "token.txt",
+        ])
+        .expect("Failed to parse arguments");
+        if let Commands::Init(value) = args.command {
+            assert_eq!(value.url, "https://testops.example.com");
+            assert_eq!(value.token_file, "token.txt");
+            assert!(!value.force);
+        } else {
+            panic!("Expected init command");
+        }
+        // Без файла с токеном init не работает
+        assert!(Cli::try_parse_from(["wot", "init", "--url", "https://example.com"]).is_err());
+    }
+
+    #[test]
+    fn test_report_command_launch_metadata() {
+        let args = Cli::try_parse_from([
+            "wot",
+            "report",
+            "-d",
+            "some_dir",
+            "-p",
+            "777",
+            "--launch-name",
+            "Nightly",
+            "--tag",
+            "smoke",
+            "--tag",
+            "main",
+            "--link",
+            "Pipeline=https://gitlab.example.com/pipelines/1",
+            "--autoclose",
+        ])
+        .expect("Failed to parse arguments");
+        let value = upload_args(args);
+        assert_eq!(value.launch_name.as_deref(), Some("Nightly"));
+        assert_eq!(value.tags, vec!["smoke", "main"]);
+        assert_eq!(
+            value.links,
+            vec![ExternalLink::new(
+                "Pipeline",
+                "https://gitlab.example.com/pipelines/1"
+            )]
+        );
+        assert!(value.autoclose);
+    }
+
+    #[rstest]
+    #[case(
+        "CI=https://ci.example.com/job/1",
+        "CI",
+        "https://ci.example.com/job/1"
+    )]
+    #[case(
+        " CI = https://ci.example.com?a=b ",
+        "CI",
+        "https://ci.example.com?a=b"
+    )]
+    fn test_parse_launch_link(#[case] value: &str, #[case] name: &str, #[case] url: &str) {
+        assert_eq!(
+            parse_launch_link(value).unwrap(),
+            ExternalLink::new(name, url)
+        );
+    }
+
+    #[rstest]
+    #[case("https://ci.example.com")]
+    #[case("=https://ci.example.com")]
+    #[case("CI=not a url")]
+    #[case("CI=")]
+    fn test_parse_launch_link_invalid(#[case] value: &str) {
+        assert!(matches!(
+            parse_launch_link(value),
+            Err(ApiError::InvalidLaunchLink(_))
+        ));
+    }
+
+    #[rstest]
+    #[case("777", ProjectRef::Id(777))]
+    #[case(" 12 ", ProjectRef::Id(12))]
+    #[case("Mobile iOS", ProjectRef::Name("Mobile iOS".to_string()))]
+    #[case("web-2", ProjectRef::Name("web-2".to_string()))]
+    fn test_parse_project(#[case] value: &str, #[case] expected: ProjectRef) {
+        assert_eq!(parse_project(value).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("0")]
+    #[case("")]
+    #[case("4294967296")]
+    fn test_parse_project_invalid(#[case] value: &str) {
+        assert!(parse_project(value).is_err());
+    }
+
+    #[rstest]
+    #[case("--project")]
+    #[case("--project-id")]
+    fn test_report_command_project_name(#[case] flag: &str) {
+        let args = Cli::try_parse_from(["wot", "report", "-d", "some_dir", flag, "Mobile"])
+            .expect("Failed to parse arguments");
+        let value = upload_args(args);
+        assert_eq!(value.project, Some(ProjectRef::Name("Mobile".to_string())));
+    }
+
+    #[test]
+    fn test_project_list_command() {
+        let args = Cli::try_parse_from(["wot", "project", "list", "--format", "json", "--refresh"])
+            .expect("Failed to parse arguments");
+        if let Commands::Project(ProjectArgs {
+            command: ProjectCommands::List(value),
+        }) = args.command
+        {
+            assert_eq!(value.format, OutputFormat::Json);
+            assert!(value.refresh);
+        } else {
+            panic!("Expected project list command");
+        }
+    }
+
+    #[rstest]
+    #[case(vec!["wot", "project", "stats", "Web"], ProjectRef::Name("Web".to_string()), 30)]
+    #[case(vec!["wot", "project", "stats", "2", "--days", "7"], ProjectRef::Id(2), 7)]
+    fn test_project_stats_command(
+        #[case] cli_args: Vec<&str>,
+        #[case] exp_project: ProjectRef,
+        #[case] exp_days: u32,
+    ) {
+        let args = Cli::try_parse_from(cli_args).expect("Failed to parse arguments");
+        if let Commands::Project(ProjectArgs {
+            command: ProjectCommands::Stats(value),
+        }) = args.command
+        {
+            assert_eq!(value.project, exp_project);
+            assert_eq!(value.days, exp_days);
+            assert_eq!(value.format, OutputFormat::Table);
+        } else {
+            panic!("Expected project stats command");
         }
     }
 
+    #[rstest]
+    #[case(vec!["wot", "project", "show"])]
+    #[case(vec!["wot", "project", "show", "0"])]
+    #[case(vec!["wot", "project", "stats", "2", "--days", "0"])]
+    #[case(vec!["wot", "project", "list", "--format", "xml"])]
+    fn test_project_command_invalid(#[case] cli_args: Vec<&str>) {
+        assert!(Cli::try_parse_from(cli_args).is_err());
+    }
+
+    #[test]
+    fn test_report_command_launch_id() {
+        let args = Cli::try_parse_from([
+            "wot",
+            "report",
+            "-d",
+            "some_dir",
+            "-p",
+            "777",
+            "--launch-id",
+            "555",
+        ])
+        .expect("Failed to parse arguments");
+        let value = upload_args(args);
+        assert_eq!(value.launch_id, Some(555));
+    }
+
+    #[rstest]
+    #[case(vec!["--launch-name", "Nightly"])]
+    #[case(vec!["--tag", "smoke"])]
+    #[case(vec!["--link", "CI=https://ci.example.com"])]
+    #[case(vec!["--autoclose"])]
+    fn test_report_command_launch_id_conflicts(#[case] extra_args: Vec<&str>) {
+        let mut cli_args = vec![
+            "wot",
+            "report",
+            "-d",
+            "some_dir",
+            "-p",
+            "777",
+            "--launch-id",
+            "555",
+        ];
+        cli_args.extend(extra_args);
+        assert!(Cli::try_parse_from(cli_args).is_err());
+    }
+
+    #[rstest]
+    #[case(vec!["--wait"], true, 600)]
+    #[case(vec!["--wait", "--wait-timeout", "30"], true, 30)]
+    fn test_report_command_wait(
+        #[case] extra_args: Vec<&str>,
+        #[case] exp_wait: bool,
+        #[case] exp_timeout: u64,
+    ) {
+        let mut cli_args = vec!["wot", "report", "-d", "some_dir", "-p", "777"];
+        cli_args.extend(extra_args);
+        let args = Cli::try_parse_from(cli_args).expect("Failed to parse arguments");
+        let value = upload_args(args);
+        assert_eq!(value.wait, exp_wait);
+        assert_eq!(value.wait_timeout, exp_timeout);
+    }
+
+    #[test]
+    fn test_report_command_wait_timeout_requires_wait() {
+        let result = Cli::try_parse_from([
+            "wot",
+            "report",
+            "-d",
+            "some_dir",
+            "-p",
+            "777",
+            "--wait-timeout",
+            "30",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_report_command_gate() {
+        let args = Cli::try_parse_from([
+            "wot",
+            "report",
+            "-d",
+            "some_dir",
+            "-p",
+            "777",
+            "--wait",
+            "--max-failed",
+            "0",
+            "--min-pass-rate",
+            "95.5",
+            "--fail-on",
+            "broken",
+            "--fail-on",
+            "skipped",
+        ])
+        .expect("Failed to parse arguments");
+        let value = upload_args(args);
+        assert_eq!(value.gate.max_failed, Some(0));
+        assert_eq!(value.gate.min_pass_rate, Some(95.5));
+        assert_eq!(value.gate.fail_on, vec!["broken", "skipped"]);
+    }
+
+    #[rstest]
+    #[case(vec!["--max-failed", "0"])]
+    #[case(vec!["--wait", "--min-pass-rate", "101"])]
+    #[case(vec!["--wait", "--min-pass-rate", "abc"])]
+    #[case(vec!["--wait", "--fail-on", "passed"])]
+    fn test_report_command_invalid_gate(#[case] extra_args: Vec<&str>) {
+        let mut cli_args = vec!["wot", "report", "-d", "some_dir", "-p", "777"];
+        cli_args.extend(extra_args);
+        assert!(Cli::try_parse_from(cli_args).is_err());
+    }
+
+    #[test]
+    fn test_launch_gate_command() {
+        let args = Cli::try_parse_from([
+            "wot",
+            "launch",
+            "gate",
+            "555",
+            "--max-failed",
+            "3",
+            "--fail-on",
+            "broken",
+        ])
+        .expect("Failed to parse arguments");
+        if let Commands::Launch(value) = args.command {
+            let LaunchCommands::Gate(gate_args) = value.command;
+            assert_eq!(gate_args.launch_id, 555);
+            assert_eq!(gate_args.gate.max_failed, Some(3));
+            assert_eq!(gate_args.gate.min_pass_rate, None);
+            assert_eq!(gate_args.gate.fail_on, vec!["broken"]);
+        } else {
+            panic!("Expected launch command");
+        }
+    }
+
+    #[test]
+    fn test_report_validate_command() {
+        let args = Cli::try_parse_from(["wot", "report", "validate", "some_dir"])
+            .expect("Failed to parse arguments");
+        let Commands::Report(command) = args.command else {
+            panic!("Expected report command");
+        };
+        if let ReportCommand {
+            command: Some(ReportCommands::Validate(value)),
+            ..
+        } = *command
+        {
+            assert_eq!(value.directory_paths, vec!["some_dir"]);
+        } else {
+            panic!("Expected report validate command");
+        }
+    }
+
+    #[rstest]
+    /// Проверка отчета работает без конфига и без переменных окружения TestOps
+    fn test_report_validate_without_config(mut cli_command: assert_cmd::Command) {
+        let work_dir = std::env::temp_dir().join(format!("wot_offline_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(work_dir.join("results")).unwrap();
+        std::fs::write(
+            work_dir.join("results").join("1-result.json"),
+            r#"{"uuid": "1", "historyId": "h1"}"#,
+        )
+        .unwrap();
+        cli_command
+            .current_dir(&work_dir)
+            .env("HOME", &work_dir)
+            .env_remove("WOT_TESTOPS_URL")
+            .env_remove("WOT_TESTOPS_TOKEN")
+            .args(["report", "validate", "results"])
+            .write_stdin("")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Report is valid"));
+        std::fs::remove_dir_all(&work_dir).unwrap();
+    }
+
+    #[rstest]
+    /// Конвертация работает без конфига и без переменных окружения TestOps
+    fn test_convert_without_config(mut cli_command: assert_cmd::Command) {
+        let work_dir = std::env::temp_dir().join(format!("wot_offline_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&work_dir).unwrap();
+        std::fs::write(
+            work_dir.join("report.xml"),
+            r#"<testsuite name="suite"><testcase classname="Api" name="test_ok" time="1,5"/></testsuite>"#,
+        )
+        .unwrap();
+        cli_command
+            .current_dir(&work_dir)
+            .env("HOME", &work_dir)
+            .env_remove("WOT_TESTOPS_URL")
+            .env_remove("WOT_TESTOPS_TOKEN")
+            .args(["convert", "junit", "report.xml", "-o", "allure-results"])
+            .write_stdin("")
+            .assert()
+            .success();
+        let converted = std::fs::read_dir(work_dir.join("allure-results"))
+            .unwrap()
+            .count();
+        std::fs::remove_dir_all(&work_dir).unwrap();
+        assert_eq!(converted, 1);
+    }
+
+    #[rstest]
+    #[case(vec!["wot", "report", "-d", "some_dir", "validate", "some_dir"])]
+    #[case(vec!["wot", "report", "validate"])]
+    fn test_report_validate_command_invalid(#[case] cli_args: Vec<&str>) {
+        assert!(Cli::try_parse_from(cli_args).is_err());
+    }
+
+    #[test]
+    fn test_report_command_several_directories() {
+        let args = Cli::try_parse_from([
+            "wot",
+            "report",
+            "-d",
+            "api_results",
+            "--directory-path",
+            "ui_results",
+            "-p",
+            "777",
+        ])
+        .expect("Failed to parse arguments");
+        let value = upload_args(args);
+        assert_eq!(value.directory_paths, vec!["api_results", "ui_results"]);
+    }
+
+    #[rstest]
+    #[case(vec![], ReportFormat::Allure)]
+    #[case(vec!["--format", "junit"], ReportFormat::Junit)]
+    fn test_report_command_format(#[case] extra_args: Vec<&str>, #[case] expected: ReportFormat) {
+        let mut cli_args = vec!["wot", "report", "-d", "some_dir", "-p", "777"];
+        cli_args.extend(extra_args);
+        let args = Cli::try_parse_from(cli_args).expect("Failed to parse arguments");
+        let value = upload_args(args);
+        assert_eq!(value.format, expected);
+    }
+
+    #[rstest]
+    #[case(vec!["wot", "convert", "junit", "reports"], vec!["reports"], "allure-results")]
+    #[case(
+        vec!["wot", "convert", "junit", "a.xml", "b.xml", "-o", "out"],
+        vec!["a.xml", "b.xml"],
+        "out"
+    )]
+    fn test_convert_command(
+        #[case] cli_args: Vec<&str>,
+        #[case] exp_paths: Vec<&str>,
+        #[case] exp_output_dir: &str,
+    ) {
+        let args = Cli::try_parse_from(cli_args).expect("Failed to parse arguments");
+        if let Commands::Convert(value) = args.command {
+            assert_eq!(value.command.format(), ReportFormat::Junit);
+            assert_eq!(value.command.input().paths, exp_paths);
+            assert_eq!(value.command.input().output_dir, exp_output_dir);
+        } else {
+            panic!("Expected convert command");
+        }
+    }
+
+    #[rstest]
+    #[case("cucumber", "cucumber.json", ReportFormat::Cucumber)]
+    #[case("trx", "results.trx", ReportFormat::Trx)]
+    #[case("nunit", "TestResult.xml", ReportFormat::Nunit)]
+    fn test_convert_format_command(
+        #[case] command: &str,
+        #[case] path: &str,
+        #[case] expected: ReportFormat,
+    ) {
+        let args = Cli::try_parse_from(["wot", "convert", command, path])
+            .expect("Failed to parse arguments");
+        if let Commands::Convert(value) = args.command {
+            assert_eq!(value.command.format(), expected);
+            assert_eq!(value.command.input().paths, vec![path]);
+        } else {
+            panic!("Expected convert command");
+        }
+    }
+
+    #[test]
+    fn test_convert_command_without_paths() {
+        assert!(Cli::try_parse_from(["wot", "convert", "junit"]).is_err());
+    }
+
+    #[test]
+    fn test_report_command_invalid_batch_size() {
+        let result = Cli::try_parse_from([
+            "wot",
+            "report",
+            "-d",
+            "some_dir",
+            "-p",
+            "777",
+            "--batch-size",
+            "1TB",
+        ]);
+        assert!(result.is_err());
+    }
+
     #[rstest]
     #[case("-i")]
     #[case("--import-testcase-id")]
@@ -339,7 +1891,6 @@ Options:
     }
 
     #[rstest]
-    #[case("report")]
     #[case("testcase")]
     fn test_missing_required_args(#[case] flag: String, mut cli_command: assert_cmd::Command) {
         cli_command
@@ -349,6 +1900,112 @@ Options:
             .stderr(predicates::str::contains("required"));
     }
 
+    #[test]
+    /// Проект и директории могут прийти из .wot.toml, поэтому флаги не обязательны
+    fn test_report_command_without_args() {
+        let args = Cli::try_parse_from(["wot", "report"]).expect("Failed to parse arguments");
+        let value = upload_args(args);
+        assert!(value.directory_paths.is_empty());
+        assert_eq!(value.project, None);
+    }
+
+    #[rstest]
+    /// Без флагов и без .wot.toml отчет не отправляется и ничего не запрашивается
+    fn test_report_without_project(mut cli_command: assert_cmd::Command) {
+        let work_dir = std::env::temp_dir().join(format!("wot_repo_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&work_dir).unwrap();
+        cli_command
+            .current_dir(&work_dir)
+            .env("HOME", &work_dir)
+            .env("WOT_TESTOPS_URL", "http://127.0.0.1:9")
+            .env("WOT_TESTOPS_TOKEN", "c4e42f15-5b22-46ae-b2a1-10b5e2ffcb14")
+            .args(["report", "--yes"])
+            .write_stdin("")
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(
+                "Failed to send report: The project is not set",
+            ));
+        std::fs::remove_dir_all(&work_dir).unwrap();
+    }
+
+    #[rstest]
+    /// Значения из .wot.toml используются, только если не заданы флагами
+    fn test_apply_repo_defaults() {
+        let repo_dir = std::env::temp_dir().join(format!("wot_repo_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&repo_dir).unwrap();
+        std::fs::write(
+            repo_dir.join(".wot.toml"),
+            "project = 7\n[report]\ndirectories = [\"results\"]\ntags = [\"nightly\"]\n\
+             [testcase]\nmark = \"regression\"",
+        )
+        .unwrap();
+        let mut from_file = Cli::try_parse_from(["wot", "report"]).unwrap();
+        from_file.apply_repo_defaults(&repo_dir).unwrap();
+        let mut from_flags =
+            Cli::try_parse_from(["wot", "report", "-p", "Web", "-d", "ui", "--tag", "smoke"])
+                .unwrap();
+        from_flags.apply_repo_defaults(&repo_dir).unwrap();
+        let mut testcase = Cli::try_parse_from(["wot", "testcase", "-i", "1"]).unwrap();
+        testcase.apply_repo_defaults(&repo_dir).unwrap();
+        std::fs::remove_dir_all(&repo_dir).unwrap();
+
+        let from_file = upload_args(from_file);
+        assert_eq!(from_file.project, Some(ProjectRef::Id(7)));
+        assert_eq!(
+            from_file.directory_paths,
+            vec![repo_dir.join("results").display().to_string()]
+        );
+        assert_eq!(from_file.tags, vec!["nightly"]);
+        let from_flags = upload_args(from_flags);
+        assert_eq!(
+            from_flags.project,
+            Some(ProjectRef::Name("Web".to_string()))
+        );
+        assert_eq!(from_flags.directory_paths, vec!["ui"]);
+        assert_eq!(from_flags.tags, vec!["smoke"]);
+        let Commands::Testcase(testcase) = testcase.command else {
+            panic!("Expected testcase command");
+        };
+        assert_eq!(testcase.mark.as_deref(), Some("regression"));
+        assert_eq!(testcase.output_dir, None);
+    }
+
+    #[rstest]
+    #[case(vec!["wot", "report"], ProjectRef::Name("Web".to_string()))]
+    #[case(vec!["wot", "report", "-p", "3"], ProjectRef::Id(3))]
+    /// WOT_PROJECT важнее файла, но не флага
+    fn test_apply_env_defaults(#[case] args: Vec<&str>, #[case] expected: ProjectRef) {
+        let env_defaults = RepoDefaults {
+            project: Some(ProjectRef::Name("Web".to_string())),
+            ..RepoDefaults::default()
+        };
+        let file_defaults = RepoDefaults {
+            project: Some(ProjectRef::Id(7)),
+            ..RepoDefaults::default()
+        };
+        let mut report_args = upload_args(Cli::try_parse_from(args).unwrap());
+        report_args.apply_defaults(&env_defaults);
+        report_args.apply_defaults(&file_defaults);
+        assert_eq!(report_args.project, Some(expected));
+    }
+
+    #[test]
+    fn test_config_defaults_flags() {
+        let cli = Cli::try_parse_from(["wot", "config", "defaults", "-p", "Web", "--tag", "smoke"])
+            .unwrap();
+        let Commands::Config(ConfigArgs {
+            command: ConfigCommands::Defaults(args),
+        }) = cli.command
+        else {
+            panic!("Expected config defaults command");
+        };
+        let flags = args.flag_values();
+        assert_eq!(flags.project, Some(ProjectRef::Name("Web".to_string())));
+        assert_eq!(flags.tags, vec!["smoke"]);
+        assert_eq!(flags.path, None);
+    }
+
     #[rstest]
     fn test_get_filename_from_testcase_args() {
         let testcase_args = TestcaseArgs::new_test(123, None);
@@ -1,2 +1,6 @@
+pub mod config;
+pub mod convert;
+pub mod launch;
+pub mod project;
 pub mod report;
 pub mod testcase;
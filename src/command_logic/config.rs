@@ -0,0 +1,539 @@
+use crate::cli_app::{InitArgs, ProfileAddArgs};
+use crate::config::{Config, Profile, CONFIG_VERSION, DEFAULT_PROFILE};
+use crate::constants::{Message, ENTER_TESTOPS_API_KEY};
+use crate::external_api::testops_api::TestopsApi;
+use crate::external_api::ApiError;
+use crate::repo_defaults::RepoDefaults;
+use crate::utils::format_table;
+use serde_json::Value;
+use std::io::{BufRead, Read, Write};
+use std::path::Path;
+
+/// Read the config, it must be created by the setup wizard first
+fn read_config(config_path: &Path) -> Result<Config, ApiError> {
+    if !config_path.exists() {
+        return Err(ApiError::ConfigNotFound(config_path.display().to_string()));
+    }
+    Config::get_config(config_path.to_path_buf())
+}
+
+/// Create the config without prompts
+///
+/// Existing config is overwritten only with `--force`, its profiles and settings are kept
+pub fn init_config<R: Read>(
+    args: &InitArgs,
+    config_path: &Path,
+    input: R,
+) -> Result<String, ApiError> {
+    let config_exists = config_path.exists();
+    if config_exists && !args.force {
+        return Err(ApiError::ConfigAlreadyExists(
+            config_path.display().to_string(),
+        ));
+    }
+    let token = read_token_file(&args.token_file, input)?;
+    let profile = Profile::new(&args.url, &token)?;
+    let config = match config_exists {
+        true => {
+            let mut config = Config::get_config(config_path.to_path_buf())?;
+            config.testops_base_url = profile.testops_base_url;
+            config.testops_api_token = profile.testops_api_token;
+            // Иначе команда или файл токена остались бы важнее нового токена
+            config.token_command = None;
+            config.token_file = None;
+            config
+        }
+        false => Config::with_profile(profile),
+    };
+    config.save(config_path)?;
+    Ok(Message::ConfigCreated(config_path.display().to_string()).to_formatted_string())
+}
+
+/// Token from the file, `-` reads it from the input
+fn read_token_file<R: Read>(token_file: &str, mut input: R) -> Result<String, ApiError> {
+    let invalid_file = |reason: String| ApiError::InvalidTokenFile(token_file.to_string(), reason);
+    let content = match token_file {
+        "-" => {
+            let mut content = String::new();
+            input
+                .read_to_string(&mut content)
+                .map_err(|e| invalid_file(e.to_string()))?;
+            content
+        }
+        path => std::fs::read_to_string(path).map_err(|e| invalid_file(e.to_string()))?,
+    };
+    let token = content.trim();
+    if token.is_empty() {
+        return Err(invalid_file("the file is empty".to_string()));
+    }
+    Ok(token.to_string())
+}
+
+/// Add a profile, the token is read from the input if it is not given
+pub fn add_profile<R, W>(
+    args: &ProfileAddArgs,
+    config_path: &Path,
+    mut input: R,
+    mut output: W,
+) -> Result<String, ApiError>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut config = read_config(config_path)?;
+    let token = match &args.token {
+        Some(token) => token.clone(),
+        None => {
+            writeln!(output, "{}", ENTER_TESTOPS_API_KEY)?;
+            let mut token = String::new();
+            input.read_line(&mut token)?;
+            token
+        }
+    };
+    config.add_profile(&args.name, Profile::new(&args.url, &token)?)?;
+    config.save(config_path)?;
+    Ok(Message::ProfileAdded(args.name.clone()).to_formatted_string())
+}
+
+/// Table of profiles, the one that would be used is marked
+pub fn list_profiles(
+    config_path: &Path,
+    selected_profile: Option<&str>,
+) -> Result<String, ApiError> {
+    let config = read_config(config_path)?;
+    let active_profile = config.profile_name(selected_profile);
+    let mut names = vec![DEFAULT_PROFILE];
+    names.extend(config.profiles.keys().map(String::as_str));
+    let mut rows = Vec::new();
+    for name in names {
+        let profile = config.profile(name)?;
+        rows.push(vec![
+            if name == active_profile { "*" } else { "" }.to_string(),
+            name.to_string(),
+            profile.testops_base_url,
+        ]);
+    }
+    Ok(format_table(&["", "NAME", "URL"], &rows))
+}
+
+pub fn remove_profile(name: &str, config_path: &Path) -> Result<String, ApiError> {
+    let mut config = read_config(config_path)?;
+    config.remove_profile(name)?;
+    config.save(config_path)?;
+    Ok(Message::ProfileRemoved(name.to_string()).to_formatted_string())
+}
+
+/// Config as json, tokens are masked
+pub fn show_config(config_path: &Path) -> Result<String, ApiError> {
+    let config = read_config(config_path)?;
+    let mut value = serde_json::to_value(&config)?;
+    mask_token(&mut value);
+    if let Some(profiles) = value.get_mut("profiles").and_then(Value::as_object_mut) {
+        profiles.values_mut().for_each(mask_token);
+    }
+    Ok(serde_json::to_string_pretty(&value)?)
+}
+
+/// Hide the token except its last characters, enough to tell tokens apart
+fn mask_token(profile: &mut Value) {
+    if let Some(Value::String(token)) = profile.get_mut("testops_api_token") {
+        let chars: Vec<char> = token.chars().collect();
+        let visible: String = match chars.len() > 8 {
+            true => chars[chars.len() - 4..].iter().collect(),
+            false => String::new(),
+        };
+        *token = format!("****{}", visible);
+    }
+}
+
+pub fn set_config_value(key: &str, value: &str, config_path: &Path) -> Result<String, ApiError> {
+    let mut config = read_config(config_path)?;
+    config.set_value(key, value)?;
+    config.save(config_path)?;
+    Ok(Message::ConfigValueSet(key.to_string()).to_formatted_string())
+}
+
+/// Rewrite an old config in the current format
+pub fn migrate_config(config_path: &Path) -> Result<String, ApiError> {
+    let config = read_config(config_path)?;
+    let version = Config::file_version(config_path)?;
+    if version == CONFIG_VERSION {
+        return Ok(Message::ConfigUpToDate(version).to_formatted_string());
+    }
+    config.save(config_path)?;
+    Ok(Message::ConfigMigrated(version, CONFIG_VERSION).to_formatted_string())
+}
+
+/// Table of the defaults from .wot.toml with the source of each value
+///
+/// Command line flags are not shown, they override any of these values
+pub fn show_repo_defaults(
+    current_dir: &Path,
+    flags: &RepoDefaults,
+    env: &RepoDefaults,
+) -> Result<String, ApiError> {
+    let defaults = RepoDefaults::discover(current_dir)?;
+    Ok(format_table(
+        &["KEY", "VALUE", "SOURCE"],
+        &defaults.describe(flags, env),
+    ))
+}
+
+/// Validate the url and the token of the profile and check that TestOps accepts them
+pub async fn check_config(profile: &Profile, testops_api: &TestopsApi) -> Result<String, ApiError> {
+    let profile = Profile::new(&profile.testops_base_url, &profile.testops_api_token)?;
+    testops_api.check_access().await?;
+    Ok(Message::ConfigCheckPassed(profile.testops_base_url).to_formatted_string())
+}
+
+/// Make the profile current for the next runs
+pub fn use_profile(name: &str, config_path: &Path) -> Result<String, ApiError> {
+    let mut config = read_config(config_path)?;
+    config.use_profile(name)?;
+    config.save(config_path)?;
+    Ok(Message::ProfileActivated(name.to_string()).to_formatted_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::path::PathBuf;
+
+    const TOKEN: &str = "c4e42f15-5b22-46ae-b2a1-10b5e2ffcb14";
+
+    fn create_config() -> PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("wot_profiles_{}", uuid::Uuid::new_v4()))
+            .join("config.json");
+        let config: Config = serde_json::from_str(&format!(
+            r#"{{"testops_base_url": "https://prod.example.com", "testops_api_token": "{}"}}"#,
+            TOKEN
+        ))
+        .unwrap();
+        config.save(&path).unwrap();
+        path
+    }
+
+    fn add_args(name: &str, token: Option<&str>) -> ProfileAddArgs {
+        ProfileAddArgs {
+            name: name.to_string(),
+            url: "https://staging.example.com/".to_string(),
+            token: token.map(str::to_string),
+        }
+    }
+
+    #[test]
+    /// Токен без флага читается из ввода, профиль сохраняется в конфиг
+    fn test_add_profile_token_from_input() {
+        let config_path = create_config();
+        let mut output = Vec::new();
+        let result = add_profile(
+            &add_args("staging", None),
+            &config_path,
+            Cursor::new(format!("{}\n", TOKEN)),
+            &mut output,
+        );
+        let config = Config::get_config(config_path.clone()).unwrap();
+        std::fs::remove_dir_all(config_path.parent().unwrap()).unwrap();
+
+        assert_eq!(result.unwrap(), "Profile 'staging' added");
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Enter the TestOps API key: \n"
+        );
+        assert_eq!(
+            config.profile("staging").unwrap(),
+            Profile {
+                testops_base_url: "https://staging.example.com".to_string(),
+                testops_api_token: TOKEN.to_string(),
+                token_command: None,
+                token_file: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_add_profile_invalid_token() {
+        let config_path = create_config();
+        let result = add_profile(
+            &add_args("staging", Some("token")),
+            &config_path,
+            Cursor::new(Vec::new()),
+            Vec::new(),
+        );
+        let config = Config::get_config(config_path.clone()).unwrap();
+        std::fs::remove_dir_all(config_path.parent().unwrap()).unwrap();
+
+        assert!(matches!(result, Err(ApiError::InvalidToken)));
+        assert!(config.profiles.is_empty());
+    }
+
+    #[test]
+    /// Выбранный флагом профиль отмечается вместо текущего
+    fn test_list_profiles() {
+        let config_path = create_config();
+        add_profile(
+            &add_args("staging", Some(TOKEN)),
+            &config_path,
+            Cursor::new(Vec::new()),
+            Vec::new(),
+        )
+        .unwrap();
+        let current = list_profiles(&config_path, None).unwrap();
+        let selected = list_profiles(&config_path, Some("staging")).unwrap();
+        std::fs::remove_dir_all(config_path.parent().unwrap()).unwrap();
+
+        assert_eq!(
+            current,
+            "   NAME     URL\n\
+             *  default  https://prod.example.com\n   \
+             staging  https://staging.example.com"
+        );
+        assert!(selected.contains("*  staging"));
+    }
+
+    #[test]
+    fn test_use_and_remove_profile() {
+        let config_path = create_config();
+        add_profile(
+            &add_args("staging", Some(TOKEN)),
+            &config_path,
+            Cursor::new(Vec::new()),
+            Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            use_profile("staging", &config_path).unwrap(),
+            "Profile 'staging' is now used by default"
+        );
+        let current_profile = Config::get_config(config_path.clone())
+            .unwrap()
+            .current_profile;
+        assert_eq!(
+            remove_profile("staging", &config_path).unwrap(),
+            "Profile 'staging' removed"
+        );
+        let config = Config::get_config(config_path.clone()).unwrap();
+        std::fs::remove_dir_all(config_path.parent().unwrap()).unwrap();
+
+        assert_eq!(current_profile.as_deref(), Some("staging"));
+        assert!(config.profiles.is_empty());
+        assert_eq!(config.current_profile, None);
+    }
+
+    fn init_args(token_file: &str, force: bool) -> InitArgs {
+        InitArgs {
+            url: "https://testops.example.com/".to_string(),
+            token_file: token_file.to_string(),
+            force,
+        }
+    }
+
+    #[test]
+    /// Токен читается из файла, пробелы и перевод строки отбрасываются
+    fn test_init_config_from_token_file() {
+        let config_path = std::env::temp_dir()
+            .join(format!("wot_init_{}", uuid::Uuid::new_v4()))
+            .join("config.json");
+        let token_path = std::env::temp_dir().join(format!("wot_token_{}", uuid::Uuid::new_v4()));
+        std::fs::write(&token_path, format!("  {}\n", TOKEN)).unwrap();
+
+        let result = init_config(
+            &init_args(token_path.to_str().unwrap(), false),
+            &config_path,
+            Cursor::new(Vec::new()),
+        );
+        let config = Config::get_config(config_path.clone()).unwrap();
+        std::fs::remove_dir_all(config_path.parent().unwrap()).unwrap();
+        std::fs::remove_file(&token_path).unwrap();
+
+        assert_eq!(
+            result.unwrap(),
+            format!("Config created at <{}>", config_path.display())
+        );
+        assert_eq!(config.testops_base_url, "https://testops.example.com");
+        assert_eq!(config.testops_api_token, TOKEN);
+    }
+
+    #[test]
+    /// С --force перезаписываются url и токен, команда и файл токена удаляются, профили сохраняются
+    fn test_init_config_force() {
+        let config_path = create_config();
+        add_profile(
+            &add_args("staging", Some(TOKEN)),
+            &config_path,
+            Cursor::new(Vec::new()),
+            Vec::new(),
+        )
+        .unwrap();
+        for (key, value) in [
+            ("token_command", "pass show testops"),
+            ("token_file", "token.txt"),
+        ] {
+            set_config_value(key, value, &config_path).unwrap();
+        }
+        let new_token = uuid::Uuid::new_v4().to_string();
+
+        let without_force = init_config(
+            &init_args("-", false),
+            &config_path,
+            Cursor::new(new_token.clone()),
+        );
+        let with_force = init_config(
+            &init_args("-", true),
+            &config_path,
+            Cursor::new(new_token.clone()),
+        );
+        let config = Config::get_config(config_path.clone()).unwrap();
+        std::fs::remove_dir_all(config_path.parent().unwrap()).unwrap();
+
+        assert!(matches!(
+            without_force,
+            Err(ApiError::ConfigAlreadyExists(_))
+        ));
+        assert!(with_force.is_ok());
+        assert_eq!(config.testops_base_url, "https://testops.example.com");
+        assert_eq!(config.testops_api_token, new_token);
+        assert_eq!(config.token_command, None);
+        assert_eq!(config.token_file, None);
+        assert!(config.profiles.contains_key("staging"));
+    }
+
+    #[test]
+    fn test_init_config_invalid_token_file() {
+        let config_path = std::env::temp_dir()
+            .join(format!("wot_init_{}", uuid::Uuid::new_v4()))
+            .join("config.json");
+        let missing = init_config(
+            &init_args("missing/token.txt", false),
+            &config_path,
+            Cursor::new(Vec::new()),
+        );
+        let empty = init_config(&init_args("-", false), &config_path, Cursor::new("\n"));
+        let invalid = init_config(&init_args("-", false), &config_path, Cursor::new("token"));
+
+        assert!(
+            matches!(missing, Err(ApiError::InvalidTokenFile(path, _)) if path == "missing/token.txt")
+        );
+        assert_eq!(
+            empty.unwrap_err().to_string(),
+            "Could not read the token file <->: the file is empty"
+        );
+        assert!(matches!(invalid, Err(ApiError::InvalidToken)));
+        assert!(!config_path.exists());
+    }
+
+    #[test]
+    /// Токены всех профилей скрыты, кроме последних символов
+    fn test_show_config() {
+        let config_path = create_config();
+        add_profile(
+            &add_args("staging", Some("2f8d3c4a-9f6b-4d2e-8a1c-5b7e9d0f1a2b")),
+            &config_path,
+            Cursor::new(Vec::new()),
+            Vec::new(),
+        )
+        .unwrap();
+        let result = show_config(&config_path).unwrap();
+        std::fs::remove_dir_all(config_path.parent().unwrap()).unwrap();
+
+        assert!(!result.contains(TOKEN));
+        assert!(result.contains(r#""testops_api_token": "****cb14""#));
+        assert!(result.contains(r#""testops_api_token": "****1a2b""#));
+    }
+
+    #[test]
+    fn test_set_config_value() {
+        let config_path = create_config();
+        let result = set_config_value("network.read_timeout_secs", "30", &config_path);
+        let config = Config::get_config(config_path.clone()).unwrap();
+        std::fs::remove_dir_all(config_path.parent().unwrap()).unwrap();
+
+        assert_eq!(
+            result.unwrap(),
+            "Config value 'network.read_timeout_secs' updated"
+        );
+        assert_eq!(config.network.read_timeout_secs, 30);
+    }
+
+    #[test]
+    /// Старый конфиг переписывается с версией, повторная миграция ничего не делает
+    fn test_migrate_config() {
+        let config_path = create_config();
+        std::fs::write(
+            &config_path,
+            format!(
+                r#"{{"testops_base_url": "https://prod.example.com", "testops_api_token": "{}"}}"#,
+                TOKEN
+            ),
+        )
+        .unwrap();
+        let first = migrate_config(&config_path).unwrap();
+        let second = migrate_config(&config_path).unwrap();
+        let file_version = Config::file_version(&config_path).unwrap();
+        std::fs::remove_dir_all(config_path.parent().unwrap()).unwrap();
+
+        assert_eq!(first, "Config migrated from version 0 to 1");
+        assert_eq!(second, "Config is up to date, version 1");
+        assert_eq!(file_version, CONFIG_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_check_config() {
+        let mut server = mockito::Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        TestopsApi::mock_get_all_projects(&mut server).await;
+        let profile = Profile::new("https://testops.example.com/", TOKEN).unwrap();
+
+        assert_eq!(
+            check_config(&profile, &testops_api).await.unwrap(),
+            "Config is valid, TestOps at <https://testops.example.com> accepted the token"
+        );
+        let invalid_profile = Profile {
+            testops_api_token: "token".to_string(),
+            ..profile
+        };
+        assert!(matches!(
+            check_config(&invalid_profile, &testops_api).await,
+            Err(ApiError::InvalidToken)
+        ));
+    }
+
+    #[test]
+    fn test_show_repo_defaults() {
+        let repo_dir = std::env::temp_dir().join(format!("wot_repo_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&repo_dir).unwrap();
+        std::fs::write(repo_dir.join(".wot.toml"), "project = 7").unwrap();
+        let flags = RepoDefaults {
+            launch_name: Some("Smoke".to_string()),
+            ..RepoDefaults::default()
+        };
+        let result = show_repo_defaults(&repo_dir, &flags, &RepoDefaults::default()).unwrap();
+        std::fs::remove_dir_all(&repo_dir).unwrap();
+
+        let first_rows: Vec<&str> = result.lines().take(4).collect();
+        assert_eq!(
+            first_rows,
+            vec![
+                "KEY                  VALUE               SOURCE",
+                &format!(
+                    "project              7                   {}",
+                    repo_dir.join(".wot.toml").display()
+                ),
+                "report.directories                       not set",
+                "report.launch_name   Smoke               flag",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_profiles_without_config() {
+        let config_path = PathBuf::from("missing/config.json");
+        assert!(matches!(
+            list_profiles(&config_path, None),
+            Err(ApiError::ConfigNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,102 @@
+use crate::allure_results::is_result_file;
+use crate::cli_app::ConvertCommands;
+use crate::constants::Message;
+use crate::converters::convert_report_files;
+use crate::external_api::ApiError;
+use crate::utils::{collect_report_files, ArchiveFilter, ReportFile};
+use std::fs;
+use std::path::Path;
+
+/// Convert reports of other tools and save allure results to the output directory
+pub fn convert_report(convert_command: &ConvertCommands) -> Result<String, ApiError> {
+    let input = convert_command.input();
+    let report_files = collect_input_files(&input.paths)?;
+    let converted_files = convert_report_files(convert_command.format(), report_files)?;
+    let output_dir = Path::new(&input.output_dir);
+    fs::create_dir_all(output_dir)?;
+    for converted_file in &converted_files {
+        fs::write(
+            output_dir.join(&converted_file.name_in_archive),
+            converted_file.read()?,
+        )?;
+    }
+    let results_count = converted_files
+        .iter()
+        .filter(|converted_file| is_result_file(&converted_file.name_in_archive))
+        .count();
+    Ok(Message::ReportConverted(results_count, input.output_dir.clone()).to_formatted_string())
+}
+
+/// Files from paths, directories are read recursively
+fn collect_input_files(paths: &[String]) -> Result<Vec<ReportFile>, ApiError> {
+    let mut report_files = Vec::new();
+    for path in paths.iter().map(Path::new) {
+        if path.is_dir() {
+            report_files.extend(collect_report_files(path, &ArchiveFilter::default())?);
+        } else if path.is_file() {
+            report_files.push(ReportFile {
+                path: path.to_path_buf(),
+                name_in_archive: path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .ok_or(ApiError::InvalidFileName)?
+                    .to_string(),
+                size: path.metadata()?.len(),
+                content: None,
+            });
+        } else {
+            return Err(ApiError::NotFoundPath(path.display().to_string()));
+        }
+    }
+    Ok(report_files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli_app::ConvertInputArgs;
+
+    const JUNIT_REPORT: &str = r#"<testsuite name="api">
+    <testcase name="a"/>
+    <testcase name="b"><failure message="boom"/></testcase>
+</testsuite>"#;
+
+    #[test]
+    fn test_convert_junit_report() {
+        let temp_dir = std::env::temp_dir().join(format!("wot_convert_{}", uuid::Uuid::new_v4()));
+        let reports_dir = temp_dir.join("reports");
+        fs::create_dir_all(reports_dir.join("nested")).unwrap();
+        fs::write(reports_dir.join("TEST-api.xml"), JUNIT_REPORT).unwrap();
+        fs::write(reports_dir.join("nested/TEST-ui.xml"), JUNIT_REPORT).unwrap();
+        let single_report = temp_dir.join("TEST-single.xml");
+        fs::write(&single_report, JUNIT_REPORT).unwrap();
+        let output_dir = temp_dir.join("allure-results");
+        let convert_command = ConvertCommands::Junit(ConvertInputArgs {
+            paths: vec![
+                reports_dir.to_str().unwrap().to_string(),
+                single_report.to_str().unwrap().to_string(),
+            ],
+            output_dir: output_dir.to_str().unwrap().to_string(),
+        });
+
+        let result = convert_report(&convert_command);
+        let output_files_count = fs::read_dir(&output_dir).unwrap().count();
+        fs::remove_dir_all(&temp_dir).unwrap();
+
+        assert_eq!(
+            result.unwrap(),
+            format!("Converted 6 results to <{}>", output_dir.display())
+        );
+        assert_eq!(output_files_count, 6);
+    }
+
+    #[test]
+    fn test_convert_not_found_path() {
+        let convert_command = ConvertCommands::Junit(ConvertInputArgs {
+            paths: vec!["non/existent/report.xml".to_string()],
+            output_dir: "allure-results".to_string(),
+        });
+        let result = convert_report(&convert_command);
+        assert!(matches!(result, Err(ApiError::NotFoundPath(_))));
+    }
+}
@@ -0,0 +1,580 @@
+use crate::cli_app::{GateRules, LaunchGateArgs};
+use crate::constants::Message;
+use crate::external_api::testops_api::models::get_launch_by_id::GetLaunchByIdResponse;
+use crate::external_api::testops_api::models::launch_statistic::LaunchStatistic;
+use crate::external_api::testops_api::TestopsApi;
+use crate::external_api::ApiError;
+use crate::utils::format_duration;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// Statuses that are always shown in the summary
+const MAIN_STATUSES: [&str; 4] = ["passed", "failed", "broken", "skipped"];
+/// Polls in a row with the same launch state after which the processing is finished
+const STABLE_POLLS: u32 = 3;
+
+/// Results of the processed launch
+#[derive(Debug, PartialEq)]
+pub struct LaunchSummary {
+    pub launch_id: u32,
+    pub statistic: Vec<LaunchStatistic>,
+    /// Launch duration in milliseconds
+    pub duration_ms: Option<i64>,
+}
+
+impl LaunchSummary {
+    /// Count of results with the status
+    pub fn count(&self, status: &str) -> u32 {
+        self.statistic
+            .iter()
+            .filter(|statistic| statistic.status.eq_ignore_ascii_case(status))
+            .map(|statistic| statistic.count)
+            .sum()
+    }
+
+    pub fn total(&self) -> u32 {
+        self.statistic.iter().map(|statistic| statistic.count).sum()
+    }
+
+    /// Percent of passed results, 0 for the launch without results
+    pub fn pass_rate(&self) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => f64::from(self.count("passed")) * 100.0 / f64::from(total),
+        }
+    }
+
+    pub fn to_formatted_string(&self) -> String {
+        let mut counts: Vec<String> = MAIN_STATUSES
+            .iter()
+            .map(|status| format!("{} {}", status, self.count(status)))
+            .collect();
+        counts.extend(
+            self.statistic
+                .iter()
+                .filter(|statistic| {
+                    !MAIN_STATUSES
+                        .iter()
+                        .any(|status| statistic.status.eq_ignore_ascii_case(status))
+                })
+                .map(|statistic| format!("{} {}", statistic.status, statistic.count)),
+        );
+        counts.push(format!("total {}", self.total()));
+        let mut summary = format!("Launch {} results: {}", self.launch_id, counts.join(", "));
+        if let Some(duration_ms) = self.duration_ms {
+            summary.push_str(&format!("\nDuration: {}", format_duration(duration_ms)));
+        }
+        summary
+    }
+}
+
+/// What the launch reports about its results, compared between polls
+#[derive(Debug, Default, PartialEq)]
+pub struct LaunchState {
+    /// Statistic sorted by status, the order of the response is not guaranteed
+    statistic: Vec<LaunchStatistic>,
+    last_modified_date: Option<i64>,
+    closed: bool,
+}
+
+impl LaunchState {
+    fn new(launch: &GetLaunchByIdResponse, mut statistic: Vec<LaunchStatistic>) -> Self {
+        statistic.sort_by(|left, right| left.status.cmp(&right.status));
+        Self {
+            statistic,
+            last_modified_date: launch.last_modified_date,
+            closed: launch.closed,
+        }
+    }
+
+    /// The launch has results and they differ from the ones before the upload
+    ///
+    /// The modification date alone is not enough: a new launch gets it before any result
+    fn has_results_since(&self, before: &LaunchState) -> bool {
+        !self.statistic.is_empty() && self != before
+    }
+}
+
+/// Current state of the launch, to tell the uploaded results from the previous ones
+pub async fn get_launch_state(
+    launch_id: u32,
+    testops_api_client: &TestopsApi,
+) -> Result<LaunchState, ApiError> {
+    let launch = testops_api_client.get_launch_by_id(launch_id).await?;
+    let statistic = testops_api_client.get_launch_statistic(launch_id).await?;
+    Ok(LaunchState::new(&launch, statistic))
+}
+
+/// Get current results of the launch
+pub async fn get_launch_summary(
+    launch_id: u32,
+    testops_api_client: &TestopsApi,
+) -> Result<LaunchSummary, ApiError> {
+    let launch = testops_api_client.get_launch_by_id(launch_id).await?;
+    let statistic = testops_api_client.get_launch_statistic(launch_id).await?;
+    Ok(LaunchSummary {
+        launch_id,
+        statistic,
+        duration_ms: launch.duration_ms(),
+    })
+}
+
+/// Check the launch results against the quality gate rules
+///
+/// A launch without results fails any gate, nothing in it was checked
+pub fn check_quality_gate(summary: &LaunchSummary, rules: &GateRules) -> Result<(), ApiError> {
+    if summary.total() == 0 {
+        return Err(ApiError::QualityGateFailed(
+            summary.launch_id,
+            "no results".to_string(),
+        ));
+    }
+    let mut violations = vec![];
+    if let Some(max_failed) = rules.max_failed {
+        let failed = summary.count("failed");
+        if failed > max_failed {
+            violations.push(format!("failed {} > {}", failed, max_failed));
+        }
+    }
+    if let Some(min_pass_rate) = rules.min_pass_rate {
+        let pass_rate = summary.pass_rate();
+        if pass_rate < min_pass_rate {
+            violations.push(format!("pass rate {:.2}% < {}%", pass_rate, min_pass_rate));
+        }
+    }
+    for status in &rules.fail_on {
+        let count = summary.count(status);
+        if count > 0 {
+            violations.push(format!("{} {}", status, count));
+        }
+    }
+    if !violations.is_empty() {
+        return Err(ApiError::QualityGateFailed(
+            summary.launch_id,
+            violations.join(", "),
+        ));
+    }
+    Ok(())
+}
+
+/// Print the launch results and check them against the quality gate rules
+pub async fn check_launch_gate<W: Write>(
+    gate_args: &LaunchGateArgs,
+    testops_api_client: &TestopsApi,
+    mut output: W,
+) -> Result<String, ApiError> {
+    let summary = get_launch_summary(gate_args.launch_id, testops_api_client).await?;
+    writeln!(output, "{}", summary.to_formatted_string())?;
+    check_quality_gate(&summary, &gate_args.gate)?;
+    Ok(Message::QualityGatePassed(gate_args.launch_id.to_string()).to_formatted_string())
+}
+
+/// Poll the launch until TestOps processes the uploaded results
+///
+/// Processing is finished when the launch gets closed, or when it has results that differ from
+/// `state_before_upload` and they stay the same for several polls. The count of uploaded
+/// files is not used: retries with the same historyId are merged into one result, and other
+/// jobs may upload into the same launch
+pub async fn wait_for_launch_processing(
+    launch_id: u32,
+    state_before_upload: LaunchState,
+    timeout: Duration,
+    poll_interval: Duration,
+    testops_api_client: &TestopsApi,
+) -> Result<LaunchSummary, ApiError> {
+    let started_at = Instant::now();
+    let mut previous_state: Option<LaunchState> = None;
+    let mut stable_polls = 0;
+    loop {
+        let launch = testops_api_client.get_launch_by_id(launch_id).await?;
+        let statistic = testops_api_client.get_launch_statistic(launch_id).await?;
+        let state = LaunchState::new(&launch, statistic.clone());
+        // Запуск, закрытый еще до загрузки, показывает старые итоги
+        let is_closed_now = state.closed && !state_before_upload.closed;
+        if state.has_results_since(&state_before_upload) {
+            stable_polls = match previous_state.as_ref() == Some(&state) {
+                true => stable_polls + 1,
+                false => 1,
+            };
+            previous_state = Some(state);
+        } else {
+            stable_polls = 0;
+            previous_state = None;
+        }
+        let summary = LaunchSummary {
+            launch_id,
+            statistic,
+            duration_ms: launch.duration_ms(),
+        };
+        if is_closed_now || stable_polls >= STABLE_POLLS {
+            return Ok(summary);
+        }
+        if started_at.elapsed() + poll_interval > timeout {
+            return Err(ApiError::LaunchProcessingTimeout(
+                launch_id,
+                timeout.as_secs(),
+            ));
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+    use rstest::rstest;
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    fn summary(statistic: Vec<LaunchStatistic>, duration_ms: Option<i64>) -> LaunchSummary {
+        LaunchSummary {
+            launch_id: 555,
+            statistic,
+            duration_ms,
+        }
+    }
+
+    #[test]
+    fn test_summary_formatted_string() {
+        let summary = summary(
+            vec![
+                LaunchStatistic::new("passed", 10),
+                LaunchStatistic::new("failed", 2),
+                LaunchStatistic::new("unknown", 1),
+            ],
+            Some(65000),
+        );
+        assert_eq!(
+            summary.to_formatted_string(),
+            "Launch 555 results: passed 10, failed 2, broken 0, skipped 0, unknown 1, total 13\nDuration: 1m 5s"
+        );
+    }
+
+    #[test]
+    fn test_summary_without_duration() {
+        let summary = summary(vec![], None);
+        assert_eq!(
+            summary.to_formatted_string(),
+            "Launch 555 results: passed 0, failed 0, broken 0, skipped 0, total 0"
+        );
+    }
+
+    #[test]
+    fn test_summary_count_ignore_case() {
+        let summary = summary(vec![LaunchStatistic::new("PASSED", 3)], None);
+        assert_eq!(summary.count("passed"), 3);
+        assert_eq!(summary.total(), 3);
+    }
+
+    #[rstest]
+    #[case(vec![], 0.0)]
+    #[case(vec![LaunchStatistic::new("passed", 3), LaunchStatistic::new("failed", 1)], 75.0)]
+    fn test_summary_pass_rate(#[case] statistic: Vec<LaunchStatistic>, #[case] exp: f64) {
+        assert_eq!(summary(statistic, None).pass_rate(), exp);
+    }
+
+    fn gate_rules(
+        max_failed: Option<u32>,
+        min_pass_rate: Option<f64>,
+        fail_on: &[&str],
+    ) -> GateRules {
+        GateRules {
+            max_failed,
+            min_pass_rate,
+            fail_on: fail_on.iter().map(|status| status.to_string()).collect(),
+        }
+    }
+
+    #[rstest]
+    #[case(gate_rules(None, None, &[]))]
+    #[case(gate_rules(Some(2), None, &[]))]
+    #[case(gate_rules(None, Some(80.0), &[]))]
+    #[case(gate_rules(Some(2), Some(80.0), &["skipped"]))]
+    fn test_quality_gate_passed(#[case] rules: GateRules) {
+        let summary = summary(
+            vec![
+                LaunchStatistic::new("passed", 8),
+                LaunchStatistic::new("failed", 2),
+            ],
+            None,
+        );
+        assert!(check_quality_gate(&summary, &rules).is_ok());
+    }
+
+    #[rstest]
+    #[case(gate_rules(Some(1), None, &[]), "failed 2 > 1")]
+    #[case(gate_rules(None, Some(95.0), &[]), "pass rate 70.00% < 95%")]
+    #[case(gate_rules(None, None, &["broken"]), "broken 1")]
+    #[case(
+        gate_rules(Some(0), Some(90.5), &["broken", "skipped"]),
+        "failed 2 > 0, pass rate 70.00% < 90.5%, broken 1"
+    )]
+    fn test_quality_gate_failed(#[case] rules: GateRules, #[case] exp_violations: &str) {
+        let summary = summary(
+            vec![
+                LaunchStatistic::new("passed", 7),
+                LaunchStatistic::new("failed", 2),
+                LaunchStatistic::new("broken", 1),
+            ],
+            None,
+        );
+        let result = check_quality_gate(&summary, &rules);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!(
+                "Launch with ID == 555 failed the quality gate: {}",
+                exp_violations
+            )
+        );
+    }
+
+    #[rstest]
+    #[case(gate_rules(Some(0), None, &[]))]
+    #[case(gate_rules(None, None, &["broken"]))]
+    #[case(gate_rules(None, None, &[]))]
+    /// Пустой запуск не проходит проверку, даже если ни одно правило не нарушено
+    fn test_quality_gate_no_results(#[case] rules: GateRules) {
+        let result = check_quality_gate(&summary(vec![], None), &rules);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Launch with ID == 555 failed the quality gate: no results"
+        );
+    }
+
+    #[tokio::test]
+    /// Итоги запуска печатаются и при проваленной проверке
+    async fn test_check_launch_gate() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        let launch = GetLaunchByIdResponse::new(555, "Nightly", 2);
+        TestopsApi::mock_get_launch_by_id(&mut server, &launch, 555).await;
+        TestopsApi::mock_get_launch_statistic(
+            &mut server,
+            &[LaunchStatistic::new("failed", 1)],
+            555,
+        )
+        .await;
+        let gate_args = LaunchGateArgs {
+            launch_id: 555,
+            gate: gate_rules(Some(0), None, &[]),
+        };
+        let mut output = Vec::<u8>::new();
+
+        let result = check_launch_gate(&gate_args, &testops_api, &mut output).await;
+
+        assert_eq!(result.unwrap_err().exit_code(), 4);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Launch 555 results: passed 0, failed 1, broken 0, skipped 0, total 1\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_launch_gate_passed() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        let launch = GetLaunchByIdResponse::new(555, "Nightly", 2);
+        TestopsApi::mock_get_launch_by_id(&mut server, &launch, 555).await;
+        TestopsApi::mock_get_launch_statistic(
+            &mut server,
+            &[LaunchStatistic::new("passed", 1)],
+            555,
+        )
+        .await;
+        let gate_args = LaunchGateArgs {
+            launch_id: 555,
+            gate: gate_rules(Some(0), Some(100.0), &["broken"]),
+        };
+
+        let result = check_launch_gate(&gate_args, &testops_api, Vec::<u8>::new()).await;
+
+        assert_eq!(result.unwrap(), "Launch 555 passed the quality gate");
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_launch_processing_done() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        let launch = GetLaunchByIdResponse::new(555, "Nightly", 2);
+        TestopsApi::mock_get_launch_by_id(&mut server, &launch, 555).await;
+        let statistic = vec![
+            LaunchStatistic::new("passed", 2),
+            LaunchStatistic::new("failed", 1),
+        ];
+        TestopsApi::mock_get_launch_statistic(&mut server, &statistic, 555).await;
+
+        let result = wait_for_launch_processing(
+            555,
+            LaunchState::default(),
+            Duration::from_secs(1),
+            POLL_INTERVAL,
+            &testops_api,
+        )
+        .await;
+        assert_eq!(result.unwrap(), summary(statistic, None));
+    }
+
+    #[tokio::test]
+    /// Загружено 3 файла результатов, но перезапуски с одним historyId TestOps объединяет
+    /// в 2 результата: ожидание завершается, когда статистика перестает меняться
+    async fn test_wait_for_launch_processing_merged_results() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        let launch = GetLaunchByIdResponse::new(555, "Nightly", 2);
+        TestopsApi::mock_get_launch_by_id(&mut server, &launch, 555).await;
+        let statistic = vec![
+            LaunchStatistic::new("passed", 1),
+            LaunchStatistic::new("failed", 1),
+        ];
+        // Первые опросы до начала обработки возвращают пустую статистику
+        let mut mocks = vec![];
+        for (statistic, hits) in [(vec![], 2), (statistic, STABLE_POLLS as usize)] {
+            let mock = server
+                .mock("GET", "/api/rs/launch/555/statistic")
+                .with_header("content-type", "application/json")
+                .with_body(serde_json::to_string(&statistic).unwrap())
+                .expect(hits)
+                .create_async()
+                .await;
+            mocks.push(mock);
+        }
+
+        let result = wait_for_launch_processing(
+            555,
+            LaunchState::default(),
+            Duration::from_secs(1),
+            POLL_INTERVAL,
+            &testops_api,
+        )
+        .await;
+        assert_eq!(result.unwrap().total(), 2);
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    /// Состояние запуска, не изменившееся с момента до загрузки, не считается обработкой
+    async fn test_wait_for_launch_processing_not_started() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        let launch = GetLaunchByIdResponse::new(555, "Nightly", 2);
+        TestopsApi::mock_get_launch_by_id(&mut server, &launch, 555).await;
+        let statistic = vec![LaunchStatistic::new("passed", 4)];
+        TestopsApi::mock_get_launch_statistic(&mut server, &statistic, 555).await;
+        let state_before_upload = get_launch_state(555, &testops_api).await.unwrap();
+
+        let result = wait_for_launch_processing(
+            555,
+            state_before_upload,
+            Duration::from_millis(50),
+            POLL_INTERVAL,
+            &testops_api,
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(ApiError::LaunchProcessingTimeout(555, _))
+        ));
+    }
+
+    #[tokio::test]
+    /// Если TestOps не обработал результаты за отведенное время, получаем ошибку
+    async fn test_wait_for_launch_processing_timeout() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        let launch = GetLaunchByIdResponse::new(555, "Nightly", 2);
+        TestopsApi::mock_get_launch_by_id(&mut server, &launch, 555).await;
+        TestopsApi::mock_get_launch_statistic(&mut server, &[], 555).await;
+
+        let result = wait_for_launch_processing(
+            555,
+            LaunchState::default(),
+            Duration::from_millis(50),
+            POLL_INTERVAL,
+            &testops_api,
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(ApiError::LaunchProcessingTimeout(555, _))
+        ));
+    }
+
+    #[tokio::test]
+    /// Дата изменения нового запуска без результатов не означает, что обработка закончилась
+    async fn test_wait_for_launch_processing_empty_statistic() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        let mut launch = GetLaunchByIdResponse::new(555, "Nightly", 2);
+        launch.last_modified_date = Some(1735389247000);
+        TestopsApi::mock_get_launch_by_id(&mut server, &launch, 555).await;
+        TestopsApi::mock_get_launch_statistic(&mut server, &[], 555).await;
+
+        let result = wait_for_launch_processing(
+            555,
+            LaunchState::default(),
+            Duration::from_millis(100),
+            POLL_INTERVAL,
+            &testops_api,
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(ApiError::LaunchProcessingTimeout(555, _))
+        ));
+    }
+
+    #[tokio::test]
+    /// Запуск, закрытый до загрузки, не отдает старые итоги как результат ожидания
+    async fn test_wait_for_launch_closed_before_upload() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        let mut launch = GetLaunchByIdResponse::new(555, "Nightly", 2);
+        launch.closed = true;
+        launch.last_modified_date = Some(1735389247000);
+        TestopsApi::mock_get_launch_by_id(&mut server, &launch, 555).await;
+        TestopsApi::mock_get_launch_statistic(
+            &mut server,
+            &[LaunchStatistic::new("passed", 4)],
+            555,
+        )
+        .await;
+        let state_before_upload = get_launch_state(555, &testops_api).await.unwrap();
+
+        let result = wait_for_launch_processing(
+            555,
+            state_before_upload,
+            Duration::from_millis(100),
+            POLL_INTERVAL,
+            &testops_api,
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(ApiError::LaunchProcessingTimeout(555, _))
+        ));
+    }
+
+    #[tokio::test]
+    /// Закрытый запуск считается обработанным
+    async fn test_wait_for_closed_launch() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        let mut launch = GetLaunchByIdResponse::new(555, "Nightly", 2);
+        launch.closed = true;
+        TestopsApi::mock_get_launch_by_id(&mut server, &launch, 555).await;
+        TestopsApi::mock_get_launch_statistic(&mut server, &[], 555).await;
+
+        let result = wait_for_launch_processing(
+            555,
+            LaunchState::default(),
+            Duration::from_secs(1),
+            POLL_INTERVAL,
+            &testops_api,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+}
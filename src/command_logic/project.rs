@@ -0,0 +1,641 @@
+use crate::cli_app::{OutputFormat, ProjectListArgs, ProjectShowArgs, ProjectStatsArgs};
+use crate::constants::PROJECT_CACHE_TTL;
+use crate::external_api::testops_api::models::project_info::ProjectInfo;
+use crate::external_api::testops_api::TestopsApi;
+use crate::external_api::ApiError;
+use crate::utils::format_table;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How many matching projects are listed when the name is ambiguous
+const MAX_AMBIGUOUS_PROJECTS: usize = 10;
+const MILLIS_IN_DAY: i64 = 24 * 60 * 60 * 1000;
+
+/// Project given by id or by name
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProjectRef {
+    Id(u32),
+    Name(String),
+}
+
+/// Test cases and launches of the project
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectStats {
+    pub project_id: u32,
+    pub project_name: String,
+    pub test_cases: usize,
+    pub automated: usize,
+    pub manual: usize,
+    /// Period of counted launches in days
+    pub days: u32,
+    pub launches: usize,
+}
+
+impl ProjectStats {
+    fn to_table(&self) -> String {
+        let share = |count: usize| match self.test_cases {
+            0 => 0.0,
+            total => count as f64 * 100.0 / total as f64,
+        };
+        let rows = vec![
+            vec![
+                "Project".to_string(),
+                format!("{} ({})", self.project_name, self.project_id),
+            ],
+            vec!["Test cases".to_string(), self.test_cases.to_string()],
+            vec![
+                "Automated".to_string(),
+                format!("{} ({:.1}%)", self.automated, share(self.automated)),
+            ],
+            vec![
+                "Manual".to_string(),
+                format!("{} ({:.1}%)", self.manual, share(self.manual)),
+            ],
+            vec![
+                format!("Launches in {} days", self.days),
+                self.launches.to_string(),
+            ],
+        ];
+        format_table(&[], &rows)
+    }
+}
+
+/// Project list of the instance saved between runs
+#[derive(Deserialize, Serialize)]
+struct ProjectCache {
+    base_url: String,
+    /// Unix time of the update in seconds
+    updated_at: u64,
+    projects: Vec<ProjectInfo>,
+}
+
+impl ProjectCache {
+    /// Projects from the cache file written for the same instance and not older than `ttl`
+    fn read(path: &Path, base_url: &str, ttl: Duration) -> Option<Vec<ProjectInfo>> {
+        let content = std::fs::read(path).ok()?;
+        let cache: ProjectCache = serde_json::from_slice(&content).ok()?;
+        let age = unix_time().checked_sub(cache.updated_at)?;
+        (cache.base_url == base_url && age < ttl.as_secs()).then_some(cache.projects)
+    }
+
+    fn write(path: &Path, base_url: &str, projects: &[ProjectInfo]) -> Result<(), ApiError> {
+        let cache = ProjectCache {
+            base_url: base_url.to_string(),
+            updated_at: unix_time(),
+            projects: projects.to_vec(),
+        };
+        if let Some(parent_dir) = path.parent() {
+            std::fs::create_dir_all(parent_dir)?;
+        }
+        std::fs::write(path, serde_json::to_vec(&cache)?)?;
+        Ok(())
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Find the project by id or by name
+///
+/// Id is checked with a single request. Name is looked up in the cached project list,
+/// the list is requested again when the name is not found in the cache
+pub async fn resolve_project(
+    project: &ProjectRef,
+    testops_api_client: &TestopsApi,
+) -> Result<ProjectInfo, ApiError> {
+    let name = match project {
+        ProjectRef::Id(project_id) => {
+            return testops_api_client
+                .get_project_info_by_id(project_id)
+                .await
+                .map_err(|e| match e {
+                    ApiError::Api(StatusCode::NOT_FOUND, _) => {
+                        ApiError::ProjectIdNotFound(*project_id)
+                    }
+                    e => e,
+                })
+        }
+        ProjectRef::Name(name) => name,
+    };
+    if let Some(projects) = cached_projects(testops_api_client) {
+        match find_project_by_name(name, &projects) {
+            // Проект мог появиться после обновления кэша
+            Err(ApiError::ProjectNameNotFound(_)) => {}
+            result => return result,
+        }
+    }
+    let projects = fetch_projects(testops_api_client).await?;
+    find_project_by_name(name, &projects)
+}
+
+/// Projects from the cache if it is enabled and not expired
+pub fn cached_projects(testops_api_client: &TestopsApi) -> Option<Vec<ProjectInfo>> {
+    ProjectCache::read(
+        testops_api_client.project_cache_path.as_deref()?,
+        testops_api_client.client.base_url.as_str(),
+        PROJECT_CACHE_TTL,
+    )
+}
+
+/// Request all projects and save them to the cache
+///
+/// Cache is optional: if it can't be written, the next run requests projects again
+pub async fn fetch_projects(testops_api_client: &TestopsApi) -> Result<Vec<ProjectInfo>, ApiError> {
+    let projects = testops_api_client.get_all_projects().await?;
+    if let Some(path) = &testops_api_client.project_cache_path {
+        let _ = ProjectCache::write(path, testops_api_client.client.base_url.as_str(), &projects);
+    }
+    Ok(projects)
+}
+
+/// List projects of the instance, the cached list is used unless `--refresh` is given
+pub async fn list_projects(
+    args: &ProjectListArgs,
+    testops_api_client: &TestopsApi,
+) -> Result<String, ApiError> {
+    let projects = match cached_projects(testops_api_client).filter(|_| !args.refresh) {
+        Some(projects) => projects,
+        None => fetch_projects(testops_api_client).await?,
+    };
+    match args.format {
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(&projects)?),
+        OutputFormat::Table => {
+            let rows: Vec<Vec<String>> = projects
+                .iter()
+                .map(|project| vec![project.id.to_string(), project.name.clone()])
+                .collect();
+            Ok(format_table(&["ID", "NAME"], &rows))
+        }
+    }
+}
+
+/// Show project details
+pub async fn show_project(
+    args: &ProjectShowArgs,
+    testops_api_client: &TestopsApi,
+) -> Result<String, ApiError> {
+    let project = resolve_project(&args.project, testops_api_client).await?;
+    // Список проектов содержит не все поля, полные данные только в ответе по id
+    let project = match args.project {
+        ProjectRef::Id(_) => project,
+        ProjectRef::Name(_) => {
+            testops_api_client
+                .get_project_info_by_id(&project.id)
+                .await?
+        }
+    };
+    if args.format == OutputFormat::Json {
+        return Ok(serde_json::to_string_pretty(&project)?);
+    }
+    let mut rows = vec![
+        vec!["ID".to_string(), project.id.to_string()],
+        vec!["Name".to_string(), project.name],
+    ];
+    if let Some(abbr) = project.abbr {
+        rows.push(vec!["Abbreviation".to_string(), abbr]);
+    }
+    if let Some(description) = project.description {
+        rows.push(vec!["Description".to_string(), description]);
+    }
+    if let Some(is_public) = project.is_public {
+        let visibility = if is_public { "public" } else { "private" };
+        rows.push(vec!["Visibility".to_string(), visibility.to_string()]);
+    }
+    if let Some(created) = project
+        .created_date
+        .and_then(chrono::DateTime::from_timestamp_millis)
+    {
+        let created = created.with_timezone(&chrono::Local);
+        rows.push(vec![
+            "Created".to_string(),
+            created.format("%d/%m/%Y %H:%M").to_string(),
+        ]);
+    }
+    Ok(format_table(&[], &rows))
+}
+
+/// Count test cases of the project and its launches in the last days
+pub async fn project_stats(
+    args: &ProjectStatsArgs,
+    testops_api_client: &TestopsApi,
+) -> Result<String, ApiError> {
+    let project = resolve_project(&args.project, testops_api_client).await?;
+    let test_cases = testops_api_client
+        .get_project_test_cases(project.id)
+        .await?;
+    let since = chrono::Utc::now().timestamp_millis() - i64::from(args.days) * MILLIS_IN_DAY;
+    let launches = testops_api_client
+        .get_project_launches_since(project.id, since)
+        .await?;
+    let automated = test_cases
+        .iter()
+        .filter(|test_case| test_case.automated)
+        .count();
+    let stats = ProjectStats {
+        project_id: project.id,
+        project_name: project.name,
+        test_cases: test_cases.len(),
+        automated,
+        manual: test_cases.len() - automated,
+        days: args.days,
+        launches: launches.len(),
+    };
+    match args.format {
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(&stats)?),
+        OutputFormat::Table => Ok(stats.to_table()),
+    }
+}
+
+/// Find the project by name, from the strictest match to the most fuzzy one:
+/// exact name, name ignoring case and punctuation, part of the name, name with typos
+///
+/// The first level with matches wins, several matches on it make the name ambiguous
+fn find_project_by_name(name: &str, projects: &[ProjectInfo]) -> Result<ProjectInfo, ApiError> {
+    let query = normalize_name(name);
+    // Одна опечатка на каждые 5 символов, но не больше двух.
+    // Цифры должны совпадать, иначе "Project3" нашел бы "Project1"
+    let max_typos = (query.chars().count() / 5).min(2);
+    let query_digits = digits(&query);
+    let matchers: [&dyn Fn(&str) -> bool; 4] = [
+        &|project_name| project_name == name,
+        &|project_name| !query.is_empty() && normalize_name(project_name) == query,
+        &|project_name| !query.is_empty() && normalize_name(project_name).contains(&query),
+        &|project_name| {
+            let project_name = normalize_name(project_name);
+            max_typos > 0
+                && digits(&project_name) == query_digits
+                && edit_distance(&project_name, &query) <= max_typos
+        },
+    ];
+    for is_match in matchers {
+        let found: Vec<&ProjectInfo> = projects
+            .iter()
+            .filter(|project| is_match(&project.name))
+            .collect();
+        match found[..] {
+            [] => continue,
+            [project] => return Ok(project.clone()),
+            _ => {
+                let mut matches: Vec<String> = found
+                    .iter()
+                    .take(MAX_AMBIGUOUS_PROJECTS)
+                    .map(|project| format!("{} ({})", project.name, project.id))
+                    .collect();
+                if found.len() > MAX_AMBIGUOUS_PROJECTS {
+                    matches.push("...".to_string());
+                }
+                return Err(ApiError::ProjectNameAmbiguous(
+                    name.to_string(),
+                    matches.join(", "),
+                ));
+            }
+        }
+    }
+    Err(ApiError::ProjectNameNotFound(name.to_string()))
+}
+
+/// Lowercase letters and digits of the name, without spaces and punctuation
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn digits(value: &str) -> String {
+    value.chars().filter(char::is_ascii_digit).collect()
+}
+
+/// Levenshtein distance between strings
+fn edit_distance(left: &str, right: &str) -> usize {
+    let right: Vec<char> = right.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=right.len()).collect();
+    for (i, left_char) in left.chars().enumerate() {
+        let mut current_row = vec![i + 1];
+        for (j, right_char) in right.iter().enumerate() {
+            let substitution = previous_row[j] + usize::from(left_char != *right_char);
+            current_row.push(
+                substitution
+                    .min(previous_row[j + 1] + 1)
+                    .min(current_row[j] + 1),
+            );
+        }
+        previous_row = current_row;
+    }
+    previous_row[right.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::external_api::testops_api::models::get_launch_by_id::GetLaunchByIdResponse;
+    use crate::external_api::testops_api::models::test_case_row::TestCaseRow;
+    use mockito::Server;
+    use rstest::rstest;
+    use std::path::PathBuf;
+
+    fn projects() -> Vec<ProjectInfo> {
+        vec![
+            ProjectInfo::new(1, "Mobile iOS"),
+            ProjectInfo::new(2, "Mobile Android"),
+            ProjectInfo::new(3, "Web"),
+            ProjectInfo::new(4, "web-admin"),
+            ProjectInfo::new(5, "Billing Service"),
+        ]
+    }
+
+    fn cache_path() -> PathBuf {
+        std::env::temp_dir().join(format!("wot_projects_{}.json", uuid::Uuid::new_v4()))
+    }
+
+    #[rstest]
+    #[case("Web", 3)]
+    #[case("WEB-ADMIN", 4)]
+    #[case("web admin", 4)]
+    #[case("android", 2)]
+    #[case("billing", 5)]
+    #[case("Biling Servise", 5)]
+    fn test_find_project_by_name(#[case] name: &str, #[case] expected_id: u32) {
+        let project = find_project_by_name(name, &projects()).unwrap();
+        assert_eq!(project.id, expected_id);
+    }
+
+    #[test]
+    fn test_find_project_by_name_ambiguous() {
+        let error = find_project_by_name("mobile", &projects()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Project name <mobile> is ambiguous, matches: Mobile iOS (1), Mobile Android (2). \
+             Use the project id or a more exact name"
+        );
+    }
+
+    #[rstest]
+    #[case("Desktop")]
+    #[case("---")]
+    // Короткие имена ищутся без опечаток
+    #[case("Wbe")]
+    #[case("Billing Service 2")]
+    fn test_find_project_by_name_not_found(#[case] name: &str) {
+        let error = find_project_by_name(name, &projects()).unwrap_err();
+        assert!(matches!(error, ApiError::ProjectNameNotFound(value) if value == name));
+    }
+
+    #[rstest]
+    #[case("", "", 0)]
+    #[case("web", "wbe", 2)]
+    #[case("billing", "biling", 1)]
+    #[case("kitten", "sitting", 3)]
+    fn test_edit_distance(#[case] left: &str, #[case] right: &str, #[case] expected: usize) {
+        assert_eq!(edit_distance(left, right), expected);
+    }
+
+    #[test]
+    fn test_project_cache_read() {
+        let path = cache_path();
+        ProjectCache::write(&path, "https://testops.example.com/", &projects()).unwrap();
+        let ttl = Duration::from_secs(60);
+
+        let fresh = ProjectCache::read(&path, "https://testops.example.com/", ttl);
+        let another_instance = ProjectCache::read(&path, "https://other.example.com/", ttl);
+        let expired = ProjectCache::read(&path, "https://testops.example.com/", Duration::ZERO);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(fresh, Some(projects()));
+        assert_eq!(another_instance, None);
+        assert_eq!(expired, None);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_project_by_id() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        let mock_all_projects = server
+            .mock("GET", mockito::Matcher::Regex("^/api/rs/project\\?".into()))
+            .expect(0)
+            .create_async()
+            .await;
+        TestopsApi::mock_get_project_by_id(&mut server, &ProjectInfo::new(2, "Test Project")).await;
+
+        let project = resolve_project(&ProjectRef::Id(2), &testops_api).await;
+
+        assert_eq!(project.unwrap(), ProjectInfo::new(2, "Test Project"));
+        mock_all_projects.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_resolve_project_by_id_not_found() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        server
+            .mock("GET", "/api/rs/project/28888")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let result = resolve_project(&ProjectRef::Id(28888), &testops_api).await;
+
+        assert!(matches!(result, Err(ApiError::ProjectIdNotFound(28888))));
+    }
+
+    #[tokio::test]
+    /// Список проектов сохраняется в кэш и при следующем поиске не запрашивается
+    async fn test_resolve_project_by_name_cached() {
+        let mut server = Server::new_async().await;
+        let path = cache_path();
+        let testops_api = TestopsApi::mock(&server.url()).with_project_cache(path.clone());
+        TestopsApi::mock_get_all_projects(&mut server).await;
+
+        let first = resolve_project(&ProjectRef::Name("test project".to_string()), &testops_api)
+            .await
+            .unwrap();
+        server.reset();
+        let second = resolve_project(&ProjectRef::Name("Project3".to_string()), &testops_api)
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(first.id, 2);
+        assert_eq!(second.id, 3);
+    }
+
+    #[tokio::test]
+    /// Если в кэше проекта нет, список запрашивается заново
+    async fn test_resolve_project_by_name_refresh_cache() {
+        let mut server = Server::new_async().await;
+        let path = cache_path();
+        let base_url = format!("{}/", server.url());
+        ProjectCache::write(&path, &base_url, &[ProjectInfo::new(1, "Project1")]).unwrap();
+        let testops_api = TestopsApi::mock(&server.url()).with_project_cache(path.clone());
+        TestopsApi::mock_get_all_projects(&mut server).await;
+
+        let project = resolve_project(&ProjectRef::Name("Project3".to_string()), &testops_api)
+            .await
+            .unwrap();
+        let cached = ProjectCache::read(&path, &base_url, PROJECT_CACHE_TTL).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(project.id, 3);
+        assert_eq!(cached.len(), 3);
+    }
+
+    #[tokio::test]
+    #[rstest]
+    #[case(
+        OutputFormat::Table,
+        "ID  NAME\n1   Project1\n2   Test Project\n3   Project3"
+    )]
+    #[case(
+        OutputFormat::Json,
+        "[\n  {\n    \"id\": 1,\n    \"name\": \"Project1\"\n  },\n  {\n    \"id\": 2,\n    \"name\": \"Test Project\"\n  },\n  {\n    \"id\": 3,\n    \"name\": \"Project3\"\n  }\n]"
+    )]
+    async fn test_list_projects(#[case] format: OutputFormat, #[case] expected: &str) {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        TestopsApi::mock_get_all_projects(&mut server).await;
+        let args = ProjectListArgs {
+            refresh: false,
+            format,
+        };
+
+        let result = list_projects(&args, &testops_api).await;
+
+        assert_eq!(result.unwrap(), expected);
+    }
+
+    #[tokio::test]
+    #[rstest]
+    #[case(false, 1)]
+    #[case(true, 3)]
+    /// С --refresh кэш не используется
+    async fn test_list_projects_refresh(#[case] refresh: bool, #[case] expected_count: usize) {
+        let mut server = Server::new_async().await;
+        let path = cache_path();
+        let base_url = format!("{}/", server.url());
+        ProjectCache::write(&path, &base_url, &[ProjectInfo::new(1, "Project1")]).unwrap();
+        let testops_api = TestopsApi::mock(&server.url()).with_project_cache(path.clone());
+        TestopsApi::mock_get_all_projects(&mut server).await;
+        let args = ProjectListArgs {
+            refresh,
+            format: OutputFormat::Json,
+        };
+
+        let result = list_projects(&args, &testops_api).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let projects: Vec<ProjectInfo> = serde_json::from_str(&result).unwrap();
+        assert_eq!(projects.len(), expected_count);
+    }
+
+    #[tokio::test]
+    /// Проект по имени ищется в списке, подробности запрашиваются по id
+    async fn test_show_project_by_name() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        TestopsApi::mock_get_all_projects(&mut server).await;
+        let mut project = ProjectInfo::new(2, "Test Project");
+        project.abbr = Some("TP".to_string());
+        project.description = Some("Smoke tests".to_string());
+        project.is_public = Some(false);
+        TestopsApi::mock_get_project_by_id(&mut server, &project).await;
+        let args = ProjectShowArgs {
+            project: ProjectRef::Name("test project".to_string()),
+            format: OutputFormat::Table,
+        };
+
+        let result = show_project(&args, &testops_api).await;
+
+        assert_eq!(
+            result.unwrap(),
+            "ID            2\nName          Test Project\nAbbreviation  TP\n\
+             Description   Smoke tests\nVisibility    private"
+        );
+    }
+
+    /// Мокаем тест-кейсы и запуски проекта 2: два автоматизированных из трех и два свежих запуска
+    async fn mock_project_stats(server: &mut mockito::ServerGuard) {
+        TestopsApi::mock_get_project_by_id(server, &ProjectInfo::new(2, "Test Project")).await;
+        let test_cases = vec![
+            vec![
+                TestCaseRow::new(1, "Login", true),
+                TestCaseRow::new(2, "Logout", true),
+            ],
+            vec![TestCaseRow::new(3, "Design review", false)],
+        ];
+        TestopsApi::mock_get_pages(server, "/testcase?projectId=2", test_cases).await;
+        let now = chrono::Utc::now().timestamp_millis();
+        let launches = [
+            (3, now),
+            (2, now - MILLIS_IN_DAY),
+            (1, now - 10 * MILLIS_IN_DAY),
+        ]
+        .into_iter()
+        .map(|(id, created_date)| {
+            let mut launch = GetLaunchByIdResponse::new(id, "Nightly", 2);
+            launch.created_date = Some(created_date);
+            launch
+        })
+        .collect();
+        TestopsApi::mock_get_pages(
+            server,
+            "/launch?projectId=2&sort=createdDate,DESC",
+            vec![launches],
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_project_stats() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        mock_project_stats(&mut server).await;
+        let args = ProjectStatsArgs {
+            project: ProjectRef::Id(2),
+            days: 7,
+            format: OutputFormat::Table,
+        };
+
+        let result = project_stats(&args, &testops_api).await;
+
+        assert_eq!(
+            result.unwrap(),
+            "Project             Test Project (2)\nTest cases          3\n\
+             Automated           2 (66.7%)\nManual              1 (33.3%)\n\
+             Launches in 7 days  2"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_project_stats_json() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        mock_project_stats(&mut server).await;
+        let args = ProjectStatsArgs {
+            project: ProjectRef::Id(2),
+            days: 30,
+            format: OutputFormat::Json,
+        };
+
+        let result = project_stats(&args, &testops_api).await.unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&result).unwrap(),
+            serde_json::json!({
+                "projectId": 2,
+                "projectName": "Test Project",
+                "testCases": 3,
+                "automated": 2,
+                "manual": 1,
+                "days": 30,
+                "launches": 3
+            })
+        );
+    }
+}
@@ -1,48 +1,347 @@
-use crate::constants::Message;
+use crate::allure_results::{count_result_statuses, validate_report_files, ValidationReport};
+use crate::ci_environment::CiEnvironment;
+use crate::cli_app::{ReportArgs, ValidateArgs};
+use crate::command_logic::launch::{
+    check_quality_gate, get_launch_state, wait_for_launch_processing, LaunchState,
+};
+use crate::command_logic::project::resolve_project;
+use crate::constants::{Message, LAUNCH_POLL_INTERVAL};
+use crate::converters::convert_report_files;
 
 use crate::external_api::testops_api::models::launch_info::LaunchInfo;
 use crate::external_api::testops_api::models::response_launch_upload::ResponseLaunchUpload;
 use crate::external_api::testops_api::TestopsApi;
 use crate::external_api::ApiError;
-use crate::utils::{validate_project_id, zip_directory};
-use std::fs;
+use crate::utils::{
+    collect_report_dirs, format_byte_size, format_table, remove_stale_report_archives,
+    report_archive_size, split_into_batches, ArchiveFilter, ReportFile,
+};
+use reqwest::StatusCode;
 use std::io::{BufRead, Write};
+use std::time::Duration;
 
 /// Sending report to TestOps
+///
+/// Confirmation is asked only in a terminal, otherwise `--yes` is required
 pub async fn send_report<R, W>(
-    path_to_report_directory: &str,
-    project_id: u32,
+    report_args: &ReportArgs,
+    is_interactive: bool,
     testops_api_client: &TestopsApi,
     input: R,
-    output: W,
+    mut output: W,
 ) -> Result<String, ApiError>
 where
     R: BufRead,
     W: Write,
 {
-    validate_project_id(project_id, testops_api_client).await?;
-    confirm_upload_to_project(project_id, testops_api_client, input, output).await?;
-    let result = zip_directory(path_to_report_directory).await?;
-    let generate_launch_name = chrono::Local::now().format("%d/%m/%Y %H:%M").to_string();
-    let launch_info = LaunchInfo::new(
-        &Message::LaunchRunFrom(generate_launch_name).to_formatted_string(),
-        project_id,
-    );
-    let response: ResponseLaunchUpload = testops_api_client
-        .post_upload_report(&result, &launch_info)
-        .await?;
-    let _ = fs::remove_file(&result);
-    Ok(Message::LaunchLinkDownloaded(
+    let project_ref = report_args
+        .project
+        .as_ref()
+        .ok_or(ApiError::ProjectNotSet)?;
+    if report_args.directory_paths.is_empty() {
+        return Err(ApiError::ReportDirectoriesNotSet);
+    }
+    let filter = ArchiveFilter::new(&report_args.include, &report_args.exclude)?;
+    let report_files = collect_report_dirs(&report_args.directory_paths, &filter)?;
+    let mut report_files = convert_report_files(report_args.format, report_files)?;
+    let ci_environment = match report_args.no_ci_metadata {
+        true => None,
+        false => CiEnvironment::detect(),
+    };
+    if let Some(ci_environment) = &ci_environment {
+        ci_environment.add_report_files(&mut report_files, &report_args.directory_paths[0])?;
+    }
+    if !report_args.skip_validation {
+        check_report_files(&report_files, &mut output)?;
+    }
+    let project = resolve_project(project_ref, testops_api_client).await?;
+    if let Some(launch_id) = report_args.launch_id {
+        validate_launch_id(launch_id, project.id, testops_api_client).await?;
+    }
+    if report_args.dry_run {
+        let launch_info = build_launch_info(report_args, project.id, ci_environment.as_ref());
+        describe_upload(report_files, report_args, &launch_info, &mut output)?;
+        return Ok(Message::DryRunFinished(project.name).to_formatted_string());
+    }
+    if !report_args.yes {
+        if !is_interactive {
+            return Err(ApiError::ConfirmationRequired);
+        }
+        confirm_upload_to_project(&project.name, input, &mut output)?;
+    }
+    remove_stale_report_archives();
+    let launch_info = build_launch_info(report_args, project.id, ci_environment.as_ref());
+    let state_before_upload = match (report_args.wait, report_args.launch_id) {
+        (true, Some(launch_id)) => get_launch_state(launch_id, testops_api_client).await?,
+        _ => LaunchState::default(),
+    };
+    let launch_id = upload_in_batches(
+        report_files,
+        report_args.batch_size,
+        report_args.launch_id,
+        &launch_info,
+        testops_api_client,
+        &mut output,
+    )
+    .await?;
+    let launch_link = Message::LaunchLinkDownloaded(
         testops_api_client.client.base_url.to_string(),
-        response.launch_id.to_string(),
+        launch_id.to_string(),
+    )
+    .to_formatted_string();
+    if !report_args.wait {
+        return Ok(launch_link);
+    }
+
+    writeln!(output, "{}", launch_link)?;
+    writeln!(
+        output,
+        "{}",
+        Message::WaitLaunchProcessing(launch_id.to_string()).to_formatted_string()
+    )?;
+    let summary = wait_for_launch_processing(
+        launch_id,
+        state_before_upload,
+        Duration::from_secs(report_args.wait_timeout),
+        LAUNCH_POLL_INTERVAL,
+        testops_api_client,
+    )
+    .await?;
+    if report_args.gate.is_empty() {
+        return Ok(summary.to_formatted_string());
+    }
+
+    writeln!(output, "{}", summary.to_formatted_string())?;
+    check_quality_gate(&summary, &report_args.gate)?;
+    Ok(Message::QualityGatePassed(launch_id.to_string()).to_formatted_string())
+}
+
+/// Check allure results in the directory without uploading
+pub fn validate_report<W: Write>(
+    validate_args: &ValidateArgs,
+    mut output: W,
+) -> Result<String, ApiError> {
+    let report_files =
+        collect_report_dirs(&validate_args.directory_paths, &ArchiveFilter::default())?;
+    let report = check_report_files(&report_files, &mut output)?;
+    Ok(Message::ReportValid(
+        report.results_count,
+        report.containers_count,
+        report.warnings_count(),
     )
     .to_formatted_string())
 }
 
-/// Confirm upload to project
-async fn confirm_upload_to_project<R, W>(
+/// Print problems of the report files, fail if any of them is an error
+fn check_report_files<W: Write>(
+    report_files: &[ReportFile],
+    output: &mut W,
+) -> Result<ValidationReport, ApiError> {
+    let report = validate_report_files(report_files);
+    for issue in &report.issues {
+        writeln!(output, "{}", issue)?;
+    }
+    match report.errors_count() {
+        0 => Ok(report),
+        errors_count => Err(ApiError::InvalidReport(errors_count)),
+    }
+}
+
+/// Collect launch info from report arguments
+///
+/// CI links and tags are added after the user ones, a link with the same name is not replaced
+fn build_launch_info(
+    report_args: &ReportArgs,
+    project_id: u32,
+    ci_environment: Option<&CiEnvironment>,
+) -> LaunchInfo {
+    let date = chrono::Local::now().format("%d/%m/%Y %H:%M").to_string();
+    let launch_name = match &report_args.launch_name {
+        Some(template) => render_launch_name(template, &date, ci_environment),
+        None => Message::LaunchRunFrom(date).to_formatted_string(),
+    };
+    let mut tags = report_args.tags.clone();
+    let mut links = report_args.links.clone();
+    if let Some(ci_environment) = ci_environment {
+        for tag in ci_environment.tags() {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        for link in ci_environment.links() {
+            if !links.iter().any(|user_link| user_link.name == link.name) {
+                links.push(link);
+            }
+        }
+    }
+    LaunchInfo::new(&launch_name, project_id)
+        .with_tags(&tags)
+        .with_links(&links)
+        .with_autoclose(report_args.autoclose)
+}
+
+/// Launch name with the placeholders replaced, CI values are empty outside of CI
+fn render_launch_name(
+    template: &str,
+    date: &str,
+    ci_environment: Option<&CiEnvironment>,
+) -> String {
+    let ci_value = |value: fn(&CiEnvironment) -> Option<&String>| {
+        ci_environment
+            .and_then(value)
+            .map(String::as_str)
+            .unwrap_or_default()
+    };
+    let commit: String = ci_value(|ci| ci.commit.as_ref()).chars().take(8).collect();
+    template
+        .replace("{date}", date)
+        .replace("{branch}", ci_value(|ci| ci.branch.as_ref()))
+        .replace("{commit}", &commit)
+        .replace("{pipeline}", ci_value(|ci| ci.pipeline_id.as_ref()))
+        .trim()
+        .to_string()
+}
+
+/// Print files, result statuses, sizes and launch info that would be uploaded
+fn describe_upload<W: Write>(
+    report_files: Vec<ReportFile>,
+    report_args: &ReportArgs,
+    launch_info: &LaunchInfo,
+    output: &mut W,
+) -> Result<(), ApiError> {
+    let rows: Vec<Vec<String>> = report_files
+        .iter()
+        .map(|report_file| {
+            vec![
+                report_file.name_in_archive.clone(),
+                format_byte_size(report_file.size),
+            ]
+        })
+        .collect();
+    writeln!(output, "{}", format_table(&["FILE", "SIZE"], &rows))?;
+    let statuses: Vec<String> = count_result_statuses(&report_files)
+        .iter()
+        .map(|(status, count)| format!("{} {}", status, count))
+        .collect();
+    writeln!(output, "Results: {}", statuses.join(", "))?;
+
+    let files_count = report_files.len();
+    let total_size: u64 = report_files
+        .iter()
+        .map(|report_file| report_file.size)
+        .sum();
+    let batches = split_into_batches(report_files, report_args.batch_size);
+    let mut archive_size = 0;
+    for batch in &batches {
+        archive_size += report_archive_size(batch)?;
+    }
+    writeln!(
+        output,
+        "{}",
+        Message::DryRunSummary(
+            files_count,
+            format_byte_size(total_size),
+            format_byte_size(archive_size),
+            batches.len(),
+        )
+        .to_formatted_string()
+    )?;
+    match report_args.launch_id {
+        Some(launch_id) => writeln!(
+            output,
+            "{}",
+            Message::DryRunExistingLaunch(launch_id).to_formatted_string()
+        )?,
+        None => writeln!(output, "{}", serde_json::to_string_pretty(launch_info)?)?,
+    }
+    Ok(())
+}
+
+/// Check that the launch exists and belongs to the project
+async fn validate_launch_id(
+    launch_id: u32,
     project_id: u32,
     testops_api_client: &TestopsApi,
+) -> Result<(), ApiError> {
+    let launch = testops_api_client
+        .get_launch_by_id(launch_id)
+        .await
+        .map_err(|e| match e {
+            ApiError::Api(StatusCode::NOT_FOUND, _) => ApiError::LaunchIdNotFound(launch_id),
+            e => e,
+        })?;
+    if launch.project_id != project_id {
+        return Err(ApiError::LaunchFromAnotherProject(
+            launch_id,
+            launch.project_id,
+            project_id,
+        ));
+    }
+    Ok(())
+}
+
+/// Upload report files in size-bounded batches
+///
+/// Without an existing launch the first batch creates it, the rest are appended to it.
+/// Return launch id
+async fn upload_in_batches<W: Write>(
+    report_files: Vec<ReportFile>,
+    max_batch_size: u64,
+    existing_launch_id: Option<u32>,
+    launch_info: &LaunchInfo,
+    testops_api_client: &TestopsApi,
+    mut output: W,
+) -> Result<u32, ApiError> {
+    let batches = split_into_batches(report_files, max_batch_size);
+    let batches_count = batches.len();
+    let mut batches = batches.into_iter();
+    let mut batch_number: usize = 0;
+    let mut files_sent: usize = 0;
+
+    let launch_id = match existing_launch_id {
+        Some(launch_id) => launch_id,
+        None => {
+            let first_batch = batches.next().unwrap_or_default();
+            let files_count = first_batch.len();
+            let response: ResponseLaunchUpload = testops_api_client
+                .post_upload_report(first_batch, launch_info)
+                .await?;
+            batch_number += 1;
+            files_sent += files_count;
+            writeln!(
+                output,
+                "{}",
+                Message::BatchUploaded(batch_number, batches_count, files_count)
+                    .to_formatted_string()
+            )?;
+            response.launch_id
+        }
+    };
+
+    for batch in batches {
+        let files_count = batch.len();
+        testops_api_client
+            .post_upload_report_to_launch(launch_id, batch)
+            .await?;
+        batch_number += 1;
+        files_sent += files_count;
+        writeln!(
+            output,
+            "{}",
+            Message::BatchUploaded(batch_number, batches_count, files_count).to_formatted_string()
+        )?;
+    }
+    writeln!(
+        output,
+        "{}",
+        Message::UploadSummary(files_sent, batches_count).to_formatted_string()
+    )?;
+    Ok(launch_id)
+}
+
+/// Confirm upload to project
+fn confirm_upload_to_project<R, W>(
+    project_name: &str,
     mut input: R,
     mut output: W,
 ) -> Result<(), ApiError>
@@ -50,12 +349,8 @@ where
     R: BufRead,
     W: Write,
 {
-    let project_info = testops_api_client
-        .get_project_info_by_id(&project_id)
-        .await?;
-
     output.write_all(
-        Message::ApproveUploadReport(project_info.name)
+        Message::ApproveUploadReport(project_name.to_string())
             .to_formatted_string()
             .as_bytes(),
     )?;
@@ -74,10 +369,14 @@ where
 #[cfg(test)]
 mod tests {
 
+    use crate::external_api::testops_api::models::external_link::ExternalLink;
+    use crate::external_api::testops_api::models::get_launch_by_id::GetLaunchByIdResponse;
+    use crate::external_api::testops_api::models::launch_statistic::LaunchStatistic;
     use crate::external_api::testops_api::models::project_info::ProjectInfo;
 
     use super::*;
     use crate::constants::CARGO_MANIFEST_DIR;
+    use crate::converters::ReportFormat;
     use mockito::ServerGuard;
     use rstest::rstest;
     use std::path::Path;
@@ -86,42 +385,26 @@ mod tests {
         path::PathBuf,
     };
 
+    const VALID_RESULT: &str = r#"{"uuid": "1", "historyId": "h1"}"#;
+
     async fn precondition_send_report(
         path_to_report: PathBuf,
         mock_response_launch_upload: &ResponseLaunchUpload,
         mut server: &mut ServerGuard,
     ) -> Result<String, ApiError> {
-        let mock_response = ProjectInfo {
-            id: 2,
-            name: "Test Project".to_string(),
-        };
+        let mock_response = ProjectInfo::new(2, "Test Project");
 
         let testops_api = TestopsApi::mock(&server.url());
-        TestopsApi::mock_get_all_projects(&mut server).await;
         TestopsApi::mock_post_upload_report(&mut server, &mock_response_launch_upload).await;
-        TestopsApi::mock_get_project_by_id(
-            &mut server,
-            &ProjectInfo {
-                id: 2,
-                name: "Test Project".to_string(),
-            },
-        )
-        .await;
+        TestopsApi::mock_get_project_by_id(&mut server, &mock_response).await;
 
         let input = BufReader::new(Cursor::new(b"y".to_vec()));
         let mut output = Cursor::new(Vec::<u8>::new());
 
-        send_report(
-            path_to_report.to_str().unwrap(),
-            mock_response.id,
-            &testops_api,
-            input,
-            &mut output,
-        )
-        .await
+        let report_args = ReportArgs::new_test(path_to_report.to_str().unwrap(), mock_response.id);
+        send_report(&report_args, true, &testops_api, input, &mut output).await
     }
 
-    #[tokio::test]
     #[rstest]
     #[case(b"\n")]
     #[case(b"y\n")]
@@ -129,21 +412,11 @@ mod tests {
     #[case(b"")]
     #[case(b"y")]
     #[case(b"yes")]
-    async fn test_confirm_project(#[case] input_data: &[u8]) {
-        let mock_response = ProjectInfo {
-            id: 2,
-            name: "Test Project".to_string(),
-        };
-        let mut server = mockito::Server::new_async().await;
-
-        let testops_api = TestopsApi::mock(&server.url());
-        TestopsApi::mock_get_project_by_id(&mut server, &mock_response).await;
-
+    fn test_confirm_project(#[case] input_data: &[u8]) {
         let input = BufReader::new(Cursor::new(input_data.to_vec()));
 
         let mut output = Cursor::new(Vec::<u8>::new());
-        let result =
-            confirm_upload_to_project(mock_response.id, &testops_api, input, &mut output).await;
+        let result = confirm_upload_to_project("Test Project", input, &mut output);
         assert!(result.is_ok());
 
         let output_str = String::from_utf8(output.into_inner()).unwrap();
@@ -153,39 +426,533 @@ mod tests {
         );
     }
 
-    #[tokio::test]
     #[rstest]
     #[case(b"n\n")]
     #[case(b"yse\n")]
-    async fn test_not_confirm_project(#[case] input_data: &[u8]) {
-        let mock_response = ProjectInfo {
-            id: 2,
-            name: "Test Project".to_string(),
-        };
+    fn test_not_confirm_project(#[case] input_data: &[u8]) {
+        let input = BufReader::new(Cursor::new(input_data.to_vec()));
+
+        let mut output = Cursor::new(Vec::<u8>::new());
+        let result = confirm_upload_to_project("Test Project", input, &mut output);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            ApiError::UploadCancelledByUser.to_string()
+        );
+    }
+
+    #[tokio::test]
+    /// Без терминала и без --yes загрузка не начинается
+    async fn test_send_report_not_interactive() {
+        let mut server = mockito::Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        TestopsApi::mock_get_project_by_id(&mut server, &ProjectInfo::new(2, "Test Project")).await;
+        let report_dir =
+            std::env::temp_dir().join(format!("wot_not_interactive_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&report_dir).unwrap();
+        let report_args = ReportArgs::new_test(report_dir.to_str().unwrap(), 2);
+        let input = BufReader::new(Cursor::new(b"y\n".to_vec()));
+        let mut output = Cursor::new(Vec::<u8>::new());
+
+        let result = send_report(&report_args, false, &testops_api, input, &mut output).await;
+        std::fs::remove_dir_all(&report_dir).unwrap();
+
+        assert!(matches!(result, Err(ApiError::ConfirmationRequired)));
+        assert!(output.into_inner().is_empty());
+    }
+
+    #[tokio::test]
+    #[rstest]
+    #[case(true)]
+    #[case(false)]
+    /// С --yes подтверждение не спрашивается, даже без терминала
+    async fn test_send_report_with_yes(#[case] is_interactive: bool) {
+        let mock_response_launch_upload = ResponseLaunchUpload::default();
         let mut server = mockito::Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        TestopsApi::mock_get_project_by_id(&mut server, &ProjectInfo::new(2, "Test Project")).await;
+        TestopsApi::mock_post_upload_report(&mut server, &mock_response_launch_upload).await;
+        let report_dir = std::env::temp_dir().join(format!("wot_yes_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&report_dir).unwrap();
+        std::fs::write(report_dir.join("1-result.json"), VALID_RESULT).unwrap();
+        let mut report_args = ReportArgs::new_test(report_dir.to_str().unwrap(), 2);
+        report_args.yes = true;
+        let input = BufReader::new(Cursor::new(Vec::<u8>::new()));
+        let mut output = Cursor::new(Vec::<u8>::new());
+
+        let result = send_report(
+            &report_args,
+            is_interactive,
+            &testops_api,
+            input,
+            &mut output,
+        )
+        .await;
+        std::fs::remove_dir_all(&report_dir).unwrap();
 
+        assert_eq!(
+            result.unwrap(),
+            format!(
+                "Link to downloaded launch: {}/launch/{}",
+                server.url(),
+                mock_response_launch_upload.launch_id
+            )
+        );
+        let output_str = String::from_utf8(output.into_inner()).unwrap();
+        assert!(!output_str.contains("[y/n]"));
+    }
+
+    #[tokio::test]
+    /// Все пачки загружаются в существующий запуск, новый запуск не создается
+    async fn test_upload_in_batches_existing_launch() {
+        let mock_response_launch_upload = ResponseLaunchUpload::default();
+        let mut server = mockito::Server::new_async().await;
         let testops_api = TestopsApi::mock(&server.url());
-        TestopsApi::mock_get_project_by_id(
+        let mock_create = server
+            .mock("POST", "/api/rs/launch/upload")
+            .expect(0)
+            .create_async()
+            .await;
+        let mock_append = TestopsApi::mock_post_upload_report_to_launch(
             &mut server,
-            &ProjectInfo {
-                id: 2,
-                name: "Test Project".to_string(),
-            },
+            &mock_response_launch_upload,
+        )
+        .await;
+        let mut output = Cursor::new(Vec::<u8>::new());
+        let launch_id = upload_in_batches(
+            vec![],
+            20,
+            Some(mock_response_launch_upload.launch_id),
+            &LaunchInfo::default(),
+            &testops_api,
+            &mut output,
         )
         .await;
 
-        let input = BufReader::new(Cursor::new(input_data.to_vec()));
+        assert_eq!(launch_id.unwrap(), mock_response_launch_upload.launch_id);
+        mock_create.assert_async().await;
+        mock_append.assert_async().await;
+    }
 
+    #[tokio::test]
+    /// Запуск из --launch-id проверяется один раз, до подтверждения загрузки
+    async fn test_send_report_to_existing_launch() {
+        let mock_response_launch_upload = ResponseLaunchUpload::default();
+        let launch_id = mock_response_launch_upload.launch_id;
+        let mut server = mockito::Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        TestopsApi::mock_get_project_by_id(&mut server, &ProjectInfo::new(2, "Test Project")).await;
+        let mock_launch = server
+            .mock("GET", format!("/api/rs/launch/{}", launch_id).as_str())
+            .with_header("content-type", "application/json")
+            .with_body(
+                serde_json::to_string(&GetLaunchByIdResponse::new(launch_id, "Nightly", 2))
+                    .unwrap(),
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let mock_append = TestopsApi::mock_post_upload_report_to_launch(
+            &mut server,
+            &mock_response_launch_upload,
+        )
+        .await;
+        let report_dir =
+            std::env::temp_dir().join(format!("wot_launch_id_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&report_dir).unwrap();
+        std::fs::write(report_dir.join("1-result.json"), VALID_RESULT).unwrap();
+        let mut report_args = ReportArgs::new_test(report_dir.to_str().unwrap(), 2);
+        report_args.launch_id = Some(launch_id);
+        let input = BufReader::new(Cursor::new(b"y\n".to_vec()));
         let mut output = Cursor::new(Vec::<u8>::new());
-        let result =
-            confirm_upload_to_project(mock_response.id, &testops_api, input, &mut output).await;
-        assert!(result.is_err());
+
+        let result = send_report(&report_args, true, &testops_api, input, &mut output).await;
+        std::fs::remove_dir_all(&report_dir).unwrap();
+
+        assert!(result.is_ok());
+        mock_launch.assert_async().await;
+        mock_append.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_validate_launch_id() {
+        let mut server = mockito::Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        let launch = GetLaunchByIdResponse::new(555, "Nightly", 2);
+        TestopsApi::mock_get_launch_by_id(&mut server, &launch, 555).await;
+        assert!(validate_launch_id(555, 2, &testops_api).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_validate_launch_id_another_project() {
+        let mut server = mockito::Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        let launch = GetLaunchByIdResponse::new(555, "Nightly", 3);
+        TestopsApi::mock_get_launch_by_id(&mut server, &launch, 555).await;
+        let result = validate_launch_id(555, 2, &testops_api).await;
+        assert!(matches!(
+            result,
+            Err(ApiError::LaunchFromAnotherProject(555, 3, 2))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_validate_launch_id_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        server
+            .mock("GET", "/api/rs/launch/555")
+            .with_status(404)
+            .create_async()
+            .await;
+        let result = validate_launch_id(555, 2, &testops_api).await;
+        assert!(matches!(result, Err(ApiError::LaunchIdNotFound(555))));
+    }
+
+    #[test]
+    fn test_build_launch_info_default_name() {
+        let report_args = ReportArgs::new_test("some_dir", 2);
+        let json = serde_json::to_value(build_launch_info(&report_args, 2, None)).unwrap();
+        assert!(json["name"].as_str().unwrap().starts_with("Run from "));
+        assert_eq!(json["projectId"], 2);
+    }
+
+    #[test]
+    fn test_build_launch_info_from_args() {
+        let mut report_args = ReportArgs::new_test("some_dir", 2);
+        report_args.launch_name = Some("Nightly".to_string());
+        report_args.tags = vec!["smoke".to_string()];
+        report_args.links = vec![ExternalLink::new("CI", "https://ci.example.com/1")];
+        report_args.autoclose = true;
         assert_eq!(
-            result.unwrap_err().to_string(),
-            ApiError::UploadCancelledByUser.to_string()
+            build_launch_info(&report_args, 2, None),
+            LaunchInfo::new("Nightly", 2)
+                .with_tags(&["smoke".to_string()])
+                .with_links(&[ExternalLink::new("CI", "https://ci.example.com/1")])
+                .with_autoclose(true)
+        );
+    }
+
+    #[test]
+    /// Вне CI значения CI в шаблоне пустые
+    fn test_render_launch_name() {
+        let ci_environment = CiEnvironment::from_vars(|name| match name {
+            "GITLAB_CI" => Some("true".to_string()),
+            "CI_PIPELINE_ID" => Some("7".to_string()),
+            "CI_COMMIT_REF_NAME" => Some("main".to_string()),
+            "CI_COMMIT_SHA" => Some("1234abcd5678ef90".to_string()),
+            _ => None,
+        })
+        .unwrap();
+        let template = "Nightly {branch} #{pipeline} {commit} {date}";
+        assert_eq!(
+            render_launch_name(template, "01/02/2026 10:00", Some(&ci_environment)),
+            "Nightly main #7 1234abcd 01/02/2026 10:00"
+        );
+        assert_eq!(
+            render_launch_name("{date} {branch}", "01/02/2026 10:00", None),
+            "01/02/2026 10:00"
+        );
+    }
+
+    #[test]
+    /// Теги и ссылки CI добавляются после пользовательских, ссылка с тем же именем не заменяется
+    fn test_build_launch_info_with_ci() {
+        let ci_environment = CiEnvironment::from_vars(|name| match name {
+            "GITLAB_CI" => Some("true".to_string()),
+            "CI_JOB_URL" => Some("https://gitlab.example.com/jobs/42".to_string()),
+            "CI_PIPELINE_URL" => Some("https://gitlab.example.com/pipelines/7".to_string()),
+            "CI_PIPELINE_ID" => Some("7".to_string()),
+            "CI_COMMIT_REF_NAME" => Some("main".to_string()),
+            _ => None,
+        })
+        .unwrap();
+        let mut report_args = ReportArgs::new_test("some_dir", 2);
+        report_args.launch_name = Some("Nightly".to_string());
+        report_args.tags = vec!["smoke".to_string(), "branch:main".to_string()];
+        report_args.links = vec![ExternalLink::new("Job", "https://ci.example.com/1")];
+        assert_eq!(
+            build_launch_info(&report_args, 2, Some(&ci_environment)),
+            LaunchInfo::new("Nightly", 2)
+                .with_tags(&[
+                    "smoke".to_string(),
+                    "branch:main".to_string(),
+                    "pipeline:7".to_string()
+                ])
+                .with_links(&[
+                    ExternalLink::new("Job", "https://ci.example.com/1"),
+                    ExternalLink::new("Pipeline", "https://gitlab.example.com/pipelines/7"),
+                ])
+        );
+    }
+
+    #[tokio::test]
+    /// С --wait печатаем ссылку и возвращаем итоги обработки запуска
+    async fn test_send_report_with_wait() {
+        let mock_response_launch_upload = ResponseLaunchUpload::default();
+        let launch_id = mock_response_launch_upload.launch_id;
+        let mut server = mockito::Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        TestopsApi::mock_get_project_by_id(&mut server, &ProjectInfo::new(2, "Test Project")).await;
+        TestopsApi::mock_post_upload_report(&mut server, &mock_response_launch_upload).await;
+        // Закрытый запуск обработан, ожидание не ждет стабилизации статистики
+        let mut launch = GetLaunchByIdResponse::new(launch_id, "Nightly", 2);
+        launch.closed = true;
+        TestopsApi::mock_get_launch_by_id(&mut server, &launch, launch_id).await;
+        TestopsApi::mock_get_launch_statistic(
+            &mut server,
+            &[LaunchStatistic::new("passed", 1)],
+            launch_id,
+        )
+        .await;
+        let report_dir = std::env::temp_dir().join(format!("wot_wait_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&report_dir).unwrap();
+        std::fs::write(report_dir.join("1-result.json"), VALID_RESULT).unwrap();
+        let mut report_args = ReportArgs::new_test(report_dir.to_str().unwrap(), 2);
+        report_args.yes = true;
+        report_args.wait = true;
+        let input = BufReader::new(Cursor::new(Vec::<u8>::new()));
+        let mut output = Cursor::new(Vec::<u8>::new());
+
+        let result = send_report(&report_args, false, &testops_api, input, &mut output).await;
+        std::fs::remove_dir_all(&report_dir).unwrap();
+
+        assert_eq!(
+            result.unwrap(),
+            format!(
+                "Launch {} results: passed 1, failed 0, broken 0, skipped 0, total 1",
+                launch_id
+            )
+        );
+        let output_str = String::from_utf8(output.into_inner()).unwrap();
+        assert!(output_str.contains(&format!(
+            "Link to downloaded launch: {}/launch/{}",
+            server.url(),
+            launch_id
+        )));
+        assert!(output_str.contains("Waiting for launch"));
+    }
+
+    #[tokio::test]
+    /// Результат проверки качества после загрузки определяет код завершения
+    async fn test_send_report_with_gate() {
+        let mock_response_launch_upload = ResponseLaunchUpload::default();
+        let launch_id = mock_response_launch_upload.launch_id;
+        let mut server = mockito::Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        TestopsApi::mock_get_project_by_id(&mut server, &ProjectInfo::new(2, "Test Project")).await;
+        TestopsApi::mock_post_upload_report(&mut server, &mock_response_launch_upload).await;
+        // Закрытый запуск обработан, ожидание не ждет стабилизации статистики
+        let mut launch = GetLaunchByIdResponse::new(launch_id, "Nightly", 2);
+        launch.closed = true;
+        TestopsApi::mock_get_launch_by_id(&mut server, &launch, launch_id).await;
+        TestopsApi::mock_get_launch_statistic(
+            &mut server,
+            &[LaunchStatistic::new("broken", 1)],
+            launch_id,
+        )
+        .await;
+        let report_dir = std::env::temp_dir().join(format!("wot_gate_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&report_dir).unwrap();
+        std::fs::write(report_dir.join("1-result.json"), VALID_RESULT).unwrap();
+        let mut report_args = ReportArgs::new_test(report_dir.to_str().unwrap(), 2);
+        report_args.yes = true;
+        report_args.wait = true;
+        report_args.gate.fail_on = vec!["broken".to_string()];
+        let input = BufReader::new(Cursor::new(Vec::<u8>::new()));
+        let mut output = Cursor::new(Vec::<u8>::new());
+
+        let result = send_report(&report_args, false, &testops_api, input, &mut output).await;
+        std::fs::remove_dir_all(&report_dir).unwrap();
+
+        assert!(matches!(
+            result,
+            Err(ApiError::QualityGateFailed(id, _)) if id == launch_id
+        ));
+        let output_str = String::from_utf8(output.into_inner()).unwrap();
+        assert!(output_str.contains("broken 1"));
+    }
+
+    #[test]
+    fn test_validate_report() {
+        let report_dir =
+            std::env::temp_dir().join(format!("wot_validate_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&report_dir).unwrap();
+        std::fs::write(report_dir.join("1-result.json"), VALID_RESULT).unwrap();
+        std::fs::write(report_dir.join("orphan-attachment.txt"), "log").unwrap();
+        let validate_args = ValidateArgs {
+            directory_paths: vec![report_dir.to_str().unwrap().to_string()],
+        };
+        let mut output = Vec::<u8>::new();
+
+        let result = validate_report(&validate_args, &mut output);
+        std::fs::remove_dir_all(&report_dir).unwrap();
+
+        assert_eq!(
+            result.unwrap(),
+            "Report is valid: 1 results, 0 containers, 1 warning(s)"
+        );
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "warning: orphan-attachment.txt: attachment is not referenced by any result or container\n"
         );
     }
 
+    #[tokio::test]
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    /// Битый отчет не отправляется, пока проверка не отключена
+    async fn test_send_report_invalid_report(#[case] skip_validation: bool) {
+        let mock_response_launch_upload = ResponseLaunchUpload::default();
+        let mut server = mockito::Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        TestopsApi::mock_get_project_by_id(&mut server, &ProjectInfo::new(2, "Test Project")).await;
+        let mock_upload =
+            TestopsApi::mock_post_upload_report(&mut server, &mock_response_launch_upload).await;
+        let report_dir = std::env::temp_dir().join(format!("wot_invalid_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&report_dir).unwrap();
+        std::fs::write(report_dir.join("1-result.json"), r#"{"uuid": "1"}"#).unwrap();
+        let mut report_args = ReportArgs::new_test(report_dir.to_str().unwrap(), 2);
+        report_args.yes = true;
+        report_args.skip_validation = skip_validation;
+        let input = BufReader::new(Cursor::new(Vec::<u8>::new()));
+        let mut output = Cursor::new(Vec::<u8>::new());
+
+        let result = send_report(&report_args, false, &testops_api, input, &mut output).await;
+        std::fs::remove_dir_all(&report_dir).unwrap();
+
+        if skip_validation {
+            assert!(result.is_ok());
+            mock_upload.assert_async().await;
+        } else {
+            assert!(matches!(result, Err(ApiError::InvalidReport(1))));
+            mock_upload.expect(0).assert_async().await;
+            let output_str = String::from_utf8(output.into_inner()).unwrap();
+            assert_eq!(
+                output_str,
+                "error: 1-result.json: missing field `historyId`\n"
+            );
+        }
+    }
+
+    #[tokio::test]
+    /// JUnit отчет конвертируется и проходит проверку перед загрузкой
+    async fn test_send_report_junit_format() {
+        let mock_response_launch_upload = ResponseLaunchUpload::default();
+        let mut server = mockito::Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        TestopsApi::mock_get_project_by_id(&mut server, &ProjectInfo::new(2, "Test Project")).await;
+        let mock_upload =
+            TestopsApi::mock_post_upload_report(&mut server, &mock_response_launch_upload).await;
+        let report_dir = std::env::temp_dir().join(format!("wot_junit_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&report_dir).unwrap();
+        std::fs::write(
+            report_dir.join("TEST-api.xml"),
+            r#"<testsuite name="api"><testcase name="a"><system-out>log</system-out></testcase></testsuite>"#,
+        )
+        .unwrap();
+        let mut report_args = ReportArgs::new_test(report_dir.to_str().unwrap(), 2);
+        report_args.yes = true;
+        report_args.format = ReportFormat::Junit;
+        let input = BufReader::new(Cursor::new(Vec::<u8>::new()));
+        let mut output = Cursor::new(Vec::<u8>::new());
+
+        let result = send_report(&report_args, false, &testops_api, input, &mut output).await;
+        std::fs::remove_dir_all(&report_dir).unwrap();
+
+        assert!(result.is_ok());
+        mock_upload.assert_async().await;
+        let output_str = String::from_utf8(output.into_inner()).unwrap();
+        assert_eq!(
+            output_str,
+            "Batch 1/1 uploaded: 2 files\nFiles sent: 2 in 1 batch(es)\n"
+        );
+    }
+
+    #[tokio::test]
+    /// При --dry-run отчет описывается, но не загружается и подтверждение не спрашивается
+    async fn test_send_report_dry_run() {
+        let mut server = mockito::Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        TestopsApi::mock_get_project_by_id(&mut server, &ProjectInfo::new(2, "Test Project")).await;
+        let mock_upload =
+            TestopsApi::mock_post_upload_report(&mut server, &ResponseLaunchUpload::default())
+                .await;
+        let report_dir = std::env::temp_dir().join(format!("wot_dry_run_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&report_dir).unwrap();
+        std::fs::write(
+            report_dir.join("1-result.json"),
+            r#"{"uuid": "1", "historyId": "h1", "status": "passed"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            report_dir.join("2-result.json"),
+            r#"{"uuid": "2", "historyId": "h2", "status": "failed"}"#,
+        )
+        .unwrap();
+        let mut report_args = ReportArgs::new_test(report_dir.to_str().unwrap(), 2);
+        report_args.dry_run = true;
+        report_args.launch_name = Some("Nightly".to_string());
+        let input = BufReader::new(Cursor::new(Vec::<u8>::new()));
+        let mut output = Cursor::new(Vec::<u8>::new());
+
+        let result = send_report(&report_args, false, &testops_api, input, &mut output).await;
+        std::fs::remove_dir_all(&report_dir).unwrap();
+
+        assert_eq!(
+            result.unwrap(),
+            "Dry run: nothing was uploaded to the project 'Test Project'"
+        );
+        mock_upload.expect(0).assert_async().await;
+        let output_str = String::from_utf8(output.into_inner()).unwrap();
+        assert!(
+            output_str.contains("1-result.json  52 B\n"),
+            "{}",
+            output_str
+        );
+        assert!(
+            output_str.contains("Results: passed 1, failed 1, broken 0, skipped 0, unknown 0\n")
+        );
+        assert!(output_str.contains("Files: 2, total size 104 B, archive size "));
+        assert!(output_str.contains("in 1 batch(es)\n"));
+        assert!(
+            output_str.contains(r#""name": "Nightly""#),
+            "{}",
+            output_str
+        );
+        assert!(!output_str.contains("[y/n]"));
+    }
+
+    #[tokio::test]
+    /// При --dry-run с --launch-id вместо описания запуска выводится его номер
+    async fn test_send_report_dry_run_existing_launch() {
+        let mut server = mockito::Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        TestopsApi::mock_get_project_by_id(&mut server, &ProjectInfo::new(2, "Test Project")).await;
+        let launch = GetLaunchByIdResponse::new(555, "Nightly", 2);
+        TestopsApi::mock_get_launch_by_id(&mut server, &launch, 555).await;
+        let report_dir =
+            std::env::temp_dir().join(format!("wot_dry_run_launch_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&report_dir).unwrap();
+        std::fs::write(report_dir.join("1-result.json"), VALID_RESULT).unwrap();
+        let mut report_args = ReportArgs::new_test(report_dir.to_str().unwrap(), 2);
+        report_args.dry_run = true;
+        report_args.launch_id = Some(555);
+        let input = BufReader::new(Cursor::new(Vec::<u8>::new()));
+        let mut output = Cursor::new(Vec::<u8>::new());
+
+        let result = send_report(&report_args, false, &testops_api, input, &mut output).await;
+        std::fs::remove_dir_all(&report_dir).unwrap();
+
+        assert!(result.is_ok());
+        let output_str = String::from_utf8(output.into_inner()).unwrap();
+        assert!(
+            output_str.contains("Results: passed 0, failed 0, broken 0, skipped 0, unknown 1\n")
+        );
+        assert!(output_str.ends_with("Results would be added to the launch 555\n"));
+    }
+
     #[tokio::test]
     async fn test_send_report_error() {
         let mock_response_launch_upload = ResponseLaunchUpload::default();
@@ -203,6 +970,52 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    /// Проверяем, что большой отчет отправляется в один запуск несколькими пачками
+    async fn test_upload_in_batches() {
+        let mock_response_launch_upload = ResponseLaunchUpload::default();
+        let mut server = mockito::Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        TestopsApi::mock_post_upload_report(&mut server, &mock_response_launch_upload).await;
+        let mock_append = TestopsApi::mock_post_upload_report_to_launch(
+            &mut server,
+            &mock_response_launch_upload,
+        )
+        .await;
+
+        let report_dir =
+            std::env::temp_dir().join(format!("wot_upload_batches_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&report_dir).unwrap();
+        for index in 0..3 {
+            std::fs::write(
+                report_dir.join(format!("{index}-result.json")),
+                format!(r#"{{"uuid": "{index}"}}"#),
+            )
+            .unwrap();
+        }
+        let report_files =
+            crate::utils::collect_report_files(&report_dir, &ArchiveFilter::default()).unwrap();
+        let mut output = Cursor::new(Vec::<u8>::new());
+        let launch_id = upload_in_batches(
+            report_files,
+            20,
+            None,
+            &LaunchInfo::default(),
+            &testops_api,
+            &mut output,
+        )
+        .await;
+        std::fs::remove_dir_all(&report_dir).unwrap();
+
+        assert_eq!(launch_id.unwrap(), mock_response_launch_upload.launch_id);
+        mock_append.expect(2).assert_async().await;
+        let output_str = String::from_utf8(output.into_inner()).unwrap();
+        assert_eq!(
+            output_str,
+            "Batch 1/3 uploaded: 1 files\nBatch 2/3 uploaded: 1 files\nBatch 3/3 uploaded: 1 files\nFiles sent: 3 in 3 batch(es)\n"
+        );
+    }
+
     #[tokio::test]
     async fn test_send_report_success() {
         let mock_response_launch_upload = ResponseLaunchUpload::default();
@@ -1,5 +1,6 @@
 use crate::external_api::testops_api::TestopsApi;
 use crate::external_api::ApiError;
+use crate::repo_defaults::DEFAULT_TESTCASE_MARK;
 use crate::{
     cli_app::TestcaseArgs,
     create_template::ati_su_python_template_test::create_template_python_ati_su,
@@ -19,8 +20,17 @@ pub async fn import_testcase_by_id(
         .await
         .map_err(|_| ApiError::CouldNotFindTestCaseById(test_case_args.import_testcase_id))?;
     let file_name = test_case_args.get_filename_for_test();
-    let full_path_to_file =
-        create_template_python_ati_su(test_case_overview, test_case_scenario, &file_name).await?;
+    let full_path_to_file = create_template_python_ati_su(
+        test_case_overview,
+        test_case_scenario,
+        &file_name,
+        test_case_args.output_dir.as_deref(),
+        test_case_args
+            .mark
+            .as_deref()
+            .unwrap_or(DEFAULT_TESTCASE_MARK),
+    )
+    .await?;
     Ok(format!("File created: {}", full_path_to_file))
 }
 
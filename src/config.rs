@@ -1,19 +1,89 @@
+use super::external_api::auth::AuthSettings;
+use super::external_api::network::NetworkSettings;
+use super::external_api::retry::RetryPolicy;
 use super::external_api::ApiError;
-use crate::constants::{COMPLETE_SETUP, ENTER_INSTANCE_URL_TESTOPS, ENTER_TESTOPS_API_KEY};
+use crate::constants::{
+    Message, COMPLETE_SETUP, ENTER_INSTANCE_URL_TESTOPS, ENTER_TESTOPS_API_KEY,
+};
 use regex::Regex;
 use serde::Deserialize;
 use serde::Serialize;
-use std::fs::File;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use uuid::Uuid;
 
+/// Name of the profile stored in the top level fields of the config
+pub const DEFAULT_PROFILE: &str = "default";
+/// Variable with the instance url, overrides the url of the profile
+pub const ENV_TESTOPS_URL: &str = "WOT_TESTOPS_URL";
+/// Variable with the API token, overrides the token of the profile
+pub const ENV_TESTOPS_TOKEN: &str = "WOT_TESTOPS_TOKEN";
+/// Version of the config schema, increased when old files need a migration
+pub const CONFIG_VERSION: u32 = 1;
+/// Steps that upgrade the config json, the step at index N migrates version N to N + 1
+const MIGRATIONS: [fn(&mut Map<String, Value>); 1] = [migrate_unversioned];
+/// Keys changeable by `wot config set`, besides profile keys and `current_profile`
+const SETTING_KEYS: [&str; 16] = [
+    "retry.max_attempts",
+    "retry.initial_backoff_ms",
+    "retry.max_backoff_ms",
+    "retry.jitter",
+    "retry.retry_statuses",
+    "retry.retry_network_errors",
+    "network.connect_timeout_secs",
+    "network.read_timeout_secs",
+    "network.upload_timeout_secs",
+    "network.proxy",
+    "network.no_proxy",
+    "network.ca_bundle",
+    "network.insecure",
+    "auth.mode",
+    "auth.token_endpoint",
+    "auth.refresh_margin_secs",
+];
+/// Keys of a profile, set at the top level for the default one and as `profiles.<name>.<key>`
+const PROFILE_KEYS: [&str; 4] = [
+    "testops_base_url",
+    "testops_api_token",
+    "token_command",
+    "token_file",
+];
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Config {
+    /// Schema version, older files are migrated when read
+    #[serde(default)]
+    pub version: u32,
     /// Instance url
     pub testops_base_url: String,
-    /// Token for authorization in TestOps API
+    /// Token for authorization in TestOps API, may be left empty with `token_command` or `token_file`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub testops_api_token: String,
+    /// Command that prints the token, replaces `testops_api_token`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_command: Option<String>,
+    /// File with the token, replaces `testops_api_token`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_file: Option<PathBuf>,
+    /// Other TestOps instances by profile name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Profile used without `--profile` and `WOT_PROFILE`, the default one if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_profile: Option<String>,
+    /// Repeating of failed requests
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// Timeouts, proxy and certificates
+    #[serde(default)]
+    pub network: NetworkSettings,
+    /// API token or jwt authorization
+    #[serde(default)]
+    pub auth: AuthSettings,
 }
 
 impl Config {
@@ -26,19 +96,352 @@ impl Config {
         let testops_api_token = validate_testops_api_token(&get_data_from_user_input()?)?;
         println!("{}", COMPLETE_SETUP);
 
-        Ok(Self {
+        Ok(Self::with_profile(Profile {
             testops_base_url,
             testops_api_token: testops_api_token.trim().to_string(),
-        })
+            token_command: None,
+            token_file: None,
+        }))
+    }
+
+    /// Config with the default profile and default settings
+    pub fn with_profile(profile: Profile) -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            testops_base_url: profile.testops_base_url,
+            testops_api_token: profile.testops_api_token,
+            token_command: profile.token_command,
+            token_file: profile.token_file,
+            profiles: BTreeMap::new(),
+            current_profile: None,
+            retry: RetryPolicy::default(),
+            network: NetworkSettings::default(),
+            auth: AuthSettings::default(),
+        }
     }
 
-    /// Get data from app config
+    /// Config from the file, or only from the environment if there is no file
+    ///
+    /// Without the file both `WOT_TESTOPS_URL` and `WOT_TESTOPS_TOKEN` are required.
+    /// A file readable by other users is loaded with a warning
+    pub fn load(path_to_config: &Path, env: &EnvProfile) -> Result<Self, ApiError> {
+        if path_to_config.exists() {
+            if let Some(mode) = exposed_mode(path_to_config, 0o044) {
+                let path = path_to_config.display().to_string();
+                eprintln!(
+                    "{}",
+                    Message::ConfigReadableByOthers(path, mode).to_formatted_string()
+                );
+            }
+            return Self::get_config(path_to_config.to_path_buf());
+        }
+        match (&env.testops_base_url, &env.testops_api_token) {
+            (Some(url), Some(token)) => Ok(Self::with_profile(Profile::new(url, token)?)),
+            _ => Err(ApiError::ConfigNotFound(
+                path_to_config.display().to_string(),
+            )),
+        }
+    }
+
+    /// Get data from app config, migrated to the current version
     pub fn get_config(path_to_config: PathBuf) -> Result<Self, ApiError> {
         let file = File::open(path_to_config)?;
-        serde_json::from_reader(file).map_err(ApiError::Serde)
+        let mut value: Value = serde_json::from_reader(file)?;
+        if let Some(fields) = value.as_object_mut() {
+            let version = version_of(fields)?;
+            for migration in &MIGRATIONS[version as usize..] {
+                migration(fields);
+            }
+            fields.insert("version".to_string(), CONFIG_VERSION.into());
+        }
+        serde_json::from_value(value).map_err(ApiError::Serde)
+    }
+
+    /// Version of the config file as it is written, before the migration
+    pub fn file_version(path_to_config: &Path) -> Result<u32, ApiError> {
+        let file = File::open(path_to_config)?;
+        match serde_json::from_reader(file)? {
+            Value::Object(fields) => version_of(&fields),
+            _ => Ok(0),
+        }
+    }
+
+    /// Write the config, creating its directory if needed
+    ///
+    /// A new file is readable only by the owner, it contains the token
+    pub fn save(&self, path_to_config: &Path) -> Result<(), ApiError> {
+        if let Some(parent_dir) = path_to_config.parent() {
+            std::fs::create_dir_all(parent_dir)?;
+        }
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let file = options.open(path_to_config)?;
+        serde_json::to_writer_pretty(file, self).map_err(|_| ApiError::CantCreateConfig)
+    }
+
+    /// Name of the profile to use: the given one, the current one or the default one
+    pub fn profile_name<'a>(&'a self, name: Option<&'a str>) -> &'a str {
+        name.or(self.current_profile.as_deref())
+            .unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// Url and token of the profile
+    pub fn profile(&self, name: &str) -> Result<Profile, ApiError> {
+        if name == DEFAULT_PROFILE {
+            return Ok(Profile {
+                testops_base_url: self.testops_base_url.clone(),
+                testops_api_token: self.testops_api_token.clone(),
+                token_command: self.token_command.clone(),
+                token_file: self.token_file.clone(),
+            });
+        }
+        self.profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ApiError::ProfileNotFound(name.to_string()))
+    }
+
+    pub fn add_profile(&mut self, name: &str, profile: Profile) -> Result<(), ApiError> {
+        if name == DEFAULT_PROFILE || self.profiles.contains_key(name) {
+            return Err(ApiError::ProfileAlreadyExists(name.to_string()));
+        }
+        self.profiles.insert(name.to_string(), profile);
+        Ok(())
+    }
+
+    /// Remove the profile, the default one is used instead of the removed current profile
+    pub fn remove_profile(&mut self, name: &str) -> Result<(), ApiError> {
+        if name == DEFAULT_PROFILE {
+            return Err(ApiError::DefaultProfileRemoval);
+        }
+        self.profiles
+            .remove(name)
+            .ok_or_else(|| ApiError::ProfileNotFound(name.to_string()))?;
+        if self.current_profile.as_deref() == Some(name) {
+            self.current_profile = None;
+        }
+        Ok(())
+    }
+
+    /// Change the setting by its dotted key, e.g. `network.proxy` or `profiles.staging.token_file`
+    ///
+    /// The value is parsed as json and taken as a string otherwise,
+    /// an empty value removes an optional setting
+    pub fn set_value(&mut self, key: &str, value: &str) -> Result<(), ApiError> {
+        let path: Vec<&str> = key.split('.').collect();
+        match path.as_slice() {
+            ["current_profile"] => return self.use_profile(value),
+            ["profiles", name, field] if PROFILE_KEYS.contains(field) => {
+                self.profile(name)?;
+            }
+            _ if PROFILE_KEYS.contains(&key) || SETTING_KEYS.contains(&key) => {}
+            _ => return Err(ApiError::UnknownConfigKey(key.to_string())),
+        }
+        let as_string = Value::String(value.to_string());
+        *self = match path.last() {
+            Some(&"testops_base_url") => {
+                self.with_value(&path, Value::String(validate_url(value.to_string())?))?
+            }
+            // Пустой токен оставляет только token_command или token_file
+            Some(&"testops_api_token") if !value.is_empty() => {
+                let token = validate_testops_api_token(value.trim())?;
+                self.with_value(&path, Value::String(token))?
+            }
+            Some(&"testops_api_token") => self.with_value(&path, as_string)?,
+            _ if value.is_empty() => self.with_value(&path, Value::Null)?,
+            _ => match serde_json::from_str(value) {
+                Ok(parsed) => self
+                    .with_value(&path, parsed)
+                    .or_else(|_| self.with_value(&path, as_string))?,
+                Err(_) => self.with_value(&path, as_string)?,
+            },
+        };
+        Ok(())
+    }
+
+    /// Copy of the config with the json value at the path replaced
+    fn with_value(&self, path: &[&str], value: Value) -> Result<Self, ApiError> {
+        let mut config = serde_json::to_value(self)?;
+        let mut target = &mut config;
+        for key in path {
+            target = &mut target[*key];
+        }
+        *target = value;
+        serde_json::from_value(config).map_err(ApiError::Serde)
+    }
+
+    pub fn use_profile(&mut self, name: &str) -> Result<(), ApiError> {
+        self.profile(name)?;
+        self.current_profile = match name {
+            DEFAULT_PROFILE => None,
+            name => Some(name.to_string()),
+        };
+        Ok(())
+    }
+}
+
+/// TestOps instance and the token for it
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Profile {
+    /// Instance url
+    pub testops_base_url: String,
+    /// Token for authorization in TestOps API
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub testops_api_token: String,
+    /// Command that prints the token, e.g. `pass show testops`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_command: Option<String>,
+    /// File with the token, it must not be accessible by group and others
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_file: Option<PathBuf>,
+}
+
+impl Profile {
+    /// Profile with checked url and token
+    pub fn new(testops_base_url: &str, testops_api_token: &str) -> Result<Self, ApiError> {
+        Ok(Self {
+            testops_base_url: validate_url(testops_base_url.to_string())?,
+            testops_api_token: validate_testops_api_token(testops_api_token.trim())?,
+            token_command: None,
+            token_file: None,
+        })
+    }
+
+    /// Profile with the token from `token_command` or `token_file`, the command is preferred
+    pub fn resolve_token(self) -> Result<Self, ApiError> {
+        let token = match (&self.token_command, &self.token_file) {
+            (Some(command), _) => run_token_command(command)?,
+            (None, Some(path)) => read_token_file(path)?,
+            (None, None) => return Ok(self),
+        };
+        Ok(Self {
+            testops_api_token: validate_testops_api_token(token.trim())?,
+            ..self
+        })
     }
 }
 
+/// Stdout of the command run by the shell, stdin and stderr are left for password prompts
+fn run_token_command(command: &str) -> Result<String, ApiError> {
+    let failed = |reason: String| ApiError::TokenCommandFailed(command.to_string(), reason);
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let output = Command::new(shell)
+        .args([flag, command])
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| failed(e.to_string()))?;
+    if !output.status.success() {
+        return Err(failed(output.status.to_string()));
+    }
+    String::from_utf8(output.stdout).map_err(|e| failed(e.to_string()))
+}
+
+/// Content of the token file, a file accessible by group or others is refused
+fn read_token_file(path: &Path) -> Result<String, ApiError> {
+    let invalid_file =
+        |reason: String| ApiError::InvalidTokenFile(path.display().to_string(), reason);
+    let content = std::fs::read_to_string(path).map_err(|e| invalid_file(e.to_string()))?;
+    if let Some(mode) = exposed_mode(path, 0o077) {
+        return Err(invalid_file(format!(
+            "permissions {:o} are too open, run chmod 600",
+            mode
+        )));
+    }
+    Ok(content)
+}
+
+/// Permission bits of the file if any of the `mask` bits are set
+#[cfg(unix)]
+fn exposed_mode(path: &Path, mask: u32) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = std::fs::metadata(path).ok()?.permissions().mode() & 0o777;
+    (mode & mask != 0).then_some(mode)
+}
+
+/// Unix permissions are not checked on other systems
+#[cfg(not(unix))]
+fn exposed_mode(_path: &Path, _mask: u32) -> Option<u32> {
+    None
+}
+
+/// Version field of the config json, a newer one than supported is an error
+fn version_of(fields: &Map<String, Value>) -> Result<u32, ApiError> {
+    let version = fields.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if version > CONFIG_VERSION as u64 {
+        return Err(ApiError::UnsupportedConfigVersion(version, CONFIG_VERSION));
+    }
+    Ok(version as u32)
+}
+
+/// Configs written before versioning already have all fields of the first version
+fn migrate_unversioned(_fields: &mut Map<String, Value>) {}
+
+/// Url and token from the environment variables
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EnvProfile {
+    pub testops_base_url: Option<String>,
+    pub testops_api_token: Option<String>,
+}
+
+impl EnvProfile {
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Read the variables, empty values are ignored
+    pub fn from_vars<F: Fn(&str) -> Option<String>>(var: F) -> Self {
+        let var = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+        Self {
+            testops_base_url: var(ENV_TESTOPS_URL),
+            testops_api_token: var(ENV_TESTOPS_TOKEN),
+        }
+    }
+
+    /// Both url and token are set, the config file is not needed
+    pub fn is_complete(&self) -> bool {
+        self.testops_base_url.is_some() && self.testops_api_token.is_some()
+    }
+
+    /// Profile with the url and the token replaced by the set variables
+    ///
+    /// Without `WOT_TESTOPS_TOKEN` the token of the profile is resolved from its command or file
+    pub fn apply(&self, profile: Profile) -> Result<Profile, ApiError> {
+        let profile = match &self.testops_api_token {
+            Some(token) => Profile {
+                testops_api_token: validate_testops_api_token(token.trim())?,
+                token_command: None,
+                token_file: None,
+                ..profile
+            },
+            None => profile.resolve_token()?,
+        };
+        let testops_base_url = match &self.testops_base_url {
+            Some(url) => validate_url(url.clone())?,
+            None => profile.testops_base_url,
+        };
+        Ok(Profile {
+            testops_base_url,
+            ..profile
+        })
+    }
+}
+
+/// Check the profile name, it is used in the command line and in `WOT_PROFILE`
+pub fn validate_profile_name(value: &str) -> Result<String, ApiError> {
+    let regex = Regex::new(r"^[A-Za-z0-9_.-]+$").unwrap();
+    if !regex.is_match(value) {
+        return Err(ApiError::InvalidProfileName(value.to_string()));
+    }
+    Ok(value.to_string())
+}
+
 fn validate_url(mut value: String) -> Result<String, ApiError> {
     let regex = Regex::new(r"^https?://.+$").unwrap();
     value.truncate(value.trim_end_matches('/').len());
@@ -106,6 +509,427 @@ mod tests {
             "Ожидали что api token: '{value}' НЕ пройдет валидацию и мы получим сообщение об ошибке");
     }
 
+    #[test]
+    /// Конфиг без секции retry использует политику по умолчанию
+    fn test_get_config_default_retry() {
+        let path = std::env::temp_dir().join(format!("wot_config_{}.json", Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"{"testops_base_url": "https://example.com", "testops_api_token": "token", "retry": {"max_attempts": 5}}"#,
+        )
+        .unwrap();
+        let config = Config::get_config(path.clone()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            config.retry,
+            RetryPolicy {
+                max_attempts: 5,
+                ..RetryPolicy::default()
+            }
+        );
+    }
+
+    fn config_with_profiles() -> Config {
+        let mut config: Config = serde_json::from_str(
+            r#"{"testops_base_url": "https://prod.example.com", "testops_api_token": "prod"}"#,
+        )
+        .unwrap();
+        config
+            .add_profile(
+                "staging",
+                Profile {
+                    testops_base_url: "https://staging.example.com".to_string(),
+                    testops_api_token: "staging".to_string(),
+                    token_command: None,
+                    token_file: None,
+                },
+            )
+            .unwrap();
+        config
+    }
+
+    #[test]
+    /// Явно указанный профиль важнее текущего, без них используется профиль по умолчанию
+    fn test_profile_name() {
+        let mut config = config_with_profiles();
+        assert_eq!(config.profile_name(None), DEFAULT_PROFILE);
+        config.use_profile("staging").unwrap();
+        assert_eq!(config.profile_name(None), "staging");
+        assert_eq!(config.profile_name(Some("default")), DEFAULT_PROFILE);
+    }
+
+    #[test]
+    fn test_profile() {
+        let config = config_with_profiles();
+        assert_eq!(
+            config.profile(DEFAULT_PROFILE).unwrap().testops_base_url,
+            "https://prod.example.com"
+        );
+        assert_eq!(
+            config.profile("staging").unwrap().testops_api_token,
+            "staging"
+        );
+        assert!(matches!(
+            config.profile("local"),
+            Err(ApiError::ProfileNotFound(name)) if name == "local"
+        ));
+    }
+
+    #[test_case("staging"; "existing profile")]
+    #[test_case("default"; "default profile")]
+    fn test_add_existing_profile(name: &str) {
+        let mut config = config_with_profiles();
+        let profile = config.profile("staging").unwrap();
+        assert!(matches!(
+            config.add_profile(name, profile),
+            Err(ApiError::ProfileAlreadyExists(_))
+        ));
+    }
+
+    #[test]
+    /// После удаления текущего профиля используется профиль по умолчанию
+    fn test_remove_current_profile() {
+        let mut config = config_with_profiles();
+        config.use_profile("staging").unwrap();
+        config.remove_profile("staging").unwrap();
+        assert!(config.profiles.is_empty());
+        assert_eq!(config.current_profile, None);
+        assert!(matches!(
+            config.remove_profile("staging"),
+            Err(ApiError::ProfileNotFound(_))
+        ));
+        assert!(matches!(
+            config.remove_profile(DEFAULT_PROFILE),
+            Err(ApiError::DefaultProfileRemoval)
+        ));
+    }
+
+    #[test]
+    fn test_use_profile() {
+        let mut config = config_with_profiles();
+        config.use_profile("staging").unwrap();
+        assert_eq!(config.current_profile.as_deref(), Some("staging"));
+        config.use_profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!(config.current_profile, None);
+        assert!(config.use_profile("local").is_err());
+    }
+
+    #[test]
+    /// Старый конфиг без профилей читается и сохраняется без новых полей
+    fn test_save_config_without_profiles() {
+        let path = std::env::temp_dir()
+            .join(format!("wot_config_{}", Uuid::new_v4()))
+            .join("config.json");
+        let config: Config = serde_json::from_str(
+            r#"{"testops_base_url": "https://prod.example.com", "testops_api_token": "prod"}"#,
+        )
+        .unwrap();
+        config.save(&path).unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert!(!saved.contains("profiles"));
+        assert!(!saved.contains("current_profile"));
+    }
+
+    #[test_case("staging", true; "simple")]
+    #[test_case("prod-eu_1.2", true; "with punctuation")]
+    #[test_case("", false; "empty")]
+    #[test_case("my profile", false; "with space")]
+    fn test_validate_profile_name(name: &str, is_valid: bool) {
+        assert_eq!(validate_profile_name(name).is_ok(), is_valid);
+    }
+
+    const TOKEN: &str = "c4e42f15-5b22-46ae-b2a1-10b5e2ffcb14";
+
+    fn env_profile(url: Option<&str>, token: Option<&str>) -> EnvProfile {
+        EnvProfile::from_vars(|name| match name {
+            ENV_TESTOPS_URL => url.map(str::to_string),
+            ENV_TESTOPS_TOKEN => token.map(str::to_string),
+            _ => None,
+        })
+    }
+
+    #[test]
+    /// Без файла конфига используются url и токен из окружения
+    fn test_load_config_from_env() {
+        let path = PathBuf::from("missing/config.json");
+        let config = Config::load(
+            &path,
+            &env_profile(Some("https://testops.example.com/"), Some(TOKEN)),
+        )
+        .unwrap();
+        assert_eq!(config.testops_base_url, "https://testops.example.com");
+        assert_eq!(config.testops_api_token, TOKEN);
+        assert!(matches!(
+            Config::load(
+                &path,
+                &env_profile(Some("https://testops.example.com"), None)
+            ),
+            Err(ApiError::ConfigNotFound(_))
+        ));
+        assert!(matches!(
+            Config::load(
+                &path,
+                &env_profile(Some("https://testops.example.com"), Some(""))
+            ),
+            Err(ApiError::ConfigNotFound(_))
+        ));
+    }
+
+    #[test]
+    /// Переменные окружения заменяют только заданные поля профиля
+    fn test_env_profile_apply() {
+        let profile = Profile {
+            testops_base_url: "https://prod.example.com".to_string(),
+            testops_api_token: "prod".to_string(),
+            token_command: None,
+            token_file: None,
+        };
+        assert_eq!(
+            env_profile(Some("https://staging.example.com"), None)
+                .apply(profile.clone())
+                .unwrap(),
+            Profile {
+                testops_base_url: "https://staging.example.com".to_string(),
+                testops_api_token: "prod".to_string(),
+                token_command: None,
+                token_file: None,
+            }
+        );
+        assert_eq!(
+            env_profile(None, None).apply(profile.clone()).unwrap(),
+            profile
+        );
+        assert!(matches!(
+            env_profile(None, Some("token")).apply(profile),
+            Err(ApiError::InvalidToken)
+        ));
+    }
+
+    fn profile_with_secret(token_command: Option<&str>, token_file: Option<PathBuf>) -> Profile {
+        Profile {
+            testops_base_url: "https://prod.example.com".to_string(),
+            testops_api_token: String::new(),
+            token_command: token_command.map(str::to_string),
+            token_file,
+        }
+    }
+
+    #[cfg(unix)]
+    fn write_token_file(mode: u32) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = std::env::temp_dir().join(format!("wot_token_{}", Uuid::new_v4()));
+        std::fs::write(&path, format!("{}\n", TOKEN)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[test]
+    /// Токен берется из вывода команды, команда важнее файла
+    fn test_resolve_token_from_command() {
+        let profile = profile_with_secret(
+            Some(&format!("echo {}", TOKEN)),
+            Some(PathBuf::from("missing/token")),
+        );
+        assert_eq!(profile.resolve_token().unwrap().testops_api_token, TOKEN);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_token_command_failed() {
+        let error = profile_with_secret(Some("exit 3"), None)
+            .resolve_token()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Token command <exit 3> failed: exit status: 3"
+        );
+        assert!(matches!(
+            profile_with_secret(Some("echo token"), None).resolve_token(),
+            Err(ApiError::InvalidToken)
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test_case(0o600, true; "owner only")]
+    #[test_case(0o400, true; "read only")]
+    #[test_case(0o640, false; "group readable")]
+    #[test_case(0o604, false; "others readable")]
+    fn test_resolve_token_from_file(mode: u32, is_valid: bool) {
+        let path = write_token_file(mode);
+        let result = profile_with_secret(None, Some(path.clone())).resolve_token();
+        std::fs::remove_file(&path).unwrap();
+        match is_valid {
+            true => assert_eq!(result.unwrap().testops_api_token, TOKEN),
+            false => assert_eq!(
+                result.unwrap_err().to_string(),
+                format!(
+                    "Could not read the token file <{}>: permissions {:o} are too open, run chmod 600",
+                    path.display(),
+                    mode
+                )
+            ),
+        }
+    }
+
+    #[test]
+    /// Токен из окружения заменяет команду, и она не запускается
+    fn test_env_token_replaces_command() {
+        let profile = profile_with_secret(Some("exit 1"), None);
+        let resolved = env_profile(None, Some(TOKEN))
+            .apply(profile.clone())
+            .unwrap();
+        assert_eq!(resolved.testops_api_token, TOKEN);
+        assert_eq!(resolved.token_command, None);
+        assert!(matches!(
+            env_profile(None, None).apply(profile),
+            Err(ApiError::TokenCommandFailed(_, _))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    /// Новый конфиг доступен только владельцу, открытый для других конфиг определяется
+    fn test_save_config_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let path = std::env::temp_dir()
+            .join(format!("wot_config_{}", Uuid::new_v4()))
+            .join("config.json");
+        Config::with_profile(Profile::new("https://prod.example.com", TOKEN).unwrap())
+            .save(&path)
+            .unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        let private_mode = exposed_mode(&path, 0o044);
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let public_mode = exposed_mode(&path, 0o044);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(mode, 0o600);
+        assert_eq!(private_mode, None);
+        assert_eq!(public_mode, Some(0o644));
+    }
+
+    #[test]
+    /// Конфиг с командой вместо токена читается и сохраняется без пустого токена
+    fn test_config_with_token_command() {
+        let config: Config = serde_json::from_str(
+            r#"{"testops_base_url": "https://prod.example.com", "token_command": "pass show testops"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config
+                .profile(DEFAULT_PROFILE)
+                .unwrap()
+                .token_command
+                .as_deref(),
+            Some("pass show testops")
+        );
+        assert!(!serde_json::to_string(&config)
+            .unwrap()
+            .contains("testops_api_token"));
+    }
+
+    #[test_case("network.proxy", "http://proxy:3128"; "string")]
+    #[test_case("retry.max_attempts", "5"; "number")]
+    #[test_case("retry.retry_statuses", "[429, 503]"; "array")]
+    #[test_case("network.insecure", "true"; "bool")]
+    #[test_case("auth.mode", "jwt"; "enum")]
+    #[test_case("profiles.staging.token_command", "true"; "string that looks like json")]
+    fn test_set_value(key: &str, value: &str) {
+        let mut config = config_with_profiles();
+        config.set_value(key, value).unwrap();
+        let json = serde_json::to_value(&config).unwrap();
+        let expected = serde_json::from_str(value).unwrap_or(Value::String(value.to_string()));
+        let actual = key.split('.').fold(&json, |json, key| &json[key]);
+        match actual {
+            Value::String(actual) => assert_eq!(actual, value),
+            actual => assert_eq!(actual, &expected),
+        }
+    }
+
+    #[test]
+    /// Пустое значение удаляет необязательную настройку, url и токен проверяются
+    fn test_set_value_validation() {
+        let mut config = config_with_profiles();
+        config
+            .set_value("network.proxy", "http://proxy:3128")
+            .unwrap();
+        config.set_value("network.proxy", "").unwrap();
+        assert_eq!(config.network.proxy, None);
+        config
+            .set_value(
+                "profiles.staging.testops_base_url",
+                "https://new.example.com/",
+            )
+            .unwrap();
+        assert_eq!(
+            config.profile("staging").unwrap().testops_base_url,
+            "https://new.example.com"
+        );
+        config.set_value("testops_api_token", "").unwrap();
+        assert_eq!(config.testops_api_token, "");
+
+        assert!(matches!(
+            config.set_value("testops_base_url", "example.com"),
+            Err(ApiError::InvalidUrl)
+        ));
+        assert!(matches!(
+            config.set_value("testops_api_token", "token"),
+            Err(ApiError::InvalidToken)
+        ));
+        assert!(matches!(
+            config.set_value("retry.max_attempts", "many"),
+            Err(ApiError::Serde(_))
+        ));
+        assert!(matches!(
+            config.set_value("retry.max_attempts", ""),
+            Err(ApiError::Serde(_))
+        ));
+        assert!(matches!(
+            config.set_value("network.timeout", "5"),
+            Err(ApiError::UnknownConfigKey(key)) if key == "network.timeout"
+        ));
+        assert!(matches!(
+            config.set_value("profiles.local.testops_base_url", "https://example.com"),
+            Err(ApiError::ProfileNotFound(_))
+        ));
+        config.set_value("current_profile", "staging").unwrap();
+        assert_eq!(config.current_profile.as_deref(), Some("staging"));
+    }
+
+    #[test]
+    /// Конфиг без версии мигрирует в текущую, файл при чтении не меняется
+    fn test_get_config_migrates_unversioned() {
+        let path = std::env::temp_dir().join(format!("wot_config_{}.json", Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"{"testops_base_url": "https://example.com", "testops_api_token": "token"}"#,
+        )
+        .unwrap();
+        let config = Config::get_config(path.clone()).unwrap();
+        let file_version = Config::file_version(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(file_version, 0);
+    }
+
+    #[test]
+    fn test_get_config_newer_version() {
+        let path = std::env::temp_dir().join(format!("wot_config_{}.json", Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"{"version": 99, "testops_base_url": "https://example.com"}"#,
+        )
+        .unwrap();
+        let result = Config::get_config(path.clone());
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            result,
+            Err(ApiError::UnsupportedConfigVersion(99, CONFIG_VERSION))
+        ));
+    }
+
     #[test]
     fn test_get_config_by_invalid_path() {
         let path: PathBuf = PathBuf::from(format!(
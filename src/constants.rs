@@ -2,6 +2,20 @@ pub const CONFIG_DIR: &str = ".config/wot";
 pub const ENTER_INSTANCE_URL_TESTOPS: &str = "Enter the url of the testops instance: ";
 pub const ENTER_TESTOPS_API_KEY: &str = "Enter the TestOps API key: ";
 pub const COMPLETE_SETUP: &str = "To view the available commands, type: wot --help";
+/// Exit code for any failed command
+pub const EXIT_CODE_ERROR: u8 = 1;
+/// Exit code when the command needs user input, but no terminal is attached
+///
+/// Code 2 is taken by clap for invalid arguments
+pub const EXIT_CODE_NOT_INTERACTIVE: u8 = 3;
+/// Exit code when the launch does not pass the quality gate
+pub const EXIT_CODE_QUALITY_GATE_FAILED: u8 = 4;
+/// How often the launch is polled while waiting for processing
+pub const LAUNCH_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+/// File with the cached project list in the config directory
+pub const PROJECT_CACHE_FILE: &str = "projects_cache.json";
+/// How long the cached project list is used before requesting it again
+pub const PROJECT_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
 #[cfg(test)]
 pub const CARGO_MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
 
@@ -11,6 +25,26 @@ pub enum Message {
     LaunchRunFrom(String),
     LaunchLinkDownloaded(String, String),
     ApproveUploadReport(String),
+    BatchUploaded(usize, usize, usize),
+    UploadSummary(usize, usize),
+    WaitLaunchProcessing(String),
+    QualityGatePassed(String),
+    ReportValid(usize, usize, usize),
+    ReportConverted(usize, String),
+    DryRunSummary(usize, String, String, usize),
+    DryRunExistingLaunch(u32),
+    DryRunFinished(String),
+    RequestRetry(String, std::time::Duration, u32, u32),
+    InsecureConnection,
+    ProfileAdded(String),
+    ProfileRemoved(String),
+    ProfileActivated(String),
+    ConfigCreated(String),
+    ConfigValueSet(String),
+    ConfigMigrated(u32, u32),
+    ConfigUpToDate(u32),
+    ConfigCheckPassed(String),
+    ConfigReadableByOthers(String, u32),
 }
 
 impl Message {
@@ -25,6 +59,72 @@ impl Message {
                 "You want to load a report into a project: '{}' [y/n]? ",
                 value
             ),
+            Message::BatchUploaded(batch_number, batches_count, files_count) => format!(
+                "Batch {}/{} uploaded: {} files",
+                batch_number, batches_count, files_count
+            ),
+            Message::UploadSummary(files_count, batches_count) => {
+                format!("Files sent: {} in {} batch(es)", files_count, batches_count)
+            }
+            Message::WaitLaunchProcessing(launch_id) => {
+                format!("Waiting for launch {} to be processed...", launch_id)
+            }
+            Message::ReportValid(results_count, containers_count, warnings_count) => format!(
+                "Report is valid: {} results, {} containers, {} warning(s)",
+                results_count, containers_count, warnings_count
+            ),
+            Message::ReportConverted(results_count, output_dir) => {
+                format!("Converted {} results to <{}>", results_count, output_dir)
+            }
+            Message::QualityGatePassed(launch_id) => {
+                format!("Launch {} passed the quality gate", launch_id)
+            }
+            Message::DryRunSummary(files_count, total_size, archive_size, batches_count) => {
+                format!(
+                    "Files: {}, total size {}, archive size {} in {} batch(es)",
+                    files_count, total_size, archive_size, batches_count
+                )
+            }
+            Message::DryRunExistingLaunch(launch_id) => {
+                format!("Results would be added to the launch {}", launch_id)
+            }
+            Message::RequestRetry(reason, delay, attempt, max_attempts) => format!(
+                "Request failed: {}, attempt {}/{} in {:.1}s",
+                reason,
+                attempt,
+                max_attempts,
+                delay.as_secs_f64()
+            ),
+            Message::InsecureConnection => {
+                "Warning: TLS certificate verification is disabled".to_string()
+            }
+            Message::ProfileAdded(name) => format!("Profile '{}' added", name),
+            Message::ProfileRemoved(name) => format!("Profile '{}' removed", name),
+            Message::ProfileActivated(name) => {
+                format!("Profile '{}' is now used by default", name)
+            }
+            Message::ConfigCreated(path) => format!("Config created at <{}>", path),
+            Message::ConfigValueSet(key) => format!("Config value '{}' updated", key),
+            Message::ConfigMigrated(from, to) => {
+                format!("Config migrated from version {} to {}", from, to)
+            }
+            Message::ConfigUpToDate(version) => {
+                format!("Config is up to date, version {}", version)
+            }
+            Message::ConfigCheckPassed(url) => {
+                format!("Config is valid, TestOps at <{}> accepted the token", url)
+            }
+            Message::ConfigReadableByOthers(path, mode) => format!(
+                "Warning: config <{}> with the token is readable by other users (permissions {:o}), \
+                 run chmod 600 on it",
+                path, mode
+            ),
+            Message::DryRunFinished(project_name) => {
+                format!(
+                    "Dry run: nothing was uploaded to the project '{}'",
+                    project_name
+                )
+            }
         }
     }
 }
@@ -84,4 +184,178 @@ mod tests {
             expected
         );
     }
+
+    #[test]
+    fn test_batch_uploaded() {
+        assert_eq!(
+            Message::BatchUploaded(1, 3, 120).to_formatted_string(),
+            "Batch 1/3 uploaded: 120 files"
+        );
+    }
+
+    #[test]
+    fn test_wait_launch_processing() {
+        assert_eq!(
+            Message::WaitLaunchProcessing("555".to_string()).to_formatted_string(),
+            "Waiting for launch 555 to be processed..."
+        );
+    }
+
+    #[test]
+    fn test_report_valid() {
+        assert_eq!(
+            Message::ReportValid(10, 2, 1).to_formatted_string(),
+            "Report is valid: 10 results, 2 containers, 1 warning(s)"
+        );
+    }
+
+    #[test]
+    fn test_report_converted() {
+        assert_eq!(
+            Message::ReportConverted(12, "allure-results".to_string()).to_formatted_string(),
+            "Converted 12 results to <allure-results>"
+        );
+    }
+
+    #[test]
+    fn test_quality_gate_passed() {
+        assert_eq!(
+            Message::QualityGatePassed("555".to_string()).to_formatted_string(),
+            "Launch 555 passed the quality gate"
+        );
+    }
+
+    #[test]
+    fn test_dry_run_summary() {
+        assert_eq!(
+            Message::DryRunSummary(3, "1.5 KB".to_string(), "900 B".to_string(), 1)
+                .to_formatted_string(),
+            "Files: 3, total size 1.5 KB, archive size 900 B in 1 batch(es)"
+        );
+    }
+
+    #[test]
+    fn test_dry_run_existing_launch() {
+        assert_eq!(
+            Message::DryRunExistingLaunch(555).to_formatted_string(),
+            "Results would be added to the launch 555"
+        );
+    }
+
+    #[test]
+    fn test_dry_run_finished() {
+        assert_eq!(
+            Message::DryRunFinished("Test Project".to_string()).to_formatted_string(),
+            "Dry run: nothing was uploaded to the project 'Test Project'"
+        );
+    }
+
+    #[test]
+    fn test_request_retry() {
+        assert_eq!(
+            Message::RequestRetry(
+                "503 Service Unavailable".to_string(),
+                std::time::Duration::from_millis(1500),
+                2,
+                3
+            )
+            .to_formatted_string(),
+            "Request failed: 503 Service Unavailable, attempt 2/3 in 1.5s"
+        );
+    }
+
+    #[test]
+    fn test_insecure_connection() {
+        assert_eq!(
+            Message::InsecureConnection.to_formatted_string(),
+            "Warning: TLS certificate verification is disabled"
+        );
+    }
+
+    #[test]
+    fn test_profile_added() {
+        assert_eq!(
+            Message::ProfileAdded("staging".to_string()).to_formatted_string(),
+            "Profile 'staging' added"
+        );
+    }
+
+    #[test]
+    fn test_profile_removed() {
+        assert_eq!(
+            Message::ProfileRemoved("staging".to_string()).to_formatted_string(),
+            "Profile 'staging' removed"
+        );
+    }
+
+    #[test]
+    fn test_profile_activated() {
+        assert_eq!(
+            Message::ProfileActivated("staging".to_string()).to_formatted_string(),
+            "Profile 'staging' is now used by default"
+        );
+    }
+
+    #[test]
+    fn test_config_created() {
+        assert_eq!(
+            Message::ConfigCreated("/home/user/.config/wot/config.json".to_string())
+                .to_formatted_string(),
+            "Config created at </home/user/.config/wot/config.json>"
+        );
+    }
+
+    #[test]
+    fn test_config_value_set() {
+        assert_eq!(
+            Message::ConfigValueSet("network.proxy".to_string()).to_formatted_string(),
+            "Config value 'network.proxy' updated"
+        );
+    }
+
+    #[test]
+    fn test_config_migrated() {
+        assert_eq!(
+            Message::ConfigMigrated(0, 1).to_formatted_string(),
+            "Config migrated from version 0 to 1"
+        );
+    }
+
+    #[test]
+    fn test_config_up_to_date() {
+        assert_eq!(
+            Message::ConfigUpToDate(1).to_formatted_string(),
+            "Config is up to date, version 1"
+        );
+    }
+
+    #[test]
+    fn test_config_check_passed() {
+        assert_eq!(
+            Message::ConfigCheckPassed("https://testops.example.com".to_string())
+                .to_formatted_string(),
+            "Config is valid, TestOps at <https://testops.example.com> accepted the token"
+        );
+    }
+
+    #[test]
+    fn test_config_readable_by_others() {
+        assert_eq!(
+            Message::ConfigReadableByOthers(
+                "/home/user/.config/wot/config.json".to_string(),
+                0o644
+            )
+            .to_formatted_string(),
+            "Warning: config </home/user/.config/wot/config.json> with the token is readable \
+             by other users (permissions 644), run chmod 600 on it"
+        );
+    }
+
+    #[test]
+    fn test_upload_summary() {
+        assert_eq!(
+            Message::UploadSummary(360, 3).to_formatted_string(),
+            "Files sent: 360 in 3 batch(es)"
+        );
+    }
 }
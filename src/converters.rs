@@ -0,0 +1,218 @@
+pub mod allure_result;
+pub mod cucumber;
+pub mod junit;
+pub mod nunit;
+pub mod trx;
+
+use crate::external_api::ApiError;
+use crate::utils::ReportFile;
+use allure_result::{AllureAttachment, AllureResult};
+use chrono::{DateTime, NaiveDateTime};
+use clap::ValueEnum;
+use roxmltree::Node;
+use std::path::Path;
+use uuid::Uuid;
+
+/// Format of the test results in the report directory
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum ReportFormat {
+    // Без doc-комментариев, иначе `--help` и `-h` выводят значения по-разному
+    #[default]
+    Allure,
+    Junit,
+    Cucumber,
+    Trx,
+    Nunit,
+}
+
+impl ReportFormat {
+    /// Extension of the files the converter reads
+    fn file_extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Allure => "",
+            ReportFormat::Junit => ".xml",
+            ReportFormat::Cucumber => ".json",
+            ReportFormat::Trx => ".trx",
+            ReportFormat::Nunit => ".xml",
+        }
+    }
+}
+
+/// Allure results produced by a converter
+#[derive(Debug, Default)]
+pub struct ConvertedResults {
+    pub results: Vec<AllureResult>,
+    /// Attachment files as name and content
+    pub attachments: Vec<(String, Vec<u8>)>,
+}
+
+impl ConvertedResults {
+    /// Save content as an attachment file and return a reference to it
+    pub fn attach(
+        &mut self,
+        name: &str,
+        mime_type: &str,
+        extension: &str,
+        content: Vec<u8>,
+    ) -> AllureAttachment {
+        let source = format!("{}-attachment.{}", Uuid::new_v4(), extension);
+        self.attachments.push((source.clone(), content));
+        AllureAttachment {
+            name: name.to_string(),
+            source,
+            mime_type: mime_type.to_string(),
+        }
+    }
+
+    pub fn attach_text(&mut self, name: &str, text: &str) -> AllureAttachment {
+        self.attach(name, "text/plain", "txt", text.as_bytes().to_vec())
+    }
+
+    /// Report files with results and attachments, kept in memory
+    fn into_report_files(self, source_path: &Path) -> Result<Vec<ReportFile>, ApiError> {
+        let mut files = Vec::new();
+        for result in &self.results {
+            files.push((result.file_name(), serde_json::to_vec(result)?));
+        }
+        files.extend(self.attachments);
+        Ok(files
+            .into_iter()
+            .map(|(name_in_archive, content)| ReportFile {
+                path: source_path.to_path_buf(),
+                name_in_archive,
+                size: content.len() as u64,
+                content: Some(content),
+            })
+            .collect())
+    }
+}
+
+/// Convert report files to allure results
+///
+/// Files with other extensions than the format expects are skipped
+pub fn convert_report_files(
+    format: ReportFormat,
+    report_files: Vec<ReportFile>,
+) -> Result<Vec<ReportFile>, ApiError> {
+    let convert: fn(&str, &mut ConvertedResults) -> Result<(), String> = match format {
+        ReportFormat::Allure => return Ok(report_files),
+        ReportFormat::Junit => junit::convert,
+        ReportFormat::Cucumber => cucumber::convert,
+        ReportFormat::Trx => trx::convert,
+        ReportFormat::Nunit => nunit::convert,
+    };
+    let mut converted_files = Vec::new();
+    for report_file in report_files.iter().filter(|report_file| {
+        report_file
+            .name_in_archive
+            .ends_with(format.file_extension())
+    }) {
+        let name = report_file.name_in_archive.as_str();
+        let content = String::from_utf8(report_file.read()?)
+            .map_err(|_| ApiError::ConvertFailed(name.to_string(), "not UTF-8".to_string()))?;
+        let mut converted_results = ConvertedResults::default();
+        convert(&content, &mut converted_results)
+            .map_err(|e| ApiError::ConvertFailed(name.to_string(), e))?;
+        converted_files.extend(converted_results.into_report_files(&report_file.path)?);
+    }
+    converted_files.sort_by(|a, b| a.name_in_archive.cmp(&b.name_in_archive));
+    Ok(converted_files)
+}
+
+/// Text of the XML element including CDATA sections
+fn element_text(node: Node) -> String {
+    node.descendants()
+        .filter(Node::is_text)
+        .filter_map(|text| text.text())
+        .collect()
+}
+
+/// Milliseconds since epoch from ISO 8601 timestamp
+///
+/// Timestamps without a time zone are treated as UTC, space is allowed instead of `T`
+fn parse_timestamp(value: &str) -> Option<i64> {
+    let value = value.trim().replacen(' ', "T", 1);
+    DateTime::parse_from_rfc3339(&value)
+        .map(|date_time| date_time.timestamp_millis())
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(&value, "%Y-%m-%dT%H:%M:%S%.f")
+                .ok()
+                .map(|date_time| date_time.and_utc().timestamp_millis())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allure_results::{is_attachment_file, is_result_file};
+    use rstest::rstest;
+    use std::path::PathBuf;
+
+    fn report_file(name: &str, content: &str) -> ReportFile {
+        ReportFile {
+            path: PathBuf::from(name),
+            name_in_archive: name.to_string(),
+            size: content.len() as u64,
+            content: Some(content.as_bytes().to_vec()),
+        }
+    }
+
+    #[rstest]
+    #[case("2024-01-15T10:00:00", Some(1_705_312_800_000))]
+    #[case("2024-01-15T10:00:00.250", Some(1_705_312_800_250))]
+    #[case("2024-01-15T13:00:00+03:00", Some(1_705_312_800_000))]
+    #[case("2024-01-15 10:00:00Z", Some(1_705_312_800_000))]
+    #[case("2024-01-15T10:00:00.1234567+00:00", Some(1_705_312_800_123))]
+    #[case("yesterday", None)]
+    fn test_parse_timestamp(#[case] value: &str, #[case] expected: Option<i64>) {
+        assert_eq!(parse_timestamp(value), expected);
+    }
+
+    #[test]
+    fn test_convert_allure_files_as_is() {
+        let report_files = vec![report_file("1-result.json", "{}")];
+        let converted = convert_report_files(ReportFormat::Allure, report_files).unwrap();
+        assert_eq!(converted, vec![report_file("1-result.json", "{}")]);
+    }
+
+    #[test]
+    fn test_convert_junit_files() {
+        let report_files = vec![
+            report_file(
+                "reports/TEST-auth.xml",
+                r#"<testsuite name="auth"><testcase name="login"><system-out>ok</system-out></testcase></testsuite>"#,
+            ),
+            report_file("reports/build.log", "not a report"),
+        ];
+        let converted = convert_report_files(ReportFormat::Junit, report_files).unwrap();
+        assert_eq!(converted.len(), 2);
+        assert_eq!(
+            converted
+                .iter()
+                .filter(|file| is_result_file(&file.name_in_archive))
+                .count(),
+            1
+        );
+        assert_eq!(
+            converted
+                .iter()
+                .filter(|file| is_attachment_file(&file.name_in_archive))
+                .count(),
+            1
+        );
+        assert!(converted
+            .iter()
+            .all(|file| file.size == file.read().unwrap().len() as u64));
+    }
+
+    #[test]
+    fn test_convert_invalid_file() {
+        let report_files = vec![report_file("TEST-auth.xml", "<testsuite>")];
+        let result = convert_report_files(ReportFormat::Junit, report_files);
+        assert!(matches!(
+            result,
+            Err(ApiError::ConvertFailed(name, _)) if name == "TEST-auth.xml"
+        ));
+    }
+}
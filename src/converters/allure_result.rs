@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Stage of the finished test, converted results are always finished
+const STAGE_FINISHED: &str = "finished";
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AllureStatus {
+    Passed,
+    Failed,
+    Broken,
+    Skipped,
+    #[default]
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct StatusDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AllureLabel {
+    pub name: String,
+    pub value: String,
+}
+
+impl AllureLabel {
+    pub fn new(name: &str, value: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AllureAttachment {
+    pub name: String,
+    /// File name of the attachment next to the result
+    pub source: String,
+    #[serde(rename = "type")]
+    pub mime_type: String,
+}
+
+/// Step of the test, may contain nested steps
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AllureStep {
+    pub name: String,
+    pub status: AllureStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_details: Option<StatusDetails>,
+    pub stage: String,
+    pub start: i64,
+    pub stop: i64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<AllureStep>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<AllureAttachment>,
+}
+
+impl AllureStep {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            status: AllureStatus::default(),
+            status_details: None,
+            stage: STAGE_FINISHED.to_string(),
+            start: 0,
+            stop: 0,
+            steps: vec![],
+            attachments: vec![],
+        }
+    }
+}
+
+/// Allure test result, saved as `<uuid>-result.json`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AllureResult {
+    pub uuid: String,
+    pub history_id: String,
+    pub full_name: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub status: AllureStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_details: Option<StatusDetails>,
+    pub stage: String,
+    /// Start time in milliseconds since epoch
+    pub start: i64,
+    /// Stop time in milliseconds since epoch
+    pub stop: i64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<AllureLabel>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<AllureStep>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<AllureAttachment>,
+}
+
+impl AllureResult {
+    /// New finished result, full name is used as history id
+    /// to link runs of the same test
+    pub fn new(name: &str, full_name: &str) -> Self {
+        Self {
+            uuid: Uuid::new_v4().to_string(),
+            history_id: full_name.to_string(),
+            full_name: full_name.to_string(),
+            name: name.to_string(),
+            description: None,
+            status: AllureStatus::default(),
+            status_details: None,
+            stage: STAGE_FINISHED.to_string(),
+            start: 0,
+            stop: 0,
+            labels: vec![],
+            steps: vec![],
+            attachments: vec![],
+        }
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("{}-result.json", self.uuid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_allure_result_serialization() {
+        let mut result = AllureResult::new("login", "auth.LoginTest.login");
+        result.uuid = "1".to_string();
+        result.status = AllureStatus::Failed;
+        result.status_details = Some(StatusDetails {
+            message: Some("expected 200".to_string()),
+            trace: None,
+        });
+        result.labels = vec![AllureLabel::new("suite", "auth")];
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            json!({
+                "uuid": "1",
+                "historyId": "auth.LoginTest.login",
+                "fullName": "auth.LoginTest.login",
+                "name": "login",
+                "status": "failed",
+                "statusDetails": {"message": "expected 200"},
+                "stage": "finished",
+                "start": 0,
+                "stop": 0,
+                "labels": [{"name": "suite", "value": "auth"}]
+            })
+        );
+        assert_eq!(result.file_name(), "1-result.json");
+    }
+
+    #[test]
+    fn test_allure_step_serialization() {
+        let mut step = AllureStep::new("Given I open the page");
+        step.status = AllureStatus::Passed;
+        step.steps = vec![AllureStep::new("Before hook")];
+        assert_eq!(
+            serde_json::to_value(&step).unwrap(),
+            json!({
+                "name": "Given I open the page",
+                "status": "passed",
+                "stage": "finished",
+                "start": 0,
+                "stop": 0,
+                "steps": [{
+                    "name": "Before hook",
+                    "status": "unknown",
+                    "stage": "finished",
+                    "start": 0,
+                    "stop": 0
+                }]
+            })
+        );
+    }
+}
@@ -0,0 +1,633 @@
+use super::allure_result::{AllureLabel, AllureResult, AllureStatus, AllureStep, StatusDetails};
+use super::ConvertedResults;
+use crate::external_api::testops_api::allure_meta_data::AllureMetaData;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Feature {
+    #[serde(default)]
+    uri: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    tags: Vec<Tag>,
+    #[serde(default)]
+    elements: Vec<Element>,
+}
+
+#[derive(Deserialize)]
+struct Tag {
+    name: String,
+}
+
+/// Scenario or background of the feature
+#[derive(Deserialize)]
+struct Element {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    keyword: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(rename = "type", default)]
+    element_type: String,
+    #[serde(default)]
+    line: u32,
+    start_timestamp: Option<String>,
+    #[serde(default)]
+    tags: Vec<Tag>,
+    #[serde(default)]
+    before: Vec<Step>,
+    #[serde(default)]
+    steps: Vec<Step>,
+    #[serde(default)]
+    after: Vec<Step>,
+}
+
+/// Step or hook, hooks have no keyword and name
+#[derive(Deserialize)]
+struct Step {
+    #[serde(default)]
+    keyword: String,
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "match")]
+    step_match: Option<StepMatch>,
+    result: Option<StepResult>,
+    #[serde(default)]
+    embeddings: Vec<Embedding>,
+    #[serde(default)]
+    output: Vec<String>,
+    doc_string: Option<DocString>,
+    #[serde(default)]
+    rows: Vec<Row>,
+    #[serde(default)]
+    before: Vec<Step>,
+    #[serde(default)]
+    after: Vec<Step>,
+}
+
+#[derive(Deserialize)]
+struct StepMatch {
+    location: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StepResult {
+    #[serde(default)]
+    status: String,
+    /// Duration in nanoseconds
+    #[serde(default)]
+    duration: u64,
+    error_message: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Embedding {
+    /// Content in base64
+    #[serde(default)]
+    data: String,
+    mime_type: Option<String>,
+    media: Option<Media>,
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Media {
+    #[serde(rename = "type")]
+    media_type: String,
+}
+
+#[derive(Deserialize)]
+struct DocString {
+    #[serde(default)]
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct Row {
+    #[serde(default)]
+    cells: Vec<String>,
+}
+
+/// Convert Cucumber JSON report: every scenario becomes an allure result
+///
+/// Steps, hooks and background keep their nesting, tags become labels
+pub fn convert(json: &str, converted_results: &mut ConvertedResults) -> Result<(), String> {
+    let features: Vec<Feature> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    for feature in &features {
+        let mut background = None;
+        for element in &feature.elements {
+            if element.element_type == "background" {
+                background = Some(element);
+                continue;
+            }
+            let result = convert_scenario(feature, element, background.take(), converted_results)?;
+            converted_results.results.push(result);
+        }
+    }
+    Ok(())
+}
+
+fn convert_scenario(
+    feature: &Feature,
+    scenario: &Element,
+    background: Option<&Element>,
+    converted_results: &mut ConvertedResults,
+) -> Result<AllureResult, String> {
+    let full_name = format!("{}: {}", feature.name, scenario.name);
+    let mut result = AllureResult::new(&scenario.name, &full_name);
+    // У каждого примера Scenario Outline свой id, имя у них общее
+    result.history_id = match scenario.id.is_empty() {
+        true => format!("{}:{}", feature.uri, scenario.line),
+        false => scenario.id.clone(),
+    };
+    result.description = Some(scenario.description.trim())
+        .filter(|description| !description.is_empty())
+        .map(String::from);
+    result.labels = scenario_labels(feature, scenario);
+
+    let mut cursor = scenario
+        .start_timestamp
+        .as_deref()
+        .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+        .map(|date_time| date_time.timestamp_millis())
+        .unwrap_or_else(|| Utc::now().timestamp_millis());
+    result.start = cursor;
+    let mut before_hooks = Vec::new();
+    for hook in &scenario.before {
+        before_hooks.extend(convert_hook(
+            hook,
+            "Before hook",
+            &mut cursor,
+            converted_results,
+        )?);
+    }
+    let mut steps = Vec::new();
+    if let Some(background) = background {
+        steps.push(convert_background(
+            background,
+            &mut cursor,
+            converted_results,
+        )?);
+    }
+    for step in &scenario.steps {
+        steps.push(convert_step(step, &mut cursor, converted_results)?);
+    }
+    let mut after_hooks = Vec::new();
+    for hook in &scenario.after {
+        after_hooks.extend(convert_hook(
+            hook,
+            "After hook",
+            &mut cursor,
+            converted_results,
+        )?);
+    }
+    result.stop = cursor;
+
+    let (steps_status, steps_details) = worst_status(&steps, AllureStatus::Unknown);
+    let (hooks_status, hooks_details) = worst_status(
+        before_hooks.iter().chain(&after_hooks),
+        AllureStatus::Unknown,
+    );
+    // Упавший хук - проблема окружения, а не теста
+    let hooks_status = match hooks_status {
+        AllureStatus::Failed => AllureStatus::Broken,
+        status => status,
+    };
+    (result.status, result.status_details) =
+        if status_rank(hooks_status) > status_rank(steps_status) {
+            (hooks_status, hooks_details)
+        } else {
+            (steps_status, steps_details)
+        };
+    result.steps = before_hooks
+        .into_iter()
+        .chain(steps)
+        .chain(after_hooks)
+        .collect();
+    Ok(result)
+}
+
+/// Background steps are grouped into one step of the scenario
+fn convert_background(
+    background: &Element,
+    cursor: &mut i64,
+    converted_results: &mut ConvertedResults,
+) -> Result<AllureStep, String> {
+    let mut background_step =
+        AllureStep::new(&format!("{}: {}", background.keyword, background.name));
+    background_step.start = *cursor;
+    for step in &background.steps {
+        background_step
+            .steps
+            .push(convert_step(step, cursor, converted_results)?);
+    }
+    background_step.stop = *cursor;
+    (background_step.status, background_step.status_details) =
+        worst_status(&background_step.steps, AllureStatus::Passed);
+    Ok(background_step)
+}
+
+/// Labels from feature and scenario tags, feature name is used when there is no feature tag
+fn scenario_labels(feature: &Feature, scenario: &Element) -> Vec<AllureLabel> {
+    let mut labels: Vec<AllureLabel> = feature
+        .tags
+        .iter()
+        .chain(&scenario.tags)
+        .map(|tag| match AllureMetaData::from_tag(&tag.name) {
+            Some(meta_data) => {
+                let (name, value) = meta_data
+                    .label_name_and_value()
+                    .expect("metadata from tag always has a label");
+                AllureLabel::new(name, value)
+            }
+            None => AllureLabel::new("tag", tag.name.trim_start_matches('@')),
+        })
+        .collect();
+    if !feature.name.is_empty() {
+        if !labels.iter().any(|label| label.name == "feature") {
+            labels.push(AllureLabel::new("feature", &feature.name));
+        }
+        labels.push(AllureLabel::new("suite", &feature.name));
+    }
+    labels
+}
+
+fn convert_step(
+    step: &Step,
+    cursor: &mut i64,
+    converted_results: &mut ConvertedResults,
+) -> Result<AllureStep, String> {
+    let name = format!("{} {}", step.keyword.trim(), step.name);
+    let mut allure_step = AllureStep::new(name.trim());
+    allure_step.start = *cursor;
+    for hook in &step.before {
+        allure_step.steps.extend(convert_hook(
+            hook,
+            "BeforeStep hook",
+            cursor,
+            converted_results,
+        )?);
+    }
+    if let Some(doc_string) = &step.doc_string {
+        let attachment = converted_results.attach_text("Doc string", &doc_string.value);
+        allure_step.attachments.push(attachment);
+    }
+    if !step.rows.is_empty() {
+        let table: Vec<String> = step.rows.iter().map(|row| row.cells.join(",")).collect();
+        let attachment = converted_results.attach(
+            "Data table",
+            "text/csv",
+            "csv",
+            table.join("\n").into_bytes(),
+        );
+        allure_step.attachments.push(attachment);
+    }
+    apply_step_result(step, &mut allure_step, cursor, converted_results)?;
+    for hook in &step.after {
+        allure_step.steps.extend(convert_hook(
+            hook,
+            "AfterStep hook",
+            cursor,
+            converted_results,
+        )?);
+    }
+    allure_step.stop = *cursor;
+    Ok(allure_step)
+}
+
+/// Hook is shown only when it failed or attached something, passed empty hooks are noise
+fn convert_hook(
+    hook: &Step,
+    name: &str,
+    cursor: &mut i64,
+    converted_results: &mut ConvertedResults,
+) -> Result<Option<AllureStep>, String> {
+    let location = hook
+        .step_match
+        .as_ref()
+        .and_then(|step_match| step_match.location.as_deref());
+    let name = match location {
+        Some(location) => format!("{name}: {location}"),
+        None => name.to_string(),
+    };
+    let mut allure_step = AllureStep::new(&name);
+    allure_step.start = *cursor;
+    apply_step_result(hook, &mut allure_step, cursor, converted_results)?;
+    allure_step.stop = *cursor;
+    let is_shown =
+        allure_step.status != AllureStatus::Passed || !allure_step.attachments.is_empty();
+    Ok(is_shown.then_some(allure_step))
+}
+
+/// Status, duration, embeddings and output of the step or hook
+fn apply_step_result(
+    step: &Step,
+    allure_step: &mut AllureStep,
+    cursor: &mut i64,
+    converted_results: &mut ConvertedResults,
+) -> Result<(), String> {
+    if let Some(result) = &step.result {
+        allure_step.status = cucumber_status(&result.status);
+        allure_step.status_details =
+            result
+                .error_message
+                .as_deref()
+                .map(|error_message| StatusDetails {
+                    message: error_message.lines().next().map(String::from),
+                    trace: Some(error_message.to_string()),
+                });
+        *cursor += (result.duration / 1_000_000) as i64;
+    }
+    for embedding in &step.embeddings {
+        let mime_type = embedding
+            .mime_type
+            .as_deref()
+            .or(embedding
+                .media
+                .as_ref()
+                .map(|media| media.media_type.as_str()))
+            .unwrap_or("application/octet-stream");
+        let content = STANDARD
+            .decode(embedding.data.trim())
+            .map_err(|e| format!("invalid embedding in step `{}`: {}", allure_step.name, e))?;
+        let attachment = converted_results.attach(
+            embedding.name.as_deref().unwrap_or("Attachment"),
+            mime_type,
+            extension_by_mime_type(mime_type),
+            content,
+        );
+        allure_step.attachments.push(attachment);
+    }
+    if !step.output.is_empty() {
+        let attachment = converted_results.attach_text("Output", &step.output.join("\n"));
+        allure_step.attachments.push(attachment);
+    }
+    Ok(())
+}
+
+fn cucumber_status(status: &str) -> AllureStatus {
+    match status {
+        "passed" => AllureStatus::Passed,
+        "failed" => AllureStatus::Failed,
+        "skipped" | "pending" => AllureStatus::Skipped,
+        "undefined" | "ambiguous" => AllureStatus::Broken,
+        _ => AllureStatus::Unknown,
+    }
+}
+
+fn status_rank(status: AllureStatus) -> u8 {
+    match status {
+        AllureStatus::Unknown => 0,
+        AllureStatus::Passed => 1,
+        AllureStatus::Skipped => 2,
+        AllureStatus::Broken => 3,
+        AllureStatus::Failed => 4,
+    }
+}
+
+/// The worst status of steps with details of the first step that has it
+fn worst_status<'a>(
+    steps: impl IntoIterator<Item = &'a AllureStep>,
+    default: AllureStatus,
+) -> (AllureStatus, Option<StatusDetails>) {
+    steps
+        .into_iter()
+        .fold((default, None), |(status, details), step| {
+            if status_rank(step.status) > status_rank(status) {
+                (step.status, step.status_details.clone())
+            } else {
+                (status, details)
+            }
+        })
+}
+
+fn extension_by_mime_type(mime_type: &str) -> &'static str {
+    match mime_type {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/svg+xml" => "svg",
+        "text/plain" => "txt",
+        "text/html" => "html",
+        "text/csv" => "csv",
+        "application/json" => "json",
+        "application/xml" | "text/xml" => "xml",
+        "video/mp4" => "mp4",
+        _ => "bin",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const CUCUMBER_REPORT: &str = r#"[
+  {
+    "uri": "features/login.feature",
+    "name": "Login",
+    "tags": [{"name": "@epic:Auth"}, {"name": "@smoke"}],
+    "elements": [
+      {
+        "keyword": "Background",
+        "name": "Opened site",
+        "type": "background",
+        "steps": [
+          {"keyword": "Given ", "name": "site is opened", "result": {"status": "passed", "duration": 1000000000}}
+        ]
+      },
+      {
+        "id": "login;successful-login",
+        "keyword": "Scenario",
+        "name": "Successful login",
+        "description": "  User logs in with valid password  ",
+        "type": "scenario",
+        "line": 7,
+        "start_timestamp": "2024-01-15T10:00:00.000Z",
+        "tags": [{"name": "@story=Password"}],
+        "before": [
+          {"match": {"location": "Hooks.setUp()"}, "result": {"status": "passed", "duration": 500000000}}
+        ],
+        "steps": [
+          {
+            "keyword": "When ",
+            "name": "user enters credentials",
+            "result": {"status": "passed", "duration": 2000000000},
+            "rows": [{"cells": ["login", "password"]}, {"cells": ["admin", "secret"]}],
+            "output": ["typed login"]
+          },
+          {
+            "keyword": "Then ",
+            "name": "profile is shown",
+            "result": {"status": "failed", "duration": 1000000000, "error_message": "expected profile\n\tat Steps.profile()"},
+            "doc_string": {"value": "Welcome, admin"},
+            "after": [
+              {"match": {"location": "Hooks.afterStep()"}, "result": {"status": "passed"}, "embeddings": [{"mime_type": "image/png", "data": "iVBORw0K", "name": "Screenshot"}]}
+            ]
+          }
+        ],
+        "after": [
+          {"result": {"status": "passed"}}
+        ]
+      }
+    ]
+  }
+]"#;
+
+    fn convert_report(json: &str) -> ConvertedResults {
+        let mut converted_results = ConvertedResults::default();
+        convert(json, &mut converted_results).unwrap();
+        converted_results
+    }
+
+    #[test]
+    fn test_convert_scenario() {
+        let converted_results = convert_report(CUCUMBER_REPORT);
+        assert_eq!(converted_results.results.len(), 1);
+        let result = &converted_results.results[0];
+        assert_eq!(result.name, "Successful login");
+        assert_eq!(result.full_name, "Login: Successful login");
+        assert_eq!(result.history_id, "login;successful-login");
+        assert_eq!(
+            result.description.as_deref(),
+            Some("User logs in with valid password")
+        );
+        assert_eq!(result.status, AllureStatus::Failed);
+        assert_eq!(
+            result.status_details,
+            Some(StatusDetails {
+                message: Some("expected profile".to_string()),
+                trace: Some("expected profile\n\tat Steps.profile()".to_string()),
+            })
+        );
+        // 2024-01-15T10:00:00Z + хук 0.5s + фон 1s + шаги 2s и 1s
+        assert_eq!(result.start, 1_705_312_800_000);
+        assert_eq!(result.stop, 1_705_312_804_500);
+    }
+
+    #[test]
+    fn test_convert_labels_from_tags() {
+        let result = &convert_report(CUCUMBER_REPORT).results[0];
+        assert_eq!(
+            result.labels,
+            vec![
+                AllureLabel::new("epic", "Auth"),
+                AllureLabel::new("tag", "smoke"),
+                AllureLabel::new("story", "Password"),
+                AllureLabel::new("feature", "Login"),
+                AllureLabel::new("suite", "Login"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_convert_nested_steps() {
+        let converted_results = convert_report(CUCUMBER_REPORT);
+        let steps = &converted_results.results[0].steps;
+        // Пустые успешные хуки не показываются
+        let names: Vec<&str> = steps.iter().map(|step| step.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "Background: Opened site",
+                "When user enters credentials",
+                "Then profile is shown"
+            ]
+        );
+        assert_eq!(steps[0].status, AllureStatus::Passed);
+        assert_eq!(steps[0].steps[0].name, "Given site is opened");
+        assert_eq!(steps[0].start, 1_705_312_800_500);
+        assert_eq!(steps[0].stop, 1_705_312_801_500);
+        assert_eq!(steps[2].status, AllureStatus::Failed);
+        assert_eq!(steps[2].steps[0].name, "AfterStep hook: Hooks.afterStep()");
+    }
+
+    #[test]
+    fn test_convert_attachments() {
+        let converted_results = convert_report(CUCUMBER_REPORT);
+        let steps = &converted_results.results[0].steps;
+        let attachment_names = |step: &AllureStep| -> Vec<(String, String)> {
+            step.attachments
+                .iter()
+                .map(|attachment| (attachment.name.clone(), attachment.mime_type.clone()))
+                .collect()
+        };
+        assert_eq!(
+            attachment_names(&steps[1]),
+            vec![
+                ("Data table".to_string(), "text/csv".to_string()),
+                ("Output".to_string(), "text/plain".to_string()),
+            ]
+        );
+        assert_eq!(
+            attachment_names(&steps[2]),
+            vec![("Doc string".to_string(), "text/plain".to_string())]
+        );
+        let screenshot = &steps[2].steps[0].attachments[0];
+        assert_eq!(screenshot.name, "Screenshot");
+        assert!(screenshot.source.ends_with("-attachment.png"));
+        let (_, content) = converted_results
+            .attachments
+            .iter()
+            .find(|(name, _)| *name == screenshot.source)
+            .unwrap();
+        assert_eq!(content, &vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A]);
+        let (_, table) = converted_results
+            .attachments
+            .iter()
+            .find(|(name, _)| *name == steps[1].attachments[0].source)
+            .unwrap();
+        assert_eq!(table, b"login,password\nadmin,secret");
+    }
+
+    #[test]
+    /// Упавший хук делает сценарий broken, а не failed
+    fn test_convert_failed_hook() {
+        let converted_results = convert_report(
+            r#"[{"name": "Cart", "elements": [{
+                "name": "Add item",
+                "type": "scenario",
+                "line": 3,
+                "steps": [{"keyword": "Given ", "name": "cart", "result": {"status": "skipped"}}],
+                "after": [{"result": {"status": "failed", "error_message": "db is down"}}]
+            }]}]"#,
+        );
+        let result = &converted_results.results[0];
+        assert_eq!(result.status, AllureStatus::Broken);
+        assert_eq!(
+            result.status_details.as_ref().unwrap().message.as_deref(),
+            Some("db is down")
+        );
+        assert_eq!(result.steps[1].name, "After hook");
+        assert_eq!(result.steps[1].status, AllureStatus::Failed);
+        assert_eq!(result.history_id, ":3");
+    }
+
+    #[rstest]
+    #[case("passed", AllureStatus::Passed)]
+    #[case("failed", AllureStatus::Failed)]
+    #[case("pending", AllureStatus::Skipped)]
+    #[case("undefined", AllureStatus::Broken)]
+    #[case("unused", AllureStatus::Unknown)]
+    fn test_cucumber_status(#[case] status: &str, #[case] expected: AllureStatus) {
+        assert_eq!(cucumber_status(status), expected);
+    }
+
+    #[rstest]
+    #[case("{}", "invalid type: map, expected a sequence")]
+    #[case(
+        r#"[{"elements": [{"type": "scenario", "steps": [{"embeddings": [{"data": "***"}]}]}]}]"#,
+        "invalid embedding in step ``: Invalid symbol 42, offset 0."
+    )]
+    fn test_convert_invalid_report(#[case] json: &str, #[case] expected_error: &str) {
+        let mut converted_results = ConvertedResults::default();
+        let error = convert(json, &mut converted_results).unwrap_err();
+        assert!(error.starts_with(expected_error), "{}", error);
+    }
+}
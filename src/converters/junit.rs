@@ -0,0 +1,305 @@
+use super::allure_result::{AllureLabel, AllureResult, AllureStatus, StatusDetails};
+use super::{element_text, parse_timestamp, ConvertedResults};
+use chrono::Utc;
+use roxmltree::{Document, Node};
+
+/// Convert JUnit XML report: test suites, test cases, failures, skips, stdout and stderr
+///
+/// Test cases of a suite are placed one after another starting from the suite timestamp
+pub fn convert(xml: &str, converted_results: &mut ConvertedResults) -> Result<(), String> {
+    let document = Document::parse(xml).map_err(|e| e.to_string())?;
+    let root = document.root_element();
+    if !matches!(root.tag_name().name(), "testsuites" | "testsuite") {
+        return Err(format!(
+            "unexpected root element <{}>, expected <testsuites> or <testsuite>",
+            root.tag_name().name()
+        ));
+    }
+    for suite in root
+        .descendants()
+        .filter(|node| node.has_tag_name("testsuite"))
+    {
+        let suite_name = suite.attribute("name").unwrap_or_default();
+        let mut start = suite_start(suite);
+        for testcase in suite
+            .children()
+            .filter(|node| node.has_tag_name("testcase"))
+        {
+            let result = convert_testcase(testcase, suite_name, start, converted_results);
+            start = result.stop;
+            converted_results.results.push(result);
+        }
+    }
+    Ok(())
+}
+
+fn convert_testcase(
+    testcase: Node,
+    suite_name: &str,
+    start: i64,
+    converted_results: &mut ConvertedResults,
+) -> AllureResult {
+    let name = testcase.attribute("name").unwrap_or("unnamed");
+    let class_name = testcase
+        .attribute("classname")
+        .filter(|class_name| !class_name.is_empty());
+    let full_name = match class_name.or(Some(suite_name).filter(|suite| !suite.is_empty())) {
+        Some(prefix) => format!("{prefix}.{name}"),
+        None => name.to_string(),
+    };
+    let mut result = AllureResult::new(name, &full_name);
+    result.start = start;
+    result.stop = start + duration_ms(testcase.attribute("time"));
+    if !suite_name.is_empty() {
+        result.labels.push(AllureLabel::new("suite", suite_name));
+    }
+    if let Some(class_name) = class_name {
+        result
+            .labels
+            .push(AllureLabel::new("testClass", class_name));
+    }
+    for child in testcase.children().filter(Node::is_element) {
+        let status = match child.tag_name().name() {
+            "failure" => AllureStatus::Failed,
+            "error" => AllureStatus::Broken,
+            "skipped" => AllureStatus::Skipped,
+            output @ ("system-out" | "system-err") => {
+                let text = element_text(child);
+                if !text.trim().is_empty() {
+                    let name = if output == "system-out" {
+                        "stdout"
+                    } else {
+                        "stderr"
+                    };
+                    let attachment = converted_results.attach_text(name, &text);
+                    result.attachments.push(attachment);
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        // Учитываем только первый результат, повторные запуски surefire идут отдельными тегами
+        if result.status == AllureStatus::Unknown {
+            result.status = status;
+            result.status_details = status_details(child);
+        }
+    }
+    if result.status == AllureStatus::Unknown {
+        result.status = AllureStatus::Passed;
+    }
+    result
+}
+
+fn status_details(node: Node) -> Option<StatusDetails> {
+    let message = node
+        .attribute("message")
+        .or(node.attribute("type"))
+        .map(String::from);
+    let trace = Some(element_text(node)).filter(|trace| !trace.trim().is_empty());
+    if message.is_none() && trace.is_none() {
+        return None;
+    }
+    Some(StatusDetails { message, trace })
+}
+
+/// Duration from the `time` attribute in seconds, e.g. `1.5`, `1,234.5` or `1,5`
+///
+/// A lone comma without a dot is a decimal separator of the locale, other commas group thousands
+fn duration_ms(time: Option<&str>) -> i64 {
+    time.map(
+        |time| match (time.matches(',').count(), time.contains('.')) {
+            (1, false) => time.replace(',', "."),
+            _ => time.replace(',', ""),
+        },
+    )
+    .and_then(|time| time.trim().parse::<f64>().ok())
+    .map(|seconds| (seconds * 1000.0).round() as i64)
+    .filter(|duration| *duration > 0)
+    .unwrap_or(0)
+}
+
+/// Start of the suite from the nearest `timestamp`, current time if there is none
+fn suite_start(suite: Node) -> i64 {
+    suite
+        .ancestors()
+        .find_map(|node| node.attribute("timestamp"))
+        .and_then(parse_timestamp)
+        .unwrap_or_else(|| Utc::now().timestamp_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const SUREFIRE_REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+  <testsuite name="auth.LoginTest" timestamp="2024-01-15T10:00:00" tests="4">
+    <testcase name="login" classname="auth.LoginTest" time="1.5">
+      <system-out>user logged in</system-out>
+    </testcase>
+    <testcase name="logout" classname="auth.LoginTest" time="0.5">
+      <failure message="expected 200" type="AssertionError"><![CDATA[at LoginTest.logout(LoginTest.java:10)]]></failure>
+      <system-err>warning</system-err>
+    </testcase>
+    <testcase name="refresh" classname="auth.LoginTest" time="0.1">
+      <error type="NullPointerException">trace</error>
+    </testcase>
+    <testcase name="sso" classname="auth.LoginTest" time="0">
+      <skipped message="not supported"/>
+    </testcase>
+  </testsuite>
+</testsuites>"#;
+
+    fn convert_report(xml: &str) -> ConvertedResults {
+        let mut converted_results = ConvertedResults::default();
+        convert(xml, &mut converted_results).unwrap();
+        converted_results
+    }
+
+    #[test]
+    fn test_convert_statuses() {
+        let converted_results = convert_report(SUREFIRE_REPORT);
+        let statuses: Vec<(&str, AllureStatus)> = converted_results
+            .results
+            .iter()
+            .map(|result| (result.full_name.as_str(), result.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("auth.LoginTest.login", AllureStatus::Passed),
+                ("auth.LoginTest.logout", AllureStatus::Failed),
+                ("auth.LoginTest.refresh", AllureStatus::Broken),
+                ("auth.LoginTest.sso", AllureStatus::Skipped),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_convert_status_details() {
+        let results = convert_report(SUREFIRE_REPORT).results;
+        assert_eq!(results[0].status_details, None);
+        assert_eq!(
+            results[1].status_details,
+            Some(StatusDetails {
+                message: Some("expected 200".to_string()),
+                trace: Some("at LoginTest.logout(LoginTest.java:10)".to_string()),
+            })
+        );
+        assert_eq!(
+            results[2].status_details,
+            Some(StatusDetails {
+                message: Some("NullPointerException".to_string()),
+                trace: Some("trace".to_string()),
+            })
+        );
+        assert_eq!(
+            results[3].status_details,
+            Some(StatusDetails {
+                message: Some("not supported".to_string()),
+                trace: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_convert_time_and_labels() {
+        let results = convert_report(SUREFIRE_REPORT).results;
+        // 2024-01-15T10:00:00 UTC
+        assert_eq!(results[0].start, 1_705_312_800_000);
+        assert_eq!(results[0].stop, 1_705_312_801_500);
+        assert_eq!(results[1].start, results[0].stop);
+        assert_eq!(results[1].stop, 1_705_312_802_000);
+        assert_eq!(
+            results[0].labels,
+            vec![
+                AllureLabel::new("suite", "auth.LoginTest"),
+                AllureLabel::new("testClass", "auth.LoginTest"),
+            ]
+        );
+        assert_eq!(results[0].history_id, results[0].full_name);
+    }
+
+    #[test]
+    fn test_convert_output_attachments() {
+        let converted_results = convert_report(SUREFIRE_REPORT);
+        let results = &converted_results.results;
+        assert_eq!(results[0].attachments.len(), 1);
+        assert_eq!(results[0].attachments[0].name, "stdout");
+        assert_eq!(results[1].attachments[0].name, "stderr");
+        assert_eq!(results[1].attachments[0].mime_type, "text/plain");
+        assert!(results[2].attachments.is_empty());
+        assert_eq!(
+            converted_results.attachments,
+            vec![
+                (
+                    results[0].attachments[0].source.clone(),
+                    b"user logged in".to_vec()
+                ),
+                (
+                    results[1].attachments[0].source.clone(),
+                    b"warning".to_vec()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    /// Go и Jest пишут один testsuite без classname
+    fn test_convert_single_suite_without_classname() {
+        let results = convert_report(
+            r#"<testsuite name="api"><testcase name="TestHealth" time="0.010"/></testsuite>"#,
+        )
+        .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].full_name, "api.TestHealth");
+        assert_eq!(results[0].stop - results[0].start, 10);
+        assert_eq!(results[0].labels, vec![AllureLabel::new("suite", "api")]);
+    }
+
+    #[test]
+    fn test_convert_nested_suites() {
+        let results = convert_report(
+            r#"<testsuites timestamp="2024-01-15T10:00:00Z">
+                <testsuite name="outer">
+                    <testsuite name="inner"><testcase name="a"/></testsuite>
+                    <testcase name="b"/>
+                </testsuite>
+            </testsuites>"#,
+        )
+        .results;
+        let names: Vec<&str> = results
+            .iter()
+            .map(|result| result.full_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["outer.b", "inner.a"]);
+        assert!(results
+            .iter()
+            .all(|result| result.start == 1_705_312_800_000));
+    }
+
+    #[rstest]
+    #[case(
+        "<html/>",
+        "unexpected root element <html>, expected <testsuites> or <testsuite>"
+    )]
+    #[case("<testsuite>", "the root node was opened but never closed")]
+    fn test_convert_invalid_report(#[case] xml: &str, #[case] expected_error: &str) {
+        let mut converted_results = ConvertedResults::default();
+        let error = convert(xml, &mut converted_results).unwrap_err();
+        assert!(error.starts_with(expected_error), "{}", error);
+    }
+
+    #[rstest]
+    #[case(Some("1.5"), 1500)]
+    #[case(Some("1,234.5"), 1_234_500)]
+    #[case(Some("1,5"), 1500)]
+    #[case(Some("1,234,567"), 1_234_567_000)]
+    #[case(Some("-1"), 0)]
+    #[case(Some("abc"), 0)]
+    #[case(None, 0)]
+    fn test_duration_ms(#[case] time: Option<&str>, #[case] expected: i64) {
+        assert_eq!(duration_ms(time), expected);
+    }
+}
@@ -0,0 +1,275 @@
+use super::allure_result::{AllureLabel, AllureResult, AllureStatus, StatusDetails};
+use super::{element_text, parse_timestamp, ConvertedResults};
+use chrono::Utc;
+use roxmltree::{Document, Node};
+
+/// Convert NUnit 3 XML report: results, durations, failures, output and categories
+///
+/// Categories of the test case and of the enclosing fixtures become tags
+pub fn convert(xml: &str, converted_results: &mut ConvertedResults) -> Result<(), String> {
+    let document = Document::parse(xml).map_err(|e| e.to_string())?;
+    let root = document.root_element();
+    if !matches!(root.tag_name().name(), "test-run" | "test-suite") {
+        return Err(format!(
+            "unexpected root element <{}>, expected <test-run> or <test-suite>",
+            root.tag_name().name()
+        ));
+    }
+    for test_case in root
+        .descendants()
+        .filter(|node| node.has_tag_name("test-case"))
+    {
+        let result = convert_test_case(test_case, converted_results);
+        converted_results.results.push(result);
+    }
+    Ok(())
+}
+
+fn convert_test_case(test_case: Node, converted_results: &mut ConvertedResults) -> AllureResult {
+    let name = test_case.attribute("name").unwrap_or("unnamed");
+    let full_name = test_case.attribute("fullname").unwrap_or(name);
+    let mut result = AllureResult::new(name, full_name);
+    result.status = nunit_status(
+        test_case.attribute("result").unwrap_or_default(),
+        test_case.attribute("label").unwrap_or_default(),
+    );
+    result.start = test_case
+        .attribute("start-time")
+        .and_then(parse_timestamp)
+        .unwrap_or_else(|| Utc::now().timestamp_millis());
+    result.stop = result.start + duration_ms(test_case.attribute("duration"));
+    if let Some(class_name) = test_case.attribute("classname") {
+        result.labels.push(AllureLabel::new("suite", class_name));
+        result
+            .labels
+            .push(AllureLabel::new("testClass", class_name));
+    }
+    for category in categories(test_case) {
+        result.labels.push(AllureLabel::new("tag", category));
+    }
+    for child in test_case.children().filter(Node::is_element) {
+        match child.tag_name().name() {
+            "failure" | "reason" => result.status_details = status_details(child),
+            "output" => {
+                let text = element_text(child);
+                if !text.trim().is_empty() {
+                    let attachment = converted_results.attach_text("output", &text);
+                    result.attachments.push(attachment);
+                }
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+fn nunit_status(result: &str, label: &str) -> AllureStatus {
+    match (result, label) {
+        ("Passed" | "Warning", _) => AllureStatus::Passed,
+        ("Failed", "Error" | "Invalid" | "Cancelled") => AllureStatus::Broken,
+        ("Failed", _) => AllureStatus::Failed,
+        ("Skipped" | "Inconclusive", _) => AllureStatus::Skipped,
+        _ => AllureStatus::Unknown,
+    }
+}
+
+/// Categories of the test case and all enclosing suites, without duplicates
+fn categories<'a>(test_case: Node<'a, '_>) -> Vec<&'a str> {
+    let mut categories = Vec::new();
+    for node in test_case.ancestors() {
+        let properties = node
+            .children()
+            .filter(|child| child.has_tag_name("properties"))
+            .flat_map(|properties| properties.children())
+            .filter(|property| property.has_tag_name("property"))
+            .filter(|property| property.attribute("name") == Some("Category"));
+        for category in properties.filter_map(|property| property.attribute("value")) {
+            if !categories.contains(&category) {
+                categories.push(category);
+            }
+        }
+    }
+    categories
+}
+
+fn status_details(node: Node) -> Option<StatusDetails> {
+    let child_text = |tag_name: &str| {
+        node.children()
+            .find(|child| child.has_tag_name(tag_name))
+            .map(element_text)
+            .filter(|text| !text.trim().is_empty())
+    };
+    let message = child_text("message");
+    let trace = child_text("stack-trace");
+    if message.is_none() && trace.is_none() {
+        return None;
+    }
+    Some(StatusDetails { message, trace })
+}
+
+/// Duration from the `duration` attribute in seconds
+fn duration_ms(duration: Option<&str>) -> i64 {
+    duration
+        .and_then(|duration| duration.trim().parse::<f64>().ok())
+        .map(|seconds| (seconds * 1000.0).round() as i64)
+        .filter(|duration| *duration > 0)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const NUNIT_REPORT: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<test-run id="0" testcasecount="4" result="Failed">
+  <test-suite type="Assembly" name="Auth.Tests.dll" fullname="Auth.Tests.dll">
+    <test-suite type="TestFixture" name="LoginTests" fullname="Auth.Tests.LoginTests" classname="Auth.Tests.LoginTests">
+      <properties>
+        <property name="Category" value="Auth" />
+      </properties>
+      <test-case id="1" name="Login" fullname="Auth.Tests.LoginTests.Login" classname="Auth.Tests.LoginTests" result="Passed" start-time="2024-01-15 10:00:00Z" duration="1.500">
+        <properties>
+          <property name="Category" value="Smoke" />
+          <property name="Category" value="Auth" />
+          <property name="Description" value="login" />
+        </properties>
+        <output><![CDATA[user logged in]]></output>
+      </test-case>
+      <test-case id="2" name="Logout" fullname="Auth.Tests.LoginTests.Logout" classname="Auth.Tests.LoginTests" result="Failed" start-time="2024-01-15T10:00:02.0000000Z" duration="0.25">
+        <failure>
+          <message><![CDATA[Expected: 200 But was: 500]]></message>
+          <stack-trace><![CDATA[at Auth.Tests.LoginTests.Logout() in LoginTests.cs:line 20]]></stack-trace>
+        </failure>
+      </test-case>
+      <test-case id="3" name="Refresh" fullname="Auth.Tests.LoginTests.Refresh" classname="Auth.Tests.LoginTests" result="Failed" label="Error" start-time="2024-01-15T10:00:03Z" duration="0.1">
+        <failure>
+          <message><![CDATA[System.NullReferenceException]]></message>
+        </failure>
+      </test-case>
+      <test-case id="4" name="Sso" fullname="Auth.Tests.LoginTests.Sso" classname="Auth.Tests.LoginTests" result="Skipped" label="Ignored" start-time="2024-01-15T10:00:04Z" duration="0">
+        <reason>
+          <message><![CDATA[not supported]]></message>
+        </reason>
+      </test-case>
+    </test-suite>
+  </test-suite>
+</test-run>"#;
+
+    fn convert_report(xml: &str) -> ConvertedResults {
+        let mut converted_results = ConvertedResults::default();
+        convert(xml, &mut converted_results).unwrap();
+        converted_results
+    }
+
+    #[test]
+    fn test_convert_results() {
+        let converted_results = convert_report(NUNIT_REPORT);
+        let statuses: Vec<(&str, AllureStatus)> = converted_results
+            .results
+            .iter()
+            .map(|result| (result.full_name.as_str(), result.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("Auth.Tests.LoginTests.Login", AllureStatus::Passed),
+                ("Auth.Tests.LoginTests.Logout", AllureStatus::Failed),
+                ("Auth.Tests.LoginTests.Refresh", AllureStatus::Broken),
+                ("Auth.Tests.LoginTests.Sso", AllureStatus::Skipped),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_convert_status_details() {
+        let results = convert_report(NUNIT_REPORT).results;
+        assert_eq!(results[0].status_details, None);
+        assert_eq!(
+            results[1].status_details,
+            Some(StatusDetails {
+                message: Some("Expected: 200 But was: 500".to_string()),
+                trace: Some(
+                    "at Auth.Tests.LoginTests.Logout() in LoginTests.cs:line 20".to_string()
+                ),
+            })
+        );
+        assert_eq!(
+            results[3].status_details,
+            Some(StatusDetails {
+                message: Some("not supported".to_string()),
+                trace: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_convert_time() {
+        let results = convert_report(NUNIT_REPORT).results;
+        let times: Vec<(i64, i64)> = results
+            .iter()
+            .map(|result| (result.start, result.stop))
+            .collect();
+        assert_eq!(
+            times,
+            vec![
+                (1_705_312_800_000, 1_705_312_801_500),
+                (1_705_312_802_000, 1_705_312_802_250),
+                (1_705_312_803_000, 1_705_312_803_100),
+                (1_705_312_804_000, 1_705_312_804_000),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_convert_categories_to_tags() {
+        let results = convert_report(NUNIT_REPORT).results;
+        assert_eq!(
+            results[0].labels,
+            vec![
+                AllureLabel::new("suite", "Auth.Tests.LoginTests"),
+                AllureLabel::new("testClass", "Auth.Tests.LoginTests"),
+                AllureLabel::new("tag", "Smoke"),
+                AllureLabel::new("tag", "Auth"),
+            ]
+        );
+        // Категория фикстуры наследуется тестами
+        assert!(results[1].labels.contains(&AllureLabel::new("tag", "Auth")));
+    }
+
+    #[test]
+    fn test_convert_output_attachment() {
+        let converted_results = convert_report(NUNIT_REPORT);
+        let result = &converted_results.results[0];
+        assert_eq!(result.attachments.len(), 1);
+        assert_eq!(result.attachments[0].name, "output");
+        assert_eq!(
+            converted_results.attachments,
+            vec![(
+                result.attachments[0].source.clone(),
+                b"user logged in".to_vec()
+            )]
+        );
+    }
+
+    #[rstest]
+    #[case(
+        "<testsuites/>",
+        "unexpected root element <testsuites>, expected <test-run> or <test-suite>"
+    )]
+    #[case("<test-run>", "the root node was opened but never closed")]
+    fn test_convert_invalid_report(#[case] xml: &str, #[case] expected_error: &str) {
+        let mut converted_results = ConvertedResults::default();
+        let error = convert(xml, &mut converted_results).unwrap_err();
+        assert!(error.starts_with(expected_error), "{}", error);
+    }
+
+    #[rstest]
+    #[case(Some("1.5"), 1500)]
+    #[case(Some("0.000123"), 0)]
+    #[case(Some("-1"), 0)]
+    #[case(None, 0)]
+    fn test_duration_ms(#[case] duration: Option<&str>, #[case] expected: i64) {
+        assert_eq!(duration_ms(duration), expected);
+    }
+}
@@ -0,0 +1,346 @@
+use super::allure_result::{AllureLabel, AllureResult, AllureStatus, StatusDetails};
+use super::{element_text, parse_timestamp, ConvertedResults};
+use chrono::Utc;
+use roxmltree::{Document, Node};
+use std::collections::HashMap;
+
+/// Test method from `TestDefinitions`: class and categories of the test
+#[derive(Default)]
+struct TestDefinition<'a> {
+    class_name: Option<&'a str>,
+    categories: Vec<&'a str>,
+}
+
+/// Convert Visual Studio TRX report: outcomes, durations, errors, output and test categories
+pub fn convert(xml: &str, converted_results: &mut ConvertedResults) -> Result<(), String> {
+    let document = Document::parse(xml).map_err(|e| e.to_string())?;
+    let root = document.root_element();
+    if !root.has_tag_name("TestRun") {
+        return Err(format!(
+            "unexpected root element <{}>, expected <TestRun>",
+            root.tag_name().name()
+        ));
+    }
+    let definitions = test_definitions(root);
+    let empty_definition = TestDefinition::default();
+    for unit_test_result in root
+        .descendants()
+        .filter(|node| node.has_tag_name("UnitTestResult"))
+        // Результаты data-driven тестов вложены в InnerResults, берем только итоговый
+        .filter(|node| {
+            !node
+                .ancestors()
+                .any(|node| node.has_tag_name("InnerResults"))
+        })
+    {
+        let definition = unit_test_result
+            .attribute("testId")
+            .and_then(|test_id| definitions.get(test_id))
+            .unwrap_or(&empty_definition);
+        let result = convert_unit_test_result(unit_test_result, definition, converted_results);
+        converted_results.results.push(result);
+    }
+    Ok(())
+}
+
+fn test_definitions<'a>(root: Node<'a, '_>) -> HashMap<&'a str, TestDefinition<'a>> {
+    root.descendants()
+        .filter(|node| node.has_tag_name("UnitTest"))
+        .filter_map(|unit_test| {
+            let id = unit_test.attribute("id")?;
+            let class_name = unit_test
+                .children()
+                .find(|node| node.has_tag_name("TestMethod"))
+                .and_then(|method| method.attribute("className"))
+                // className может содержать имя сборки после запятой
+                .map(|class_name| class_name.split(',').next().unwrap_or_default().trim())
+                .filter(|class_name| !class_name.is_empty());
+            let categories = unit_test
+                .descendants()
+                .filter(|node| node.has_tag_name("TestCategoryItem"))
+                .filter_map(|item| item.attribute("TestCategory"))
+                .collect();
+            Some((
+                id,
+                TestDefinition {
+                    class_name,
+                    categories,
+                },
+            ))
+        })
+        .collect()
+}
+
+fn convert_unit_test_result(
+    unit_test_result: Node,
+    definition: &TestDefinition,
+    converted_results: &mut ConvertedResults,
+) -> AllureResult {
+    let name = unit_test_result.attribute("testName").unwrap_or("unnamed");
+    let full_name = match definition.class_name {
+        // xUnit пишет в testName полное имя вместе с классом
+        Some(class_name) if !name.starts_with(&format!("{class_name}.")) => {
+            format!("{class_name}.{name}")
+        }
+        _ => name.to_string(),
+    };
+    let mut result = AllureResult::new(name, &full_name);
+    result.status = trx_status(unit_test_result.attribute("outcome").unwrap_or_default());
+    result.start = unit_test_result
+        .attribute("startTime")
+        .and_then(parse_timestamp)
+        .unwrap_or_else(|| Utc::now().timestamp_millis());
+    result.stop = unit_test_result
+        .attribute("duration")
+        .and_then(duration_ms)
+        .map(|duration| result.start + duration)
+        .or_else(|| {
+            unit_test_result
+                .attribute("endTime")
+                .and_then(parse_timestamp)
+        })
+        .unwrap_or(result.start);
+    if let Some(class_name) = definition.class_name {
+        result.labels.push(AllureLabel::new("suite", class_name));
+        result
+            .labels
+            .push(AllureLabel::new("testClass", class_name));
+    }
+    for category in &definition.categories {
+        result.labels.push(AllureLabel::new("tag", category));
+    }
+    let output = unit_test_result
+        .children()
+        .find(|node| node.has_tag_name("Output"));
+    for child in output.iter().flat_map(Node::children) {
+        match child.tag_name().name() {
+            "ErrorInfo" => result.status_details = status_details(child),
+            output @ ("StdOut" | "StdErr") => {
+                let text = element_text(child);
+                if !text.trim().is_empty() {
+                    let name = if output == "StdOut" {
+                        "stdout"
+                    } else {
+                        "stderr"
+                    };
+                    let attachment = converted_results.attach_text(name, &text);
+                    result.attachments.push(attachment);
+                }
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+fn trx_status(outcome: &str) -> AllureStatus {
+    match outcome {
+        "Passed" | "PassedButRunAborted" | "Warning" => AllureStatus::Passed,
+        "Failed" => AllureStatus::Failed,
+        "Error" | "Timeout" | "Aborted" => AllureStatus::Broken,
+        "NotExecuted" | "NotRunnable" | "Inconclusive" | "Pending" | "Disconnected" => {
+            AllureStatus::Skipped
+        }
+        _ => AllureStatus::Unknown,
+    }
+}
+
+fn status_details(error_info: Node) -> Option<StatusDetails> {
+    let child_text = |tag_name: &str| {
+        error_info
+            .children()
+            .find(|node| node.has_tag_name(tag_name))
+            .map(element_text)
+            .filter(|text| !text.trim().is_empty())
+    };
+    let message = child_text("Message");
+    let trace = child_text("StackTrace");
+    if message.is_none() && trace.is_none() {
+        return None;
+    }
+    Some(StatusDetails { message, trace })
+}
+
+/// Duration in the `hh:mm:ss.fffffff` format, hours may be prefixed with days as `d.hh`
+fn duration_ms(duration: &str) -> Option<i64> {
+    let parts: Vec<&str> = duration.trim().split(':').collect();
+    let [hours, minutes, seconds] = parts[..] else {
+        return None;
+    };
+    let hours = match hours.split_once('.') {
+        Some((days, hours)) => days.parse::<u32>().ok()? * 24 + hours.parse::<u32>().ok()?,
+        None => hours.parse::<u32>().ok()?,
+    };
+    let minutes = minutes.parse::<u32>().ok()?;
+    let seconds = seconds.parse::<f64>().ok()?;
+    Some(((f64::from(hours * 3600 + minutes * 60) + seconds) * 1000.0).round() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const TRX_REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<TestRun id="1" name="run" xmlns="http://microsoft.com/schemas/VisualStudio/TeamTest/2010">
+  <Results>
+    <UnitTestResult testId="t1" testName="Login" outcome="Passed" duration="00:00:01.5000000" startTime="2024-01-15T10:00:00.0000000+00:00" endTime="2024-01-15T10:00:01.5000000+00:00">
+      <Output>
+        <StdOut>user logged in</StdOut>
+      </Output>
+    </UnitTestResult>
+    <UnitTestResult testId="t2" testName="Auth.Tests.LoginTests.Logout" outcome="Failed" duration="00:00:00.2500000" startTime="2024-01-15T10:00:02.0000000+00:00">
+      <Output>
+        <ErrorInfo>
+          <Message>Assert.AreEqual failed. Expected:&lt;200&gt;. Actual:&lt;500&gt;.</Message>
+          <StackTrace>at Auth.Tests.LoginTests.Logout() in LoginTests.cs:line 20</StackTrace>
+        </ErrorInfo>
+      </Output>
+    </UnitTestResult>
+    <UnitTestResult testId="t3" testName="Refresh" outcome="NotExecuted" startTime="2024-01-15T10:00:03.0000000+00:00" />
+    <UnitTestResult testId="t4" testName="Sso" outcome="Timeout" duration="00:01:00" startTime="2024-01-15T10:00:04.0000000+00:00" />
+  </Results>
+  <TestDefinitions>
+    <UnitTest name="Login" id="t1">
+      <TestCategory>
+        <TestCategoryItem TestCategory="Smoke" />
+        <TestCategoryItem TestCategory="Auth" />
+      </TestCategory>
+      <TestMethod className="Auth.Tests.LoginTests, Auth.Tests, Version=1.0.0.0" name="Login" />
+    </UnitTest>
+    <UnitTest name="Logout" id="t2">
+      <TestMethod className="Auth.Tests.LoginTests" name="Logout" />
+    </UnitTest>
+    <UnitTest name="Refresh" id="t3">
+      <TestMethod className="Auth.Tests.LoginTests" name="Refresh" />
+    </UnitTest>
+  </TestDefinitions>
+</TestRun>"#;
+
+    fn convert_report(xml: &str) -> ConvertedResults {
+        let mut converted_results = ConvertedResults::default();
+        convert(xml, &mut converted_results).unwrap();
+        converted_results
+    }
+
+    #[test]
+    fn test_convert_outcomes() {
+        let converted_results = convert_report(TRX_REPORT);
+        let statuses: Vec<(&str, AllureStatus)> = converted_results
+            .results
+            .iter()
+            .map(|result| (result.full_name.as_str(), result.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("Auth.Tests.LoginTests.Login", AllureStatus::Passed),
+                ("Auth.Tests.LoginTests.Logout", AllureStatus::Failed),
+                ("Auth.Tests.LoginTests.Refresh", AllureStatus::Skipped),
+                ("Sso", AllureStatus::Broken),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_convert_error_info() {
+        let results = convert_report(TRX_REPORT).results;
+        assert_eq!(results[0].status_details, None);
+        assert_eq!(
+            results[1].status_details,
+            Some(StatusDetails {
+                message: Some("Assert.AreEqual failed. Expected:<200>. Actual:<500>.".to_string()),
+                trace: Some(
+                    "at Auth.Tests.LoginTests.Logout() in LoginTests.cs:line 20".to_string()
+                ),
+            })
+        );
+    }
+
+    #[test]
+    fn test_convert_time() {
+        let results = convert_report(TRX_REPORT).results;
+        let times: Vec<(i64, i64)> = results
+            .iter()
+            .map(|result| (result.start, result.stop))
+            .collect();
+        assert_eq!(
+            times,
+            vec![
+                (1_705_312_800_000, 1_705_312_801_500),
+                (1_705_312_802_000, 1_705_312_802_250),
+                (1_705_312_803_000, 1_705_312_803_000),
+                (1_705_312_804_000, 1_705_312_864_000),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_convert_categories_to_tags() {
+        let results = convert_report(TRX_REPORT).results;
+        assert_eq!(
+            results[0].labels,
+            vec![
+                AllureLabel::new("suite", "Auth.Tests.LoginTests"),
+                AllureLabel::new("testClass", "Auth.Tests.LoginTests"),
+                AllureLabel::new("tag", "Smoke"),
+                AllureLabel::new("tag", "Auth"),
+            ]
+        );
+        assert!(results[3].labels.is_empty());
+    }
+
+    #[test]
+    fn test_convert_output_attachments() {
+        let converted_results = convert_report(TRX_REPORT);
+        let result = &converted_results.results[0];
+        assert_eq!(result.attachments.len(), 1);
+        assert_eq!(result.attachments[0].name, "stdout");
+        assert_eq!(
+            converted_results.attachments,
+            vec![(
+                result.attachments[0].source.clone(),
+                b"user logged in".to_vec()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_convert_skips_inner_results() {
+        let xml = r#"<TestRun>
+  <Results>
+    <UnitTestResult testId="t1" testName="Sum" outcome="Failed">
+      <InnerResults>
+        <UnitTestResult testId="t1" testName="Sum (1,2)" outcome="Passed" />
+        <UnitTestResult testId="t1" testName="Sum (2,2)" outcome="Failed" />
+      </InnerResults>
+    </UnitTestResult>
+  </Results>
+</TestRun>"#;
+        let results = convert_report(xml).results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].status, AllureStatus::Failed);
+    }
+
+    #[rstest]
+    #[case(
+        "<testsuites/>",
+        "unexpected root element <testsuites>, expected <TestRun>"
+    )]
+    #[case("<TestRun>", "the root node was opened but never closed")]
+    fn test_convert_invalid_report(#[case] xml: &str, #[case] expected_error: &str) {
+        let mut converted_results = ConvertedResults::default();
+        let error = convert(xml, &mut converted_results).unwrap_err();
+        assert!(error.starts_with(expected_error), "{}", error);
+    }
+
+    #[rstest]
+    #[case("00:00:01.5000000", Some(1500))]
+    #[case("01:02:03", Some(3_723_000))]
+    #[case("1.00:00:00", Some(86_400_000))]
+    #[case("1.5", None)]
+    #[case("abc", None)]
+    fn test_duration_ms(#[case] duration: &str, #[case] expected: Option<i64>) {
+        assert_eq!(duration_ms(duration), expected);
+    }
+}
@@ -2,11 +2,13 @@ use crate::external_api::testops_api::models::test_case_overview::TestCaseOvervi
 use crate::external_api::testops_api::models::test_case_scenario::Scenario;
 use crate::utils::{convert_to_pascal_case, save_file_in_current_directory};
 use crate::ApiError;
+use std::path::Path;
 
 fn generate_python_template(
     test_case_overview: &TestCaseOverview,
     test_case_scenario: &Scenario,
     file_name: &str,
+    mark: &str,
 ) -> String {
     let allure_metadata = test_case_overview.convert_allure_metadata_to_python_template();
     let prefix_newline = if allure_metadata.is_empty() { "" } else { "\n" };
@@ -20,7 +22,7 @@ fn generate_python_template(
 import pytest
 import allure
 {prefix_newline}{allure_metadata}
-@pytest.mark.TEMPLATE_MARK_NAME
+@pytest.mark.{mark}
 class {class_name}:
 
     @allure.id('{allure_id}')
@@ -38,6 +40,7 @@ class {class_name}:
 "#,
         prefix_newline = prefix_newline,
         allure_metadata = allure_metadata,
+        mark = mark,
         class_name = class_name,
         allure_id = test_case_overview.id,
         name = &test_case_overview.name,
@@ -47,13 +50,24 @@ class {class_name}:
     )
 }
 
+/// Create the test file in the output directory, the current one by default
 pub async fn create_template_python_ati_su(
     test_case_overview: TestCaseOverview,
     test_case_scenario: Scenario,
     file_name: &str,
+    output_dir: Option<&str>,
+    mark: &str,
 ) -> Result<String, ApiError> {
-    let template = generate_python_template(&test_case_overview, &test_case_scenario, file_name);
-    save_file_in_current_directory(file_name, template.as_bytes()).await
+    let template =
+        generate_python_template(&test_case_overview, &test_case_scenario, file_name, mark);
+    let file_path = match output_dir {
+        Some(output_dir) => {
+            std::fs::create_dir_all(output_dir).map_err(|_| ApiError::CouldNotCreateFile)?;
+            Path::new(output_dir).join(file_name).display().to_string()
+        }
+        None => file_name.to_string(),
+    };
+    save_file_in_current_directory(&file_path, template.as_bytes()).await
 }
 
 #[cfg(test)]
@@ -61,13 +75,19 @@ mod tests {
     use super::*;
     use crate::external_api::testops_api::models::test_case_overview::TestCaseOverview;
     use crate::external_api::testops_api::models::test_case_scenario::Scenario;
+    use crate::repo_defaults::DEFAULT_TESTCASE_MARK;
 
     #[test]
     fn test_generate_template_minimum() {
         let test_case_overview = TestCaseOverview::default();
         let scenario = Scenario::default();
         let file_name = "test_case_one.py";
-        let template = generate_python_template(&test_case_overview, &scenario, file_name);
+        let template = generate_python_template(
+            &test_case_overview,
+            &scenario,
+            file_name,
+            DEFAULT_TESTCASE_MARK,
+        );
         let exp_template = r#"
 import pytest
 import allure
@@ -90,4 +110,39 @@ class TestCaseOne:
 "#;
         assert_eq!(exp_template, template);
     }
+
+    #[test]
+    fn test_generate_template_with_mark() {
+        let template = generate_python_template(
+            &TestCaseOverview::default(),
+            &Scenario::default(),
+            "test_case_one.py",
+            "regression",
+        );
+        assert!(template.contains("\n@pytest.mark.regression\nclass TestCaseOne:"));
+    }
+
+    #[tokio::test]
+    /// Директория для теста создается, если ее нет
+    async fn test_create_template_in_output_dir() {
+        let output_dir = std::env::temp_dir()
+            .join(format!("wot_tests_{}", uuid::Uuid::new_v4()))
+            .join("generated");
+        let result = create_template_python_ati_su(
+            TestCaseOverview::default(),
+            Scenario::default(),
+            "test_case_one.py",
+            Some(output_dir.to_str().unwrap()),
+            "smoke",
+        )
+        .await;
+        let content = std::fs::read_to_string(output_dir.join("test_case_one.py")).unwrap();
+        std::fs::remove_dir_all(output_dir.parent().unwrap()).unwrap();
+
+        assert_eq!(
+            result.unwrap(),
+            output_dir.join("test_case_one.py").display().to_string()
+        );
+        assert!(content.contains("@pytest.mark.smoke"));
+    }
 }
@@ -1,9 +1,19 @@
+pub mod auth;
+pub mod network;
+pub mod retry;
 pub mod testops_api;
 
+use crate::constants::{
+    Message, EXIT_CODE_ERROR, EXIT_CODE_NOT_INTERACTIVE, EXIT_CODE_QUALITY_GATE_FAILED,
+};
+use auth::{AuthSettings, Authenticator};
+use network::NetworkSettings;
 use reqwest::{
     header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE},
-    multipart, Client, StatusCode, Url,
+    multipart, Client, RequestBuilder, Response, StatusCode, Url,
 };
+use retry::{retry_after, RetryPolicy};
+use std::time::Duration;
 use thiserror::Error;
 
 const APPLICATION_JSON: &str = "application/json";
@@ -15,6 +25,8 @@ pub enum ApiError {
     Reqwest(#[from] reqwest::Error),
     #[error("Api error {0}: {1}")]
     Api(StatusCode, String),
+    #[error("Authentication failed: {0}")]
+    Authentication(String),
     #[error("Deserialization error: {0}")]
     Serde(#[from] serde_json::Error),
     #[error("URL parse error: {0}")]
@@ -25,14 +37,10 @@ pub enum ApiError {
     Io(#[from] std::io::Error),
     #[error("Invalid file name")]
     InvalidFileName,
-    #[error("Invalid file format: File is not a valid ZIP archive")]
-    InvalidFileFormat,
     #[error("Project with ID == {0} not found")]
     ProjectIdNotFound(u32),
     #[error("Failed to retrieve the user's directories")]
     NotFoundUserDir,
-    #[error("Invalid system time")]
-    InvalidSystemTime,
     #[error("Could not find the directory at path: <{0}>")]
     NotFoundDirByPath(String),
     #[error("ZipError: {0}")]
@@ -54,12 +62,93 @@ pub enum ApiError {
     UploadCancelledByUser,
     #[error("Invalid test file name: {0}")]
     InvalidTestFileName(String),
+    #[error("Invalid glob pattern: {0}")]
+    InvalidGlobPattern(String),
+    #[error("Invalid size: <{0}>, expected a number with optional B/KB/MB/GB suffix")]
+    InvalidSize(String),
+    #[error("Upload confirmation is required, but stdin is not a terminal. Use --yes to confirm")]
+    ConfirmationRequired,
+    #[error(
+        "Config file not found at path: <{0}>. Run wot in a terminal or wot init to create it, \
+         or set WOT_TESTOPS_URL and WOT_TESTOPS_TOKEN"
+    )]
+    ConfigNotFound(String),
+    #[error("Invalid launch link: <{0}>, expected name=url")]
+    InvalidLaunchLink(String),
+    #[error("Launch with ID == {0} not found")]
+    LaunchIdNotFound(u32),
+    #[error("Launch with ID == {0} belongs to project with ID == {1}, not {2}")]
+    LaunchFromAnotherProject(u32, u32, u32),
+    #[error("Launch with ID == {0} was not processed in {1} seconds")]
+    LaunchProcessingTimeout(u32, u64),
+    #[error("Invalid pass rate: <{0}>, expected a number from 0 to 100")]
+    InvalidPassRate(String),
+    #[error("Launch with ID == {0} failed the quality gate: {1}")]
+    QualityGateFailed(u32, String),
+    #[error("Report validation failed with {0} error(s)")]
+    InvalidReport(usize),
+    #[error("Could not find the file or directory at path: <{0}>")]
+    NotFoundPath(String),
+    #[error("Could not convert <{0}>: {1}")]
+    ConvertFailed(String, String),
+    #[error("Project <{0}> not found")]
+    ProjectNameNotFound(String),
+    #[error(
+        "Project name <{0}> is ambiguous, matches: {1}. Use the project id or a more exact name"
+    )]
+    ProjectNameAmbiguous(String, String),
+    #[error("Invalid proxy <{0}>: {1}")]
+    InvalidProxy(String, String),
+    #[error("Could not read CA bundle <{0}>: {1}")]
+    InvalidCaBundle(String, String),
+    #[error("Profile <{0}> not found")]
+    ProfileNotFound(String),
+    #[error("Profile <{0}> already exists")]
+    ProfileAlreadyExists(String),
+    #[error("The default profile can't be removed")]
+    DefaultProfileRemoval,
+    #[error("Invalid profile name: <{0}>, use letters, digits, '.', '_' and '-'")]
+    InvalidProfileName(String),
+    #[error("Config file already exists at path: <{0}>. Use --force to overwrite it")]
+    ConfigAlreadyExists(String),
+    #[error("Could not read the token file <{0}>: {1}")]
+    InvalidTokenFile(String, String),
+    #[error("Token command <{0}> failed: {1}")]
+    TokenCommandFailed(String, String),
+    #[error("Unknown config key: <{0}>")]
+    UnknownConfigKey(String),
+    #[error("Config version {0} is not supported, the latest known is {1}. Update wot")]
+    UnsupportedConfigVersion(u64, u32),
+    #[error("Invalid <{0}>: {1}")]
+    InvalidRepoDefaults(String, String),
+    #[error("The project is not set, use --project, WOT_PROJECT or `project` in .wot.toml")]
+    ProjectNotSet,
+    #[error("Report directories are not set, use -d or `report.directories` in .wot.toml")]
+    ReportDirectoriesNotSet,
+}
+
+impl ApiError {
+    /// Process exit code for the error
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ApiError::ConfirmationRequired | ApiError::ConfigNotFound(_) => {
+                EXIT_CODE_NOT_INTERACTIVE
+            }
+            ApiError::QualityGateFailed(_, _) => EXIT_CODE_QUALITY_GATE_FAILED,
+            _ => EXIT_CODE_ERROR,
+        }
+    }
 }
 
 /// Basic api client
 pub struct BaseApiClient {
     client: Client,
+    /// Client with the read timeout of uploads, the server answers after the whole archive
+    upload_client: Client,
     pub base_url: Url,
+    pub authenticator: Authenticator,
+    pub retry_policy: RetryPolicy,
+    upload_timeout: Duration,
 }
 
 impl BaseApiClient {
@@ -69,17 +158,12 @@ impl BaseApiClient {
             .map_err(|e| ApiError::Parse(e.to_string()))
     }
 
-    fn get_default_headers(api_key: &str) -> Result<HeaderMap, ApiError> {
-        let mut headers = HeaderMap::with_capacity(3);
+    /// Headers of every request, `Authorization` is added per request by the authenticator
+    fn get_default_headers() -> HeaderMap {
+        let mut headers = HeaderMap::with_capacity(2);
         headers.insert(ACCEPT, HeaderValue::from_static(APPLICATION_JSON));
         headers.insert(CONTENT_TYPE, HeaderValue::from_static(APPLICATION_JSON));
-
-        match HeaderValue::from_str(&format!("Api-Token {}", api_key)) {
-            Ok(value) => headers.insert(AUTHORIZATION, value),
-            Err(_) => return Err(ApiError::InvalidApiKey),
-        };
-
-        Ok(headers)
+        headers
     }
 
     // todo надо покрыть тестами эту функцию
@@ -90,6 +174,9 @@ impl BaseApiClient {
         let status = response.status();
         let body = response.text().await?;
 
+        if status == StatusCode::UNAUTHORIZED {
+            return Err(ApiError::Authentication(format!("{}: {}", status, body)));
+        }
         if !status.is_success() {
             return Err(ApiError::Api(status, body));
         }
@@ -101,37 +188,138 @@ impl BaseApiClient {
     }
 
     pub fn new(base_url: &str, api_key: &str) -> Result<Self, ApiError> {
-        let default_headers = Self::get_default_headers(api_key)?;
+        Self::with_network_settings(base_url, api_key, &NetworkSettings::default())
+    }
+
+    /// Client with timeouts, proxy and certificates from the settings
+    pub fn with_network_settings(
+        base_url: &str,
+        api_key: &str,
+        network_settings: &NetworkSettings,
+    ) -> Result<Self, ApiError> {
+        let authenticator = Authenticator::new(api_key)?;
 
-        let client = Client::builder()
-            .default_headers(default_headers)
-            .timeout(std::time::Duration::from_secs(10))
+        let client = network_settings
+            .apply(Client::builder().default_headers(Self::get_default_headers()))?
+            .build()?;
+        let upload_client = network_settings
+            .for_upload()
+            .apply(Client::builder().default_headers(Self::get_default_headers()))?
             .build()?;
 
         let parse_base_url = Url::parse(base_url).map_err(|e| ApiError::Parse(e.to_string()))?;
 
         Ok(Self {
             client,
+            upload_client,
             base_url: parse_base_url,
+            authenticator,
+            retry_policy: RetryPolicy::default(),
+            upload_timeout: network_settings.upload_timeout(),
         })
     }
 
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn with_auth_settings(mut self, auth_settings: AuthSettings) -> Self {
+        self.authenticator.settings = auth_settings;
+        self
+    }
+
+    /// Send the request again while the policy allows it
+    ///
+    /// The request is built anew for every attempt, because a sent body can't be reused.
+    /// A jwt rejected with 401 is exchanged again once, it doesn't count as an attempt
+    async fn send_with_retry<F>(
+        &self,
+        build_request: F,
+        is_idempotent: bool,
+    ) -> Result<Response, ApiError>
+    where
+        F: Fn() -> Result<RequestBuilder, ApiError>,
+    {
+        let max_attempts = self.retry_policy.max_attempts.max(1);
+        let mut attempt = 1;
+        let mut is_token_refreshed = false;
+        loop {
+            let authorization = self
+                .authenticator
+                .header(&self.client, &self.base_url)
+                .await?;
+            let result = build_request()?
+                .header(AUTHORIZATION, authorization)
+                .send()
+                .await;
+            let (reason, delay) = match result {
+                Ok(response)
+                    if response.status() == StatusCode::UNAUTHORIZED
+                        && self.authenticator.is_refreshable()
+                        && !is_token_refreshed =>
+                {
+                    self.authenticator.invalidate().await;
+                    is_token_refreshed = true;
+                    continue;
+                }
+                Ok(response)
+                    if attempt < max_attempts
+                        && self.retry_policy.is_retryable_status(response.status()) =>
+                {
+                    let delay = self
+                        .retry_policy
+                        .delay(attempt, retry_after(response.headers()));
+                    (response.status().to_string(), delay)
+                }
+                Err(e)
+                    if attempt < max_attempts
+                        && self.retry_policy.is_retryable_error(&e, is_idempotent) =>
+                {
+                    (e.to_string(), self.retry_policy.delay(attempt, None))
+                }
+                result => return Ok(result?),
+            };
+            eprintln!(
+                "{}",
+                Message::RequestRetry(reason, delay, attempt + 1, max_attempts)
+                    .to_formatted_string()
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
     pub async fn get<T: serde::de::DeserializeOwned, B: serde::Serialize>(
         &self,
         endpoint: &str,
     ) -> Result<T, ApiError> {
         let url = self.build_url(endpoint)?;
-        let response = self.client.get(url).send().await?;
+        let response = self
+            .send_with_retry(|| Ok(self.client.get(url.clone())), true)
+            .await?;
         self.handle_response(response).await
     }
 
-    pub async fn post_multipart_file<T: serde::de::DeserializeOwned, B: serde::Serialize>(
+    /// Post multipart form, `build_form` is called for every attempt
+    pub async fn post_multipart_file<T, B>(
         &self,
         endpoint: &str,
-        multipart: multipart::Form,
-    ) -> Result<T, ApiError> {
+        build_form: impl Fn() -> Result<multipart::Form, ApiError>,
+    ) -> Result<T, ApiError>
+    where
+        T: serde::de::DeserializeOwned,
+        B: serde::Serialize,
+    {
         let url = self.build_url(endpoint)?;
-        let response = self.client.post(url).multipart(multipart).send().await?;
+        let build_request = || {
+            Ok(self
+                .upload_client
+                .post(url.clone())
+                .multipart(build_form()?)
+                .timeout(self.upload_timeout))
+        };
+        let response = self.send_with_retry(build_request, false).await?;
         self.handle_response(response).await
     }
 }
@@ -191,26 +379,25 @@ mod tests {
             .and_then(|name| name.to_str())
             .ok_or_else(|| ApiError::InvalidFileName)
             .unwrap();
-        let file_part = Part::bytes(buffer)
-            .file_name(file_name)
-            .mime_str("application/zip")
-            .unwrap();
-
         let info_file_json = serde_json::to_string(&LaunchInfo {
             name: "test_launch".to_string(),
             project_id: 2,
         })
         .unwrap();
-        let info_file_multipart = Part::text(info_file_json)
-            .mime_str("application/json")
-            .unwrap();
 
-        // Собираем форму с файлом
-        let form = Form::new()
-            .part("info", info_file_multipart)
-            .part("archive", file_part);
+        // Собираем форму с файлом, для каждой попытки заново
+        let build_form = || {
+            let file_part = Part::bytes(buffer.clone())
+                .file_name(file_name)
+                .mime_str("application/zip")?;
+            let info_file_multipart =
+                Part::text(info_file_json.clone()).mime_str("application/json")?;
+            Ok(Form::new()
+                .part("info", info_file_multipart)
+                .part("archive", file_part))
+        };
         let result = base_api_client
-            .post_multipart_file::<ResponseLaunchUpload, ()>("/api/rs/launch/upload", form)
+            .post_multipart_file::<ResponseLaunchUpload, ()>("/api/rs/launch/upload", build_form)
             .await
             .unwrap();
         assert!(result.launch_id != 0);
@@ -280,17 +467,111 @@ mod tests {
     #[test]
     fn test_valid_headers() {
         // Получаем хедеры
-        let default_headers = BaseApiClient::get_default_headers("test_api_key").unwrap();
+        let default_headers = BaseApiClient::get_default_headers();
 
-        // Проверяем значение дефолтных хедеров
+        // Проверяем значение дефолтных хедеров, авторизация добавляется к каждому запросу
         assert_eq!(default_headers[ACCEPT], "application/json");
         assert_eq!(default_headers[CONTENT_TYPE], "application/json");
-        assert_eq!(default_headers[AUTHORIZATION], "Api-Token test_api_key");
+        assert!(!default_headers.contains_key(AUTHORIZATION));
+    }
+
+    #[tokio::test]
+    async fn test_api_token_authorization() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/rs/project/2")
+            .match_header("authorization", "Api-Token test_api_key")
+            .with_body(r#"{"name": "Project"}"#)
+            .create_async()
+            .await;
+        let base_api_client = BaseApiClient::new(&server.url(), "test_api_key").unwrap();
+
+        let project: Project = base_api_client
+            .get::<Project, ()>("/api/rs/project/2")
+            .await
+            .unwrap();
+        assert_eq!(project.name, "Project");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    /// Отклоненный сервером jwt обменивается заново, и запрос повторяется
+    async fn test_jwt_authorization_refreshed() {
+        let mut server = mockito::Server::new_async().await;
+        for jwt in ["jwt-1", "jwt-2"] {
+            server
+                .mock("POST", "/api/uaa/oauth/token")
+                .with_body(format!(
+                    r#"{{"access_token": "{}", "expires_in": 3600}}"#,
+                    jwt
+                ))
+                .expect(1)
+                .create_async()
+                .await;
+        }
+        let rejected = server
+            .mock("GET", "/api/rs/project/2")
+            .match_header("authorization", "Bearer jwt-1")
+            .with_status(401)
+            .create_async()
+            .await;
+        let accepted = server
+            .mock("GET", "/api/rs/project/2")
+            .match_header("authorization", "Bearer jwt-2")
+            .with_body(r#"{"name": "Project"}"#)
+            .create_async()
+            .await;
+        let base_api_client = BaseApiClient::new(&server.url(), "test_api_key")
+            .unwrap()
+            .with_auth_settings(AuthSettings {
+                mode: auth::AuthMode::Jwt,
+                ..AuthSettings::default()
+            });
+
+        let project: Project = base_api_client
+            .get::<Project, ()>("/api/rs/project/2")
+            .await
+            .unwrap();
+        assert_eq!(project.name, "Project");
+        rejected.assert_async().await;
+        accepted.assert_async().await;
+    }
+
+    #[tokio::test]
+    /// Повторно отклоненный jwt не обменивается бесконечно
+    async fn test_jwt_authorization_rejected() {
+        let mut server = mockito::Server::new_async().await;
+        let exchange = server
+            .mock("POST", "/api/uaa/oauth/token")
+            .with_body(r#"{"access_token": "jwt", "expires_in": 3600}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/rs/project/2")
+            .with_status(401)
+            .with_body("Token expired")
+            .create_async()
+            .await;
+        let base_api_client = BaseApiClient::new(&server.url(), "test_api_key")
+            .unwrap()
+            .with_auth_settings(AuthSettings {
+                mode: auth::AuthMode::Jwt,
+                ..AuthSettings::default()
+            });
+
+        let result = base_api_client
+            .get::<Project, ()>("/api/rs/project/2")
+            .await;
+        assert!(matches!(result, Err(ApiError::Authentication(_))));
+        exchange.assert_async().await;
     }
 
     #[test]
     fn test_invalid_api_key() {
-        let api_key_err = BaseApiClient::get_default_headers("invalid\nkey").unwrap_err();
+        let api_key_err = BaseApiClient::new("http://localhost", "invalid\nkey")
+            .err()
+            .unwrap();
         assert!(
             matches!(api_key_err, ApiError::InvalidApiKey),
             "Ожидали ошибку ApiError::InvalidApiKey, получили другую"
@@ -362,6 +643,14 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_authentication() {
+        assert_eq!(
+            ApiError::Authentication("401 Unauthorized: Bad credentials".to_string()).to_string(),
+            "Authentication failed: 401 Unauthorized: Bad credentials"
+        );
+    }
+
     #[tokio::test]
     async fn test_serde_error() {
         // Пытаемся десериализовать невалидный json
@@ -473,4 +762,265 @@ mod tests {
             format!("Invalid test file name: {}", error)
         );
     }
+
+    #[test]
+    fn test_confirmation_required() {
+        assert_eq!(
+            ApiError::ConfirmationRequired.to_string(),
+            "Upload confirmation is required, but stdin is not a terminal. Use --yes to confirm"
+        );
+    }
+
+    #[test]
+    fn test_config_not_found() {
+        assert_eq!(
+            ApiError::ConfigNotFound("/some/config.json".to_string()).to_string(),
+            "Config file not found at path: </some/config.json>. Run wot in a terminal or wot init \
+             to create it, or set WOT_TESTOPS_URL and WOT_TESTOPS_TOKEN"
+        );
+    }
+
+    #[test]
+    fn test_invalid_launch_link() {
+        assert_eq!(
+            ApiError::InvalidLaunchLink("CI".to_string()).to_string(),
+            "Invalid launch link: <CI>, expected name=url"
+        );
+    }
+
+    #[test]
+    fn test_launch_id_not_found() {
+        assert_eq!(
+            ApiError::LaunchIdNotFound(123).to_string(),
+            "Launch with ID == 123 not found"
+        );
+    }
+
+    #[test]
+    fn test_launch_from_another_project() {
+        assert_eq!(
+            ApiError::LaunchFromAnotherProject(123, 1, 2).to_string(),
+            "Launch with ID == 123 belongs to project with ID == 1, not 2"
+        );
+    }
+
+    #[test]
+    fn test_launch_processing_timeout() {
+        assert_eq!(
+            ApiError::LaunchProcessingTimeout(123, 600).to_string(),
+            "Launch with ID == 123 was not processed in 600 seconds"
+        );
+    }
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(ApiError::ConfirmationRequired.exit_code(), 3);
+        assert_eq!(ApiError::ConfigNotFound(String::new()).exit_code(), 3);
+        assert_eq!(ApiError::UploadCancelledByUser.exit_code(), 1);
+        assert_eq!(ApiError::InvalidUrl.exit_code(), 1);
+        assert_eq!(
+            ApiError::QualityGateFailed(123, String::new()).exit_code(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_invalid_pass_rate() {
+        assert_eq!(
+            ApiError::InvalidPassRate("101".to_string()).to_string(),
+            "Invalid pass rate: <101>, expected a number from 0 to 100"
+        );
+    }
+
+    #[test]
+    fn test_not_found_path() {
+        assert_eq!(
+            ApiError::NotFoundPath("reports".to_string()).to_string(),
+            "Could not find the file or directory at path: <reports>"
+        );
+    }
+
+    #[test]
+    fn test_convert_failed() {
+        assert_eq!(
+            ApiError::ConvertFailed("TEST-auth.xml".to_string(), "bad xml".to_string()).to_string(),
+            "Could not convert <TEST-auth.xml>: bad xml"
+        );
+    }
+
+    #[test]
+    fn test_project_name_not_found() {
+        assert_eq!(
+            ApiError::ProjectNameNotFound("Mobile".to_string()).to_string(),
+            "Project <Mobile> not found"
+        );
+    }
+
+    #[test]
+    fn test_project_name_ambiguous() {
+        assert_eq!(
+            ApiError::ProjectNameAmbiguous(
+                "mobile".to_string(),
+                "Mobile iOS (12), Mobile Android (13)".to_string()
+            )
+            .to_string(),
+            "Project name <mobile> is ambiguous, matches: Mobile iOS (12), Mobile Android (13). \
+             Use the project id or a more exact name"
+        );
+    }
+
+    #[test]
+    fn test_invalid_proxy() {
+        assert_eq!(
+            ApiError::InvalidProxy("proxy:port".to_string(), "invalid port".to_string())
+                .to_string(),
+            "Invalid proxy <proxy:port>: invalid port"
+        );
+    }
+
+    #[test]
+    fn test_invalid_ca_bundle() {
+        assert_eq!(
+            ApiError::InvalidCaBundle("ca.pem".to_string(), "not found".to_string()).to_string(),
+            "Could not read CA bundle <ca.pem>: not found"
+        );
+    }
+
+    #[test]
+    fn test_profile_not_found() {
+        assert_eq!(
+            ApiError::ProfileNotFound("staging".to_string()).to_string(),
+            "Profile <staging> not found"
+        );
+    }
+
+    #[test]
+    fn test_profile_already_exists() {
+        assert_eq!(
+            ApiError::ProfileAlreadyExists("staging".to_string()).to_string(),
+            "Profile <staging> already exists"
+        );
+    }
+
+    #[test]
+    fn test_default_profile_removal() {
+        assert_eq!(
+            ApiError::DefaultProfileRemoval.to_string(),
+            "The default profile can't be removed"
+        );
+    }
+
+    #[test]
+    fn test_invalid_profile_name() {
+        assert_eq!(
+            ApiError::InvalidProfileName("my profile".to_string()).to_string(),
+            "Invalid profile name: <my profile>, use letters, digits, '.', '_' and '-'"
+        );
+    }
+
+    #[test]
+    fn test_config_already_exists() {
+        assert_eq!(
+            ApiError::ConfigAlreadyExists("/some/config.json".to_string()).to_string(),
+            "Config file already exists at path: </some/config.json>. Use --force to overwrite it"
+        );
+    }
+
+    #[test]
+    fn test_invalid_token_file() {
+        assert_eq!(
+            ApiError::InvalidTokenFile("token.txt".to_string(), "not found".to_string())
+                .to_string(),
+            "Could not read the token file <token.txt>: not found"
+        );
+    }
+
+    #[test]
+    fn test_token_command_failed() {
+        assert_eq!(
+            ApiError::TokenCommandFailed(
+                "pass show testops".to_string(),
+                "exit status: 1".to_string()
+            )
+            .to_string(),
+            "Token command <pass show testops> failed: exit status: 1"
+        );
+    }
+
+    #[test]
+    fn test_unknown_config_key() {
+        assert_eq!(
+            ApiError::UnknownConfigKey("network.timeout".to_string()).to_string(),
+            "Unknown config key: <network.timeout>"
+        );
+    }
+
+    #[test]
+    fn test_invalid_repo_defaults() {
+        assert_eq!(
+            ApiError::InvalidRepoDefaults(
+                "/repo/.wot.toml".to_string(),
+                "unknown key `projet`".to_string()
+            )
+            .to_string(),
+            "Invalid </repo/.wot.toml>: unknown key `projet`"
+        );
+    }
+
+    #[test]
+    fn test_project_not_set() {
+        assert_eq!(
+            ApiError::ProjectNotSet.to_string(),
+            "The project is not set, use --project, WOT_PROJECT or `project` in .wot.toml"
+        );
+    }
+
+    #[test]
+    fn test_report_directories_not_set() {
+        assert_eq!(
+            ApiError::ReportDirectoriesNotSet.to_string(),
+            "Report directories are not set, use -d or `report.directories` in .wot.toml"
+        );
+    }
+
+    #[test]
+    fn test_unsupported_config_version() {
+        assert_eq!(
+            ApiError::UnsupportedConfigVersion(7, 1).to_string(),
+            "Config version 7 is not supported, the latest known is 1. Update wot"
+        );
+    }
+
+    #[test]
+    fn test_invalid_report() {
+        assert_eq!(
+            ApiError::InvalidReport(3).to_string(),
+            "Report validation failed with 3 error(s)"
+        );
+    }
+
+    #[test]
+    fn test_quality_gate_failed() {
+        assert_eq!(
+            ApiError::QualityGateFailed(123, "failed 3 > 0".to_string()).to_string(),
+            "Launch with ID == 123 failed the quality gate: failed 3 > 0"
+        );
+    }
+
+    #[test]
+    fn test_error_invalid_size() {
+        assert_eq!(
+            ApiError::InvalidSize("1TB".to_string()).to_string(),
+            "Invalid size: <1TB>, expected a number with optional B/KB/MB/GB suffix"
+        );
+    }
+
+    #[test]
+    fn test_error_invalid_glob_pattern() {
+        let error = "some error";
+        assert_eq!(
+            ApiError::InvalidGlobPattern(error.to_string()).to_string(),
+            format!("Invalid glob pattern: {}", error)
+        );
+    }
 }
@@ -0,0 +1,270 @@
+use super::ApiError;
+use reqwest::header::HeaderValue;
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// How the requests are authorized
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthMode {
+    /// `Authorization: Api-Token <token>` in every request
+    #[default]
+    ApiToken,
+    /// `Authorization: Bearer <jwt>`, the jwt is exchanged for the API token
+    Jwt,
+}
+
+/// Authorization settings of the http client
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct AuthSettings {
+    pub mode: AuthMode,
+    /// UAA endpoint that exchanges the API token for a jwt
+    pub token_endpoint: String,
+    /// The jwt is refreshed this long before it expires, so it doesn't expire in flight
+    pub refresh_margin_secs: u64,
+}
+
+impl Default for AuthSettings {
+    fn default() -> Self {
+        Self {
+            mode: AuthMode::default(),
+            token_endpoint: "/api/uaa/oauth/token".to_string(),
+            refresh_margin_secs: 30,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+struct AccessToken {
+    header: HeaderValue,
+    expires_at: Instant,
+}
+
+/// Authorization header of the requests
+///
+/// The jwt is cached until it expires and is exchanged again only then
+pub struct Authenticator {
+    api_token: String,
+    pub settings: AuthSettings,
+    access_token: Mutex<Option<AccessToken>>,
+}
+
+impl Authenticator {
+    pub fn new(api_token: &str) -> Result<Self, ApiError> {
+        // Недопустимый в заголовке токен отклоняется сразу, а не при первом запросе
+        api_token_header(api_token)?;
+        Ok(Self {
+            api_token: api_token.to_string(),
+            settings: AuthSettings::default(),
+            access_token: Mutex::new(None),
+        })
+    }
+
+    /// The server may reject a jwt before its expiration, then it is worth exchanging again
+    pub fn is_refreshable(&self) -> bool {
+        self.settings.mode == AuthMode::Jwt
+    }
+
+    /// Value of the `Authorization` header, the jwt is exchanged if there is no valid one
+    pub async fn header(&self, client: &Client, base_url: &Url) -> Result<HeaderValue, ApiError> {
+        if self.settings.mode == AuthMode::ApiToken {
+            return api_token_header(&self.api_token);
+        }
+        let mut access_token = self.access_token.lock().await;
+        match access_token.as_ref() {
+            Some(token) if token.expires_at > Instant::now() => Ok(token.header.clone()),
+            _ => {
+                let token = self.exchange(client, base_url).await?;
+                let header = token.header.clone();
+                *access_token = Some(token);
+                Ok(header)
+            }
+        }
+    }
+
+    /// Forget the cached jwt, the next request exchanges a new one
+    pub async fn invalidate(&self) {
+        *self.access_token.lock().await = None;
+    }
+
+    async fn exchange(&self, client: &Client, base_url: &Url) -> Result<AccessToken, ApiError> {
+        let url = base_url
+            .join(&self.settings.token_endpoint)
+            .map_err(|e| ApiError::Parse(e.to_string()))?;
+        let response = client
+            .post(url)
+            .form(&[
+                ("grant_type", "apitoken"),
+                ("scope", "openid"),
+                ("token", self.api_token.as_str()),
+            ])
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(ApiError::Authentication(format!(
+                "token exchange returned {}: {}",
+                status, body
+            )));
+        }
+        let token: TokenResponse = serde_json::from_str(&body)
+            .map_err(|e| ApiError::Authentication(format!("invalid token response: {}", e)))?;
+        let header = HeaderValue::from_str(&format!("Bearer {}", token.access_token))
+            .map_err(|_| ApiError::Authentication("invalid access token".to_string()))?;
+        let lifetime = Duration::from_secs(token.expires_in)
+            .saturating_sub(Duration::from_secs(self.settings.refresh_margin_secs));
+        Ok(AccessToken {
+            header,
+            expires_at: Instant::now() + lifetime,
+        })
+    }
+}
+
+fn api_token_header(api_token: &str) -> Result<HeaderValue, ApiError> {
+    HeaderValue::from_str(&format!("Api-Token {}", api_token)).map_err(|_| ApiError::InvalidApiKey)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    const TOKEN_ENDPOINT: &str = "/api/uaa/oauth/token";
+
+    fn jwt_authenticator() -> Authenticator {
+        let mut authenticator = Authenticator::new("api-token").unwrap();
+        authenticator.settings.mode = AuthMode::Jwt;
+        authenticator
+    }
+
+    #[test]
+    /// Без секции auth используется API токен
+    fn test_deserialize_partial_settings() {
+        let settings: AuthSettings = serde_json::from_str(r#"{"mode": "jwt"}"#).unwrap();
+        assert_eq!(
+            settings,
+            AuthSettings {
+                mode: AuthMode::Jwt,
+                ..AuthSettings::default()
+            }
+        );
+        assert_eq!(AuthSettings::default().mode, AuthMode::ApiToken);
+    }
+
+    #[test]
+    fn test_invalid_api_token() {
+        assert!(matches!(
+            Authenticator::new("invalid\ntoken"),
+            Err(ApiError::InvalidApiKey)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_api_token_header() {
+        let server = Server::new_async().await;
+        let base_url = Url::parse(&server.url()).unwrap();
+        let authenticator = Authenticator::new("api-token").unwrap();
+
+        let header = authenticator.header(&Client::new(), &base_url).await;
+        assert_eq!(header.unwrap(), "Api-Token api-token");
+    }
+
+    #[tokio::test]
+    /// Токен обменивается один раз и берется из кэша, пока не истек
+    async fn test_jwt_header_cached() {
+        let mut server = Server::new_async().await;
+        let base_url = Url::parse(&server.url()).unwrap();
+        let exchange = server
+            .mock("POST", TOKEN_ENDPOINT)
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("grant_type".to_string(), "apitoken".to_string()),
+                Matcher::UrlEncoded("token".to_string(), "api-token".to_string()),
+            ]))
+            .with_body(r#"{"access_token": "jwt-1", "expires_in": 3600}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let authenticator = jwt_authenticator();
+        let client = Client::new();
+
+        for _ in 0..2 {
+            let header = authenticator.header(&client, &base_url).await;
+            assert_eq!(header.unwrap(), "Bearer jwt-1");
+        }
+        exchange.assert_async().await;
+    }
+
+    #[tokio::test]
+    /// Истекающий в пределах запаса токен и сброшенный токен обмениваются заново
+    async fn test_jwt_header_refreshed() {
+        let mut server = Server::new_async().await;
+        let base_url = Url::parse(&server.url()).unwrap();
+        let exchange = server
+            .mock("POST", TOKEN_ENDPOINT)
+            .with_body(r#"{"access_token": "jwt-1", "expires_in": 10}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let authenticator = jwt_authenticator();
+        let client = Client::new();
+
+        authenticator.header(&client, &base_url).await.unwrap();
+        authenticator.header(&client, &base_url).await.unwrap();
+        exchange.assert_async().await;
+
+        let mut authenticator = jwt_authenticator();
+        authenticator.settings.refresh_margin_secs = 0;
+        let exchange = server
+            .mock("POST", TOKEN_ENDPOINT)
+            .with_body(r#"{"access_token": "jwt-2", "expires_in": 3600}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        authenticator.header(&client, &base_url).await.unwrap();
+        authenticator.invalidate().await;
+        authenticator.header(&client, &base_url).await.unwrap();
+        exchange.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_jwt_exchange_rejected() {
+        let mut server = Server::new_async().await;
+        let base_url = Url::parse(&server.url()).unwrap();
+        server
+            .mock("POST", TOKEN_ENDPOINT)
+            .with_status(401)
+            .with_body("Bad credentials")
+            .create_async()
+            .await;
+
+        let result = jwt_authenticator().header(&Client::new(), &base_url).await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Authentication failed: token exchange returned 401 Unauthorized: Bad credentials"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_jwt_invalid_token_response() {
+        let mut server = Server::new_async().await;
+        let base_url = Url::parse(&server.url()).unwrap();
+        server
+            .mock("POST", TOKEN_ENDPOINT)
+            .with_body(r#"{"token": "jwt"}"#)
+            .create_async()
+            .await;
+
+        let result = jwt_authenticator().header(&Client::new(), &base_url).await;
+        assert!(matches!(result, Err(ApiError::Authentication(reason))
+            if reason.starts_with("invalid token response")));
+    }
+}
@@ -0,0 +1,194 @@
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// When and how often failed requests are repeated
+///
+/// Timeouts are retried only for idempotent requests: an upload that timed out
+/// may have already created a launch
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Attempts including the first one, 1 disables retries
+    pub max_attempts: u32,
+    /// Delay before the second attempt, doubled for every next one
+    pub initial_backoff_ms: u64,
+    /// Upper bound of the delay, also limits `Retry-After` from the server
+    pub max_backoff_ms: u64,
+    /// Wait a random time from a half to the full delay, so parallel jobs don't retry at once
+    pub jitter: bool,
+    /// Response statuses that are retried
+    pub retry_statuses: Vec<u16>,
+    /// Retry connection errors and timeouts
+    pub retry_network_errors: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff_ms: 1000,
+            max_backoff_ms: 30_000,
+            jitter: true,
+            retry_statuses: vec![429, 502, 503, 504],
+            retry_network_errors: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that makes only one attempt
+    pub fn disabled() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_retryable_status(&self, status: StatusCode) -> bool {
+        self.retry_statuses.contains(&status.as_u16())
+    }
+
+    pub fn is_retryable_error(&self, error: &reqwest::Error, is_idempotent: bool) -> bool {
+        self.retry_network_errors && (error.is_connect() || (is_idempotent && error.is_timeout()))
+    }
+
+    /// Delay after the failed attempt, attempts are counted from 1
+    ///
+    /// `Retry-After` from the server replaces the backoff and is not randomized
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let max_backoff = Duration::from_millis(self.max_backoff_ms);
+        if let Some(retry_after) = retry_after {
+            return retry_after.min(max_backoff);
+        }
+        let backoff = Duration::from_millis(self.initial_backoff_ms)
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(max_backoff);
+        if !self.jitter || backoff.is_zero() {
+            return backoff;
+        }
+        rand::rng().random_range(backoff / 2..=backoff)
+    }
+}
+
+/// Delay from the `Retry-After` header in seconds or as an HTTP date
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delay = date.signed_duration_since(chrono::Utc::now());
+    Some(delay.to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use rstest::rstest;
+
+    fn policy_without_jitter() -> RetryPolicy {
+        RetryPolicy {
+            jitter: false,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[rstest]
+    #[case(1, 1000)]
+    #[case(2, 2000)]
+    #[case(3, 4000)]
+    #[case(6, 30_000)]
+    #[case(100, 30_000)]
+    fn test_delay_exponential(#[case] attempt: u32, #[case] expected_ms: u64) {
+        assert_eq!(
+            policy_without_jitter().delay(attempt, None),
+            Duration::from_millis(expected_ms)
+        );
+    }
+
+    #[test]
+    fn test_delay_with_jitter() {
+        let policy = RetryPolicy::default();
+        for _ in 0..100 {
+            let delay = policy.delay(2, None);
+            assert!(delay >= Duration::from_millis(1000) && delay <= Duration::from_millis(2000));
+        }
+    }
+
+    #[rstest]
+    #[case(5, 5)]
+    #[case(600, 30)]
+    /// Retry-After заменяет backoff, но не больше максимальной задержки
+    fn test_delay_retry_after(#[case] retry_after_secs: u64, #[case] expected_secs: u64) {
+        assert_eq!(
+            RetryPolicy::default().delay(1, Some(Duration::from_secs(retry_after_secs))),
+            Duration::from_secs(expected_secs)
+        );
+    }
+
+    #[rstest]
+    #[case(429, true)]
+    #[case(502, true)]
+    #[case(503, true)]
+    #[case(504, true)]
+    #[case(500, false)]
+    #[case(404, false)]
+    fn test_is_retryable_status(#[case] status: u16, #[case] expected: bool) {
+        let status = StatusCode::from_u16(status).unwrap();
+        assert_eq!(RetryPolicy::default().is_retryable_status(status), expected);
+    }
+
+    #[tokio::test]
+    /// Ошибка соединения повторяется для любых запросов, кроме выключенных настройкой
+    async fn test_is_retryable_connect_error() {
+        let error = reqwest::get("http://127.0.0.1:9").await.unwrap_err();
+        assert!(RetryPolicy::default().is_retryable_error(&error, false));
+        let policy = RetryPolicy {
+            retry_network_errors: false,
+            ..RetryPolicy::default()
+        };
+        assert!(!policy.is_retryable_error(&error, true));
+    }
+
+    #[rstest]
+    #[case(Some("120"), Some(Duration::from_secs(120)))]
+    #[case(Some("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO))]
+    #[case(Some("soon"), None)]
+    #[case(None, None)]
+    fn test_retry_after(#[case] header: Option<&str>, #[case] expected: Option<Duration>) {
+        let mut headers = HeaderMap::new();
+        if let Some(header) = header {
+            headers.insert(RETRY_AFTER, HeaderValue::from_str(header).unwrap());
+        }
+        assert_eq!(retry_after(&headers), expected);
+    }
+
+    #[test]
+    fn test_retry_after_future_date() {
+        let date = chrono::Utc::now() + chrono::Duration::seconds(90);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_str(&date.to_rfc2822()).unwrap(),
+        );
+        let delay = retry_after(&headers).unwrap();
+        assert!(delay > Duration::from_secs(85) && delay <= Duration::from_secs(90));
+    }
+
+    #[test]
+    /// Незаданные в конфиге поля берутся по умолчанию
+    fn test_deserialize_partial_policy() {
+        let policy: RetryPolicy = serde_json::from_str(r#"{"max_attempts": 5}"#).unwrap();
+        assert_eq!(
+            policy,
+            RetryPolicy {
+                max_attempts: 5,
+                ..RetryPolicy::default()
+            }
+        );
+    }
+}
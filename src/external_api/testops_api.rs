@@ -1,31 +1,68 @@
 pub mod allure_meta_data;
 pub mod models;
 
+use super::auth::AuthSettings;
+use super::network::NetworkSettings;
+use super::retry::RetryPolicy;
 use super::{ApiError, BaseApiClient};
-use crate::utils::{get_file_name, read_file_to_buffer, validate_zip_archive};
+use crate::utils::{stream_report_archive, ReportFile};
 use models::get_launch_by_id::GetLaunchByIdResponse;
 use models::launch_info::LaunchInfo;
+use models::launch_statistic::LaunchStatistic;
+use models::page::Page;
 use models::project_info::ProjectInfo;
-use models::response_get_all_project::ResponseGetAllProject;
 use models::response_launch_upload::ResponseLaunchUpload;
 use models::test_case_overview::TestCaseOverview;
+use models::test_case_row::TestCaseRow;
 use models::test_case_scenario::Scenario;
 use reqwest::multipart::{Form, Part};
-use std::collections::HashSet;
-use std::path::Path;
+use serde::de::DeserializeOwned;
+use std::path::PathBuf;
+
+/// File name of the report archive in the upload form
+const REPORT_ARCHIVE_NAME: &str = "report.zip";
+/// Count of items requested from paginated endpoints at once
+const PAGE_SIZE: u32 = 100;
 
 pub struct TestopsApi {
     pub client: BaseApiClient,
     api_prefix: String,
+    /// File with the cached project list, without it projects are always requested
+    pub project_cache_path: Option<PathBuf>,
 }
 
 impl TestopsApi {
     pub fn new(api_key: &str, base_url: &str) -> Self {
-        let base_api_client = BaseApiClient::new(base_url, api_key).unwrap();
-        Self {
+        Self::with_network_settings(api_key, base_url, &NetworkSettings::default()).unwrap()
+    }
+
+    pub fn with_network_settings(
+        api_key: &str,
+        base_url: &str,
+        network_settings: &NetworkSettings,
+    ) -> Result<Self, ApiError> {
+        let base_api_client =
+            BaseApiClient::with_network_settings(base_url, api_key, network_settings)?;
+        Ok(Self {
             client: base_api_client,
             api_prefix: "/api/rs".to_string(),
-        }
+            project_cache_path: None,
+        })
+    }
+
+    pub fn with_project_cache(mut self, path: PathBuf) -> Self {
+        self.project_cache_path = Some(path);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.client.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn with_auth_settings(mut self, auth_settings: AuthSettings) -> Self {
+        self.client = self.client.with_auth_settings(auth_settings);
+        self
     }
 
     pub async fn get_launch_by_id(
@@ -39,55 +76,136 @@ impl TestopsApi {
 
     pub async fn post_upload_report(
         &self,
-        file_path: &Path,
+        report_files: Vec<ReportFile>,
         launch_info: &LaunchInfo,
     ) -> Result<ResponseLaunchUpload, ApiError> {
-        // Читаем в буфер файл
-        let buffer = read_file_to_buffer(file_path).await?;
-
-        // Создаем multipart с данными файла
-        let file_name = get_file_name(file_path)?;
-        validate_zip_archive(&buffer)?;
-        let file_part = Part::bytes(buffer)
-            .file_name(file_name)
-            .mime_str("application/zip")
-            .unwrap();
         let info_file_json = serde_json::to_string(launch_info)?;
-        let info_file_multipart = Part::text(info_file_json)
-            .mime_str("application/json")
-            .unwrap();
-
-        // Собираем форму с файлом
-        let form = Form::new()
-            .part("info", info_file_multipart)
-            .part("archive", file_part);
+        // Форма собирается заново для каждой попытки, архив при этом пересобирается из файлов
+        let build_form = || {
+            let info_file_multipart =
+                Part::text(info_file_json.clone()).mime_str("application/json")?;
+            Ok(Form::new()
+                .part("info", info_file_multipart)
+                .part("archive", report_archive_part(report_files.clone())?))
+        };
         self.client
             .post_multipart_file::<ResponseLaunchUpload, ()>(
                 &format!("{}/launch/upload", self.api_prefix),
-                form,
+                build_form,
             )
             .await
     }
 
-    pub async fn get_all_project_ids(&self) -> Result<HashSet<u32>, ApiError> {
+    /// Count of launch results by status
+    pub async fn get_launch_statistic(
+        &self,
+        launch_id: u32,
+    ) -> Result<Vec<LaunchStatistic>, ApiError> {
+        self.client
+            .get::<Vec<LaunchStatistic>, ()>(&format!(
+                "{}/launch/{}/statistic",
+                self.api_prefix, launch_id
+            ))
+            .await
+    }
+
+    /// Upload one more archive to an already created launch
+    pub async fn post_upload_report_to_launch(
+        &self,
+        launch_id: u32,
+        report_files: Vec<ReportFile>,
+    ) -> Result<ResponseLaunchUpload, ApiError> {
+        let build_form =
+            || Ok(Form::new().part("archive", report_archive_part(report_files.clone())?));
+        self.client
+            .post_multipart_file::<ResponseLaunchUpload, ()>(
+                &format!("{}/launch/{}/upload", self.api_prefix, launch_id),
+                build_form,
+            )
+            .await
+    }
+
+    /// Page of a list endpoint, `endpoint` may already contain query parameters
+    async fn get_page<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        page: u32,
+    ) -> Result<Page<T>, ApiError> {
+        let separator = if endpoint.contains('?') { '&' } else { '?' };
+        self.client
+            .get::<Page<T>, ()>(&format!(
+                "{}{}{}page={}&size={}",
+                self.api_prefix, endpoint, separator, page, PAGE_SIZE
+            ))
+            .await
+    }
+
+    /// Items of a list endpoint page by page until the last one
+    async fn get_all_pages<T: DeserializeOwned>(&self, endpoint: &str) -> Result<Vec<T>, ApiError> {
         let mut current_page: u32 = 0;
-        let limit_pages: u32 = 50;
-        let mut project_ids: HashSet<u32> = HashSet::new();
+        let mut items: Vec<T> = Vec::new();
+        loop {
+            let response = self.get_page::<T>(endpoint, current_page).await?;
+            let is_empty_page = response.content.is_empty();
+            items.extend(response.content);
+            if is_empty_page || response.total_pages <= (current_page + 1) {
+                break;
+            };
+            current_page += 1;
+        }
+        Ok(items)
+    }
+
+    /// Make an authenticated request, fails if the token is not accepted
+    pub async fn check_access(&self) -> Result<(), ApiError> {
+        self.get_page::<ProjectInfo>("/project", 0)
+            .await
+            .map(|_| ())
+    }
+
+    /// All projects of the instance
+    pub async fn get_all_projects(&self) -> Result<Vec<ProjectInfo>, ApiError> {
+        self.get_all_pages("/project").await
+    }
+
+    /// All test cases of the project
+    pub async fn get_project_test_cases(
+        &self,
+        project_id: u32,
+    ) -> Result<Vec<TestCaseRow>, ApiError> {
+        self.get_all_pages(&format!("/testcase?projectId={}", project_id))
+            .await
+    }
+
+    /// Launches of the project created since the timestamp in milliseconds, newest first
+    pub async fn get_project_launches_since(
+        &self,
+        project_id: u32,
+        since: i64,
+    ) -> Result<Vec<GetLaunchByIdResponse>, ApiError> {
+        let endpoint = format!("/launch?projectId={}&sort=createdDate,DESC", project_id);
+        let mut current_page: u32 = 0;
+        let mut launches: Vec<GetLaunchByIdResponse> = Vec::new();
         loop {
             let response = self
-                .client
-                .get::<ResponseGetAllProject, ()>(&format!(
-                    "{}/project?page={}",
-                    self.api_prefix, current_page
-                ))
+                .get_page::<GetLaunchByIdResponse>(&endpoint, current_page)
                 .await?;
-            project_ids.extend(response.content.iter().map(|project_info| project_info.id));
-            if response.total_pages <= (current_page + 1) || current_page >= limit_pages {
+            let page_size = response.content.len();
+            let launches_before = launches.len();
+            launches.extend(
+                response
+                    .content
+                    .into_iter()
+                    .take_while(|launch| launch.created_date.is_some_and(|date| date >= since)),
+            );
+            // Запуски отсортированы от новых к старым, после первого старого дальше не идем
+            let has_older = launches.len() - launches_before < page_size;
+            if has_older || page_size == 0 || response.total_pages <= (current_page + 1) {
                 break;
             };
             current_page += 1;
         }
-        Ok(project_ids)
+        Ok(launches)
     }
 
     pub async fn get_project_info_by_id(&self, project_id: &u32) -> Result<ProjectInfo, ApiError> {
@@ -118,10 +236,19 @@ impl TestopsApi {
     }
 }
 
+/// Report archive part of the upload form
+///
+/// Archive is built on the fly right into the request body, without a temporary file
+fn report_archive_part(report_files: Vec<ReportFile>) -> Result<Part, ApiError> {
+    Ok(Part::stream(stream_report_archive(report_files))
+        .file_name(REPORT_ARCHIVE_NAME)
+        .mime_str("application/zip")?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::constants::CARGO_MANIFEST_DIR;
+    use crate::utils::{collect_report_files, ArchiveFilter};
     use mockito::Server;
 
     impl TestopsApi {
@@ -133,6 +260,7 @@ mod tests {
             Self {
                 client: base_api_client,
                 api_prefix: "/api/rs".to_string(),
+                project_cache_path: None,
             }
         }
 
@@ -143,13 +271,14 @@ mod tests {
             Self {
                 client: base_api_client,
                 api_prefix: "/api/rs".to_string(),
+                project_cache_path: None,
             }
         }
 
         pub async fn mock_post_upload_report(
             server_mock: &mut mockito::ServerGuard,
             mock_response: &ResponseLaunchUpload,
-        ) {
+        ) -> mockito::Mock {
             server_mock
                 .mock("POST", "/api/rs/launch/upload")
                 .match_header(
@@ -166,7 +295,26 @@ mod tests {
                 .with_header("content-type", "application/json")
                 .with_body(serde_json::to_string(&mock_response).unwrap())
                 .create_async()
-                .await;
+                .await
+        }
+
+        pub async fn mock_post_upload_report_to_launch(
+            server_mock: &mut mockito::ServerGuard,
+            mock_response: &ResponseLaunchUpload,
+        ) -> mockito::Mock {
+            server_mock
+                .mock(
+                    "POST",
+                    format!("/api/rs/launch/{}/upload", mock_response.launch_id).as_str(),
+                )
+                .match_body(mockito::Matcher::Regex(
+                    "Content-Disposition: form-data; name=\"archive\"".into(),
+                ))
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(serde_json::to_string(&mock_response).unwrap())
+                .create_async()
+                .await
         }
 
         pub async fn mock_get_project_by_id(
@@ -198,33 +346,63 @@ mod tests {
                 .await;
         }
 
-        pub async fn mock_get_all_projects(server_mock: &mut mockito::ServerGuard) {
-            let response_page_0 = ResponseGetAllProject {
-                total_pages: 2,
-                content: vec![
-                    ProjectInfo::new(1, "Project1"),
-                    ProjectInfo::new(2, "Test Project"),
-                ],
-            };
-            let response_page_1 = ResponseGetAllProject {
-                total_pages: 2,
-                content: vec![ProjectInfo::new(3, "Project3")],
-            };
+        pub async fn mock_get_launch_statistic(
+            server_mock: &mut mockito::ServerGuard,
+            mock_response: &[LaunchStatistic],
+            launch_id: u32,
+        ) -> mockito::Mock {
+            let endpoint =
+                mockito::Matcher::Exact(format!("/api/rs/launch/{}/statistic", launch_id));
             server_mock
-                .mock("GET", "/api/rs/project?page=0")
+                .mock("GET", endpoint)
                 .with_status(200)
                 .with_header("content-type", "application/json")
-                .with_body(serde_json::to_string(&response_page_0).unwrap())
+                .with_body(serde_json::to_string(&mock_response).unwrap())
                 .create_async()
-                .await;
+                .await
+        }
 
-            server_mock
-                .mock("GET", "/api/rs/project?page=1")
-                .with_status(200)
-                .with_header("content-type", "application/json")
-                .with_body(serde_json::to_string(&response_page_1).unwrap())
-                .create_async()
-                .await;
+        /// Мокаем постраничный список, `endpoint` без префикса api и параметров страницы
+        pub async fn mock_get_pages<T: serde::Serialize>(
+            server_mock: &mut mockito::ServerGuard,
+            endpoint: &str,
+            pages: Vec<Vec<T>>,
+        ) -> Vec<mockito::Mock> {
+            let separator = if endpoint.contains('?') { '&' } else { '?' };
+            let total_pages = pages.len() as u32;
+            let mut mocks = Vec::new();
+            for (page, content) in pages.into_iter().enumerate() {
+                let response = Page {
+                    total_pages,
+                    content,
+                };
+                let mock = server_mock
+                    .mock(
+                        "GET",
+                        mockito::Matcher::Exact(format!(
+                            "/api/rs{}{}page={}&size={}",
+                            endpoint, separator, page, PAGE_SIZE
+                        )),
+                    )
+                    .with_status(200)
+                    .with_header("content-type", "application/json")
+                    .with_body(serde_json::to_string(&response).unwrap())
+                    .create_async()
+                    .await;
+                mocks.push(mock);
+            }
+            mocks
+        }
+
+        pub async fn mock_get_all_projects(server_mock: &mut mockito::ServerGuard) {
+            let pages = vec![
+                vec![
+                    ProjectInfo::new(1, "Project1"),
+                    ProjectInfo::new(2, "Test Project"),
+                ],
+                vec![ProjectInfo::new(3, "Project3")],
+            ];
+            TestopsApi::mock_get_pages(server_mock, "/project", pages).await;
         }
 
         pub async fn mock_get_test_case_overview_by_id(
@@ -267,16 +445,103 @@ mod tests {
         let mock_response = ResponseLaunchUpload::default();
         let testops_api = TestopsApi::mock(&server.url());
         TestopsApi::mock_post_upload_report(&mut server, &mock_response).await;
-        let path_to_report =
-            Path::new(CARGO_MANIFEST_DIR).join("test_files/test_upload_launch_report.zip");
+        let report_dir = std::env::temp_dir().join(format!("wot_upload_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&report_dir).unwrap();
+        std::fs::write(report_dir.join("1-result.json"), "{}").unwrap();
+        let report_files = collect_report_files(&report_dir, &ArchiveFilter::default()).unwrap();
         // Проверяем ответ от метода
         let result = testops_api
-            .post_upload_report(&path_to_report, &LaunchInfo::default())
+            .post_upload_report(report_files, &LaunchInfo::default())
             .await;
+        std::fs::remove_dir_all(&report_dir).unwrap();
         assert!(result.is_ok());
         assert_eq!(result.unwrap().launch_id, exp_launch_id);
     }
 
+    #[tokio::test]
+    async fn test_post_upload_report_to_launch_mock() {
+        let mut server = Server::new_async().await;
+        let mock_response = ResponseLaunchUpload::default();
+        let testops_api = TestopsApi::mock(&server.url());
+        let mock = TestopsApi::mock_post_upload_report_to_launch(&mut server, &mock_response).await;
+        let result = testops_api
+            .post_upload_report_to_launch(mock_response.launch_id, vec![])
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().launch_id, mock_response.launch_id);
+        mock.assert_async().await;
+    }
+
+    fn retry_without_delay() -> RetryPolicy {
+        RetryPolicy {
+            initial_backoff_ms: 0,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[tokio::test]
+    /// Загрузка повторяется после 503, архив собирается заново для второй попытки
+    async fn test_post_upload_report_retry() {
+        let mut server = Server::new_async().await;
+        let mock_response = ResponseLaunchUpload::default();
+        let testops_api = TestopsApi::mock(&server.url()).with_retry_policy(retry_without_delay());
+        let mock_unavailable = server
+            .mock("POST", "/api/rs/launch/upload")
+            .with_status(503)
+            .with_header("retry-after", "0")
+            .expect(1)
+            .create_async()
+            .await;
+        let mock_upload = server
+            .mock("POST", "/api/rs/launch/upload")
+            .match_body(mockito::Matcher::Regex("1-result.json".into()))
+            .with_status(200)
+            .with_body(serde_json::to_string(&mock_response).unwrap())
+            .expect(1)
+            .create_async()
+            .await;
+        let report_files = vec![ReportFile {
+            path: PathBuf::from("1-result.json"),
+            name_in_archive: "1-result.json".to_string(),
+            size: 2,
+            content: Some(b"{}".to_vec()),
+        }];
+
+        let result = testops_api
+            .post_upload_report(report_files, &LaunchInfo::default())
+            .await;
+
+        assert_eq!(result.unwrap().launch_id, mock_response.launch_id);
+        mock_unavailable.assert_async().await;
+        mock_upload.assert_async().await;
+    }
+
+    #[tokio::test]
+    #[rstest::rstest]
+    #[case(502, retry_without_delay(), 3)]
+    #[case(500, retry_without_delay(), 1)]
+    #[case(503, RetryPolicy::disabled(), 1)]
+    /// Повторяются только статусы из политики и не больше max_attempts раз
+    async fn test_get_retry_attempts(
+        #[case] status: usize,
+        #[case] retry_policy: RetryPolicy,
+        #[case] expected_attempts: usize,
+    ) {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url()).with_retry_policy(retry_policy);
+        let mock = server
+            .mock("GET", "/api/rs/launch/1")
+            .with_status(status)
+            .expect(expected_attempts)
+            .create_async()
+            .await;
+
+        let result = testops_api.get_launch_by_id(1).await;
+
+        assert!(matches!(result, Err(ApiError::Api(code, _)) if code.as_u16() as usize == status));
+        mock.assert_async().await;
+    }
+
     #[tokio::test]
     async fn test_get_project_info_by_id() {
         let project_id = 2;
@@ -308,21 +573,132 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_get_all_project_ids() {
-        let exp_len_elements = 3;
-        let mut exp_response_hash_set = HashSet::with_capacity(3);
-        exp_response_hash_set.insert(1);
-        exp_response_hash_set.insert(2);
-        exp_response_hash_set.insert(3);
+    async fn test_get_launch_statistic() {
+        let launch_id = 22222;
+        let mock_response = vec![
+            LaunchStatistic::new("passed", 10),
+            LaunchStatistic::new("failed", 2),
+        ];
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        TestopsApi::mock_get_launch_statistic(&mut server, &mock_response, launch_id).await;
+
+        let result = testops_api.get_launch_statistic(launch_id).await;
+        assert_eq!(result.unwrap(), mock_response);
+    }
+
+    #[tokio::test]
+    async fn test_get_all_projects() {
         let mut server = Server::new_async().await;
         let testops_api = TestopsApi::mock(&server.url());
         TestopsApi::mock_get_all_projects(&mut server).await;
 
-        let result = testops_api.get_all_project_ids().await;
-        assert!(result.is_ok());
-        let unwrap_result = result.unwrap();
-        assert_eq!(exp_len_elements, unwrap_result.len());
-        assert_eq!(exp_response_hash_set, unwrap_result);
+        let result = testops_api.get_all_projects().await;
+        assert_eq!(
+            result.unwrap(),
+            vec![
+                ProjectInfo::new(1, "Project1"),
+                ProjectInfo::new(2, "Test Project"),
+                ProjectInfo::new(3, "Project3"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    /// Для проверки доступа достаточно первой страницы проектов
+    async fn test_check_access() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        let mocks = TestopsApi::mock_get_pages(
+            &mut server,
+            "/project",
+            vec![vec![ProjectInfo::new(1, "Project1")], vec![]],
+        )
+        .await;
+
+        assert!(testops_api.check_access().await.is_ok());
+        mocks[0].assert_async().await;
+        assert!(!mocks[1].matched_async().await);
+    }
+
+    #[tokio::test]
+    async fn test_check_access_unauthorized() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        server
+            .mock("GET", "/api/rs/project?page=0&size=100")
+            .with_status(401)
+            .with_body("Unauthorized")
+            .create_async()
+            .await;
+
+        let result = testops_api.check_access().await;
+        assert!(matches!(result, Err(ApiError::Authentication(reason))
+            if reason == "401 Unauthorized: Unauthorized"));
+    }
+
+    #[tokio::test]
+    /// Проекты запрашиваются до последней страницы, без ограничения на их количество
+    async fn test_get_all_projects_many_pages() {
+        let pages_count = 60;
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        let pages = (1..=pages_count)
+            .map(|id| vec![ProjectInfo::new(id, &format!("Project{}", id))])
+            .collect();
+        TestopsApi::mock_get_pages(&mut server, "/project", pages).await;
+
+        let result = testops_api.get_all_projects().await;
+        assert_eq!(result.unwrap().len(), pages_count as usize);
+    }
+
+    #[tokio::test]
+    async fn test_get_project_test_cases() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        let pages = vec![
+            vec![TestCaseRow::new(1, "Login", true)],
+            vec![TestCaseRow::new(2, "Logout", false)],
+        ];
+        TestopsApi::mock_get_pages(&mut server, "/testcase?projectId=2", pages).await;
+
+        let result = testops_api.get_project_test_cases(2).await;
+        assert_eq!(
+            result.unwrap(),
+            vec![
+                TestCaseRow::new(1, "Login", true),
+                TestCaseRow::new(2, "Logout", false)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    /// Запуски запрашиваются, пока не встретится запуск старше заданной даты
+    async fn test_get_project_launches_since() {
+        let mut server = Server::new_async().await;
+        let testops_api = TestopsApi::mock(&server.url());
+        let launch = |id: u32, created_date: i64| {
+            let mut launch = GetLaunchByIdResponse::new(id, &format!("Launch{}", id), 2);
+            launch.created_date = Some(created_date);
+            launch
+        };
+        let pages = vec![
+            vec![launch(5, 5000), launch(4, 4000)],
+            vec![launch(3, 3000), launch(2, 2000)],
+            vec![launch(1, 1000)],
+        ];
+        let mut mocks = TestopsApi::mock_get_pages(
+            &mut server,
+            "/launch?projectId=2&sort=createdDate,DESC",
+            pages,
+        )
+        .await;
+
+        let result = testops_api.get_project_launches_since(2, 3000).await;
+        let ids: Vec<u32> = result.unwrap().iter().map(|launch| launch.id).collect();
+        assert_eq!(ids, vec![5, 4, 3]);
+        let last_page_mock = mocks.pop().unwrap();
+        last_page_mock.expect(0).assert_async().await;
     }
 
     #[test]
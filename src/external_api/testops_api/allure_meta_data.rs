@@ -36,12 +36,42 @@ impl AllureMetaData {
             field_name.to_ascii_lowercase()
         )
     }
+
+    /// Metadata from a BDD tag: `@epic:Auth`, `@feature=Login` or `@allure.label.story:Logout`
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let tag = tag.trim_start_matches('@');
+        let tag = tag.strip_prefix("allure.label.").unwrap_or(tag);
+        let (name, value) = tag.split_once([':', '='])?;
+        let value = value.trim();
+        if value.is_empty() {
+            return None;
+        }
+        match name.to_ascii_lowercase().as_str() {
+            "epic" => Some(Self::Epic(value.to_string())),
+            "feature" => Some(Self::Feature(value.to_string())),
+            "story" => Some(Self::Story(value.to_string())),
+            "suite" => Some(Self::Suite(value.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Name and value of the allure label
+    pub fn label_name_and_value(&self) -> Option<(&'static str, &str)> {
+        match self {
+            Self::Epic(value) => Some(("epic", value)),
+            Self::Feature(value) => Some(("feature", value)),
+            Self::Story(value) => Some(("story", value)),
+            Self::Suite(value) => Some(("suite", value)),
+            Self::Label(_) | Self::Unknown => None,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use rstest::rstest;
 
     #[test]
     fn test_epic_creation() {
@@ -73,6 +103,25 @@ mod tests {
         assert_eq!(decorator, "@allure.label('smoke', 'ValueSmoke')");
     }
 
+    #[rstest]
+    #[case("@epic:Auth", Some(AllureMetaData::Epic("Auth".to_string())))]
+    #[case("@Feature=Login", Some(AllureMetaData::Feature("Login".to_string())))]
+    #[case("@allure.label.story:Logout", Some(AllureMetaData::Story("Logout".to_string())))]
+    #[case("@suite:Smoke", Some(AllureMetaData::Suite("Smoke".to_string())))]
+    #[case("@smoke", None)]
+    #[case("@epic:", None)]
+    #[case("@owner:ivan", None)]
+    fn test_from_tag(#[case] tag: &str, #[case] expected: Option<AllureMetaData>) {
+        assert_eq!(AllureMetaData::from_tag(tag), expected);
+    }
+
+    #[test]
+    fn test_label_name_and_value() {
+        let meta_data = AllureMetaData::Epic("Auth".to_string());
+        assert_eq!(meta_data.label_name_and_value(), Some(("epic", "Auth")));
+        assert_eq!(AllureMetaData::Unknown.label_name_and_value(), None);
+    }
+
     #[test]
     fn test_empty_value() {
         let decorator = AllureMetaData::epic("");
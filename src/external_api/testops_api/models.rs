@@ -1,10 +1,14 @@
 pub mod custom_field;
 pub mod custom_field_info;
+pub mod external_link;
 pub mod get_launch_by_id;
 pub mod launch_info;
+pub mod launch_statistic;
+pub mod launch_tag;
+pub mod page;
 pub mod project_info;
-pub mod response_get_all_project;
 pub mod response_launch_upload;
 pub mod tag;
 pub mod test_case_overview;
+pub mod test_case_row;
 pub mod test_case_scenario;
@@ -4,17 +4,68 @@ use serde::{Deserialize, Serialize};
 #[serde(rename_all = "camelCase")]
 pub struct GetLaunchByIdResponse {
     pub id: u32,
-    name: String,
-    project_id: u8,
+    pub name: String,
+    pub project_id: u32,
+    /// Closed launch doesn't accept new results
+    #[serde(default)]
+    pub closed: bool,
+    /// Unix timestamp in milliseconds
+    #[serde(default)]
+    pub created_date: Option<i64>,
+    /// Unix timestamp in milliseconds
+    #[serde(default)]
+    pub last_modified_date: Option<i64>,
 }
 
 impl GetLaunchByIdResponse {
     #[cfg(test)]
-    pub fn new(id: u32, name: &str, project_id: u8) -> Self {
+    pub fn new(id: u32, name: &str, project_id: u32) -> Self {
         Self {
             id,
             name: name.to_string(),
             project_id,
+            closed: false,
+            created_date: None,
+            last_modified_date: None,
         }
     }
+
+    /// Time between launch creation and its last change in milliseconds
+    pub fn duration_ms(&self) -> Option<i64> {
+        match (self.created_date, self.last_modified_date) {
+            (Some(created), Some(modified)) if modified >= created => Some(modified - created),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_minimal_launch() {
+        let launch: GetLaunchByIdResponse =
+            serde_json::from_str(r#"{"id": 1, "name": "Launch", "projectId": 300}"#).unwrap();
+        assert_eq!(launch.project_id, 300);
+        assert!(!launch.closed);
+        assert_eq!(launch.duration_ms(), None);
+    }
+
+    #[test]
+    fn test_duration_ms() {
+        let launch: GetLaunchByIdResponse = serde_json::from_str(
+            r#"{
+                "id": 1,
+                "name": "Launch",
+                "projectId": 2,
+                "closed": true,
+                "createdDate": 1735389182000,
+                "lastModifiedDate": 1735389247000
+            }"#,
+        )
+        .unwrap();
+        assert!(launch.closed);
+        assert_eq!(launch.duration_ms(), Some(65000));
+    }
 }
@@ -1,25 +1,50 @@
+use super::external_link::ExternalLink;
+use super::launch_tag::LaunchTag;
+
 #[derive(serde::Serialize, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct LaunchInfo {
     name: String,
     project_id: u32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<LaunchTag>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    links: Vec<ExternalLink>,
+    /// Close the launch automatically after processing
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    autoclose: bool,
 }
 
 impl LaunchInfo {
     #[cfg(test)]
     pub fn default() -> Self {
-        Self {
-            name: "test_report_upload".to_string(),
-            project_id: 2,
-        }
+        Self::new("test_report_upload", 2)
     }
 
     pub fn new(name: &str, project_id: u32) -> Self {
         Self {
             name: name.to_string(),
             project_id,
+            tags: vec![],
+            links: vec![],
+            autoclose: false,
         }
     }
+
+    pub fn with_tags(mut self, tags: &[String]) -> Self {
+        self.tags = tags.iter().map(|tag| LaunchTag::new(tag)).collect();
+        self
+    }
+
+    pub fn with_links(mut self, links: &[ExternalLink]) -> Self {
+        self.links = links.to_vec();
+        self
+    }
+
+    pub fn with_autoclose(mut self, autoclose: bool) -> Self {
+        self.autoclose = autoclose;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -33,7 +58,10 @@ mod tests {
             LaunchInfo::new("my_launch", 12345),
             LaunchInfo {
                 name: "my_launch".to_string(),
-                project_id: 12345
+                project_id: 12345,
+                tags: vec![],
+                links: vec![],
+                autoclose: false,
             }
         );
     }
@@ -44,8 +72,31 @@ mod tests {
             LaunchInfo::default(),
             LaunchInfo {
                 name: "test_report_upload".to_string(),
-                project_id: 2
+                project_id: 2,
+                tags: vec![],
+                links: vec![],
+                autoclose: false,
             }
         );
     }
+
+    #[test]
+    /// Пустые теги, ссылки и выключенное автозакрытие не отправляем
+    fn test_serialize_minimal_launch_info() {
+        let json = serde_json::to_string(&LaunchInfo::new("my_launch", 1)).unwrap();
+        assert_eq!(json, r#"{"name":"my_launch","projectId":1}"#);
+    }
+
+    #[test]
+    fn test_serialize_full_launch_info() {
+        let launch_info = LaunchInfo::new("my_launch", 1)
+            .with_tags(&["smoke".to_string(), "main".to_string()])
+            .with_links(&[ExternalLink::new("CI", "https://ci.example.com/1")])
+            .with_autoclose(true);
+        let json = serde_json::to_string(&launch_info).unwrap();
+        assert_eq!(
+            json,
+            r#"{"name":"my_launch","projectId":1,"tags":[{"name":"smoke"},{"name":"main"}],"links":[{"name":"CI","url":"https://ci.example.com/1"}],"autoclose":true}"#
+        );
+    }
 }
@@ -0,0 +1,16 @@
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchStatistic {
+    pub status: String,
+    pub count: u32,
+}
+
+impl LaunchStatistic {
+    #[cfg(test)]
+    pub fn new(status: &str, count: u32) -> Self {
+        Self {
+            status: status.to_string(),
+            count,
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+/// Page of a paginated list endpoint
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    pub total_pages: u32,
+    pub content: Vec<T>,
+}
@@ -1,8 +1,18 @@
-#[derive(serde::Deserialize, serde::Serialize, Debug)]
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ProjectInfo {
     pub id: u32,
     pub name: String,
+    /// Short project code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abbr: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_public: Option<bool>,
+    /// Unix timestamp in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_date: Option<i64>,
 }
 
 impl ProjectInfo {
@@ -11,6 +21,43 @@ impl ProjectInfo {
         Self {
             id,
             name: name.to_string(),
+            abbr: None,
+            description: None,
+            is_public: None,
+            created_date: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_project_details() {
+        let project: ProjectInfo = serde_json::from_str(
+            r#"{
+                "id": 2,
+                "name": "Test Project",
+                "abbr": "TP",
+                "description": "Smoke tests",
+                "isPublic": true,
+                "createdDate": 1735389182000,
+                "favorite": false
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(project.abbr.as_deref(), Some("TP"));
+        assert_eq!(project.description.as_deref(), Some("Smoke tests"));
+        assert_eq!(project.is_public, Some(true));
+        assert_eq!(project.created_date, Some(1_735_389_182_000));
+    }
+
+    #[test]
+    fn test_serialize_minimal_project() {
+        assert_eq!(
+            serde_json::to_string(&ProjectInfo::new(2, "Test Project")).unwrap(),
+            r#"{"id":2,"name":"Test Project"}"#
+        );
+    }
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// Test case from the project test case list
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TestCaseRow {
+    pub id: u32,
+    pub name: String,
+    /// Automated test case is linked with a test in the code, manual is run by hand
+    #[serde(default)]
+    pub automated: bool,
+}
+
+impl TestCaseRow {
+    #[cfg(test)]
+    pub fn new(id: u32, name: &str, automated: bool) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+            automated,
+        }
+    }
+}
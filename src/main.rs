@@ -1,48 +1,128 @@
+pub mod allure_results;
+pub mod ci_environment;
 pub mod cli_app;
 pub mod command_logic;
 pub mod config;
 pub mod constants;
+pub mod converters;
 pub mod create_template;
 pub mod external_api;
+pub mod repo_defaults;
 pub mod utils;
 
+use clap::error::ErrorKind;
 use clap::Parser;
-use cli_app::{handle_command, Cli};
-use command_logic::report::send_report;
+use cli_app::{
+    handle_check_command, handle_command, handle_config_command, handle_offline_command, Cli,
+    Commands, ConfigArgs, ConfigCommands,
+};
+use command_logic::convert::convert_report;
+use command_logic::launch::check_launch_gate;
+use command_logic::project::{list_projects, project_stats, show_project};
+use command_logic::report::{send_report, validate_report};
 use command_logic::testcase::import_testcase_by_id;
-use config::Config;
-use constants::CONFIG_DIR;
+use config::{Config, EnvProfile};
+use constants::{Message, CONFIG_DIR, PROJECT_CACHE_FILE};
 use directories::UserDirs;
 use external_api::testops_api::TestopsApi;
 use external_api::ApiError;
-use std::fs::File;
+use std::io::IsTerminal;
 use std::path::Path;
+use std::process::ExitCode;
 
 #[tokio::main]
-async fn main() -> Result<(), ApiError> {
+async fn main() -> Result<ExitCode, ApiError> {
     let mut config_path = Path::new("config.json");
     if cfg!(debug_assertions) {
         config_path = Path::new("test_config.json");
     }
     if let Some(user_dirs) = UserDirs::new() {
-        let path = user_dirs.home_dir().join(CONFIG_DIR).join(config_path);
-        if path.exists() {
-            let config = Config::get_config(path)?;
-            let testops_api = TestopsApi::new(&config.testops_api_token, &config.testops_base_url);
-            let cli = Cli::parse();
-            let stdin = std::io::stdin();
-            let stdout = std::io::stdout();
+        let default_path = user_dirs.home_dir().join(CONFIG_DIR).join(config_path);
+        let env = EnvProfile::from_env();
+        // Мастер настройки запускается только в терминале, если конфиг не задан ничем другим
+        let needs_wizard =
+            |path: &Path| !path.exists() && !env.is_complete() && std::io::stdin().is_terminal();
 
-            handle_command(cli, &testops_api, stdin, stdout).await;
-        } else {
+        // Разбираем аргументы до чтения конфига, чтобы --help работал и без него
+        let cli = match Cli::try_parse() {
+            Ok(cli) => cli,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    ErrorKind::MissingSubcommand
+                        | ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand
+                ) && needs_wizard(&default_path) =>
+            {
+                let app = Config::new()?;
+                app.save(&default_path)?;
+                return Ok(ExitCode::SUCCESS);
+            }
+            Err(e) => e.exit(),
+        };
+        let mut cli = cli;
+        let path = cli.config.clone().unwrap_or(default_path);
+        let is_check = matches!(
+            &cli.command,
+            Commands::Config(ConfigArgs {
+                command: ConfigCommands::Check
+            })
+        );
+        if !is_check && matches!(cli.command, Commands::Config(_) | Commands::Init(_)) {
+            let (stdin, stdout) = (std::io::stdin(), std::io::stdout());
+            return Ok(handle_config_command(&cli, &path, stdin, stdout));
+        }
+        if cli.is_offline() {
+            return Ok(handle_offline_command(&cli, std::io::stdout()));
+        }
+        if let Err(e) = cli.apply_repo_defaults(&std::env::current_dir()?) {
+            eprintln!("{}", e);
+            return Ok(ExitCode::from(e.exit_code()));
+        }
+        if cli.config.is_none() && needs_wizard(&path) {
             let app = Config::new()?;
-            if let Some(parent_dir) = path.parent() {
-                std::fs::create_dir_all(parent_dir)?;
+            app.save(&path)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        let loaded = Config::load(&path, &env).and_then(|config| {
+            let profile = config.profile(config.profile_name(cli.profile.as_deref()))?;
+            Ok((config, env.apply(profile)?))
+        });
+        let (config, profile) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                eprintln!("{}", e);
+                return Ok(ExitCode::from(e.exit_code()));
+            }
+        };
+        let project_cache_path = user_dirs
+            .home_dir()
+            .join(CONFIG_DIR)
+            .join(PROJECT_CACHE_FILE);
+        let network_settings = cli.network.override_settings(config.network);
+        if network_settings.insecure {
+            eprintln!("{}", Message::InsecureConnection.to_formatted_string());
+        }
+        let testops_api = match TestopsApi::with_network_settings(
+            &profile.testops_api_token,
+            &profile.testops_base_url,
+            &network_settings,
+        ) {
+            Ok(testops_api) => testops_api
+                .with_project_cache(project_cache_path)
+                .with_retry_policy(config.retry)
+                .with_auth_settings(config.auth),
+            Err(e) => {
+                eprintln!("{}", e);
+                return Ok(ExitCode::from(e.exit_code()));
             }
-            let file = File::create(path)?;
-            serde_json::to_writer_pretty(file, &app)
-                .unwrap_or_else(|_| panic!("{}", ApiError::CantCreateConfig.to_string()))
+        };
+        if is_check {
+            return Ok(handle_check_command(&profile, &testops_api).await);
         }
+        let stdin = std::io::stdin();
+        let stdout = std::io::stdout();
+
+        return Ok(handle_command(cli, &testops_api, stdin, stdout).await);
     }
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
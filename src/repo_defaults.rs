@@ -0,0 +1,374 @@
+use crate::cli_app::parse_project;
+use crate::command_logic::project::ProjectRef;
+use crate::external_api::ApiError;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, TableLike};
+
+/// File with the defaults of the repository, searched from the current directory up
+pub const REPO_DEFAULTS_FILE: &str = ".wot.toml";
+/// Launch name used when neither the flag nor the file set it
+pub const DEFAULT_LAUNCH_NAME: &str = "Run from {date}";
+/// Pytest mark left in the generated test for the user to replace
+pub const DEFAULT_TESTCASE_MARK: &str = "TEMPLATE_MARK_NAME";
+/// Project for the runs without `--project`, it overrides the file
+pub const ENV_PROJECT: &str = "WOT_PROJECT";
+
+/// Defaults of the commands from `.wot.toml`, the environment and command line flags
+/// override them
+///
+/// The same struct holds the values of each source. Relative paths from the file are resolved
+/// from its directory
+#[derive(Debug, Default, PartialEq)]
+pub struct RepoDefaults {
+    /// File the defaults were read from, None if there is no file
+    pub path: Option<PathBuf>,
+    pub project: Option<ProjectRef>,
+    pub directories: Vec<String>,
+    /// Launch name with `{date}`, `{branch}`, `{commit}` and `{pipeline}` placeholders
+    pub launch_name: Option<String>,
+    pub tags: Vec<String>,
+    /// Pytest mark of the generated test
+    pub testcase_mark: Option<String>,
+    /// Directory for the generated tests
+    pub testcase_output_dir: Option<String>,
+}
+
+impl RepoDefaults {
+    /// Defaults from the nearest `.wot.toml` in the directory or its parents
+    pub fn discover(start_dir: &Path) -> Result<Self, ApiError> {
+        for dir in start_dir.ancestors() {
+            let path = dir.join(REPO_DEFAULTS_FILE);
+            if path.is_file() {
+                let content = std::fs::read_to_string(&path)?;
+                return Self::parse(&content, &path);
+            }
+        }
+        Ok(Self::default())
+    }
+
+    /// Read the content of the file, unknown keys are errors to catch typos
+    pub fn parse(content: &str, path: &Path) -> Result<Self, ApiError> {
+        let invalid =
+            |reason: String| ApiError::InvalidRepoDefaults(path.display().to_string(), reason);
+        let document: DocumentMut = content
+            .parse()
+            .map_err(|e: toml_edit::TomlError| invalid(e.message().to_string()))?;
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        let resolve = |dir: String| base_dir.join(dir).display().to_string();
+        let mut defaults = Self {
+            path: Some(path.to_path_buf()),
+            ..Self::default()
+        };
+        for (key, item) in document.iter() {
+            match key {
+                "project" => defaults.project = Some(project_value(item).map_err(invalid)?),
+                "report" => {
+                    for (key, item) in table(item, "report").map_err(invalid)?.iter() {
+                        let key = format!("report.{}", key);
+                        match key.as_str() {
+                            "report.directories" => {
+                                defaults.directories = string_array(item, &key)
+                                    .map_err(invalid)?
+                                    .into_iter()
+                                    .map(resolve)
+                                    .collect();
+                            }
+                            "report.launch_name" => {
+                                defaults.launch_name =
+                                    Some(string_value(item, &key).map_err(invalid)?);
+                            }
+                            "report.tags" => {
+                                defaults.tags = string_array(item, &key).map_err(invalid)?;
+                            }
+                            _ => return Err(invalid(format!("unknown key `{}`", key))),
+                        }
+                    }
+                }
+                "testcase" => {
+                    for (key, item) in table(item, "testcase").map_err(invalid)?.iter() {
+                        let key = format!("testcase.{}", key);
+                        match key.as_str() {
+                            "testcase.mark" => {
+                                defaults.testcase_mark =
+                                    Some(string_value(item, &key).map_err(invalid)?);
+                            }
+                            "testcase.output_dir" => {
+                                let output_dir = string_value(item, &key).map_err(invalid)?;
+                                defaults.testcase_output_dir = Some(resolve(output_dir));
+                            }
+                            _ => return Err(invalid(format!("unknown key `{}`", key))),
+                        }
+                    }
+                }
+                _ => return Err(invalid(format!("unknown key `{}`", key))),
+            }
+        }
+        Ok(defaults)
+    }
+
+    /// Values from the environment variables, `env` returns the value of the variable
+    pub fn from_env(env: impl Fn(&str) -> Option<String>) -> Result<Self, ApiError> {
+        let project = env(ENV_PROJECT)
+            .map(|project| {
+                parse_project(&project).map_err(|_| {
+                    ApiError::InvalidRepoDefaults(
+                        ENV_PROJECT.to_string(),
+                        "must be a project id or name".to_string(),
+                    )
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            project,
+            ..Self::default()
+        })
+    }
+
+    /// Rows of key, effective value and its source
+    ///
+    /// Flags override the environment, the environment overrides the file
+    pub fn describe(&self, flags: &RepoDefaults, env: &RepoDefaults) -> Vec<Vec<String>> {
+        let file = self
+            .path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        let sources = [
+            (flags, "flag".to_string()),
+            (env, "env".to_string()),
+            (self, file),
+        ];
+        let row = |key: &str, value: fn(&RepoDefaults) -> Option<String>, default: Option<&str>| {
+            let (value, source) = sources
+                .iter()
+                .find_map(|(defaults, source)| value(defaults).map(|value| (value, source.clone())))
+                .or_else(|| default.map(|default| (default.to_string(), "default".to_string())))
+                .unwrap_or_else(|| (String::new(), "not set".to_string()));
+            vec![key.to_string(), value, source]
+        };
+        vec![
+            row(
+                "project",
+                |defaults| {
+                    defaults.project.as_ref().map(|project| match project {
+                        ProjectRef::Id(id) => id.to_string(),
+                        ProjectRef::Name(name) => name.clone(),
+                    })
+                },
+                None,
+            ),
+            row(
+                "report.directories",
+                |defaults| list(&defaults.directories),
+                None,
+            ),
+            row(
+                "report.launch_name",
+                |defaults| defaults.launch_name.clone(),
+                Some(DEFAULT_LAUNCH_NAME),
+            ),
+            row("report.tags", |defaults| list(&defaults.tags), None),
+            row(
+                "testcase.mark",
+                |defaults| defaults.testcase_mark.clone(),
+                Some(DEFAULT_TESTCASE_MARK),
+            ),
+            row(
+                "testcase.output_dir",
+                |defaults| defaults.testcase_output_dir.clone(),
+                Some("."),
+            ),
+        ]
+    }
+}
+
+fn list(values: &[String]) -> Option<String> {
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+fn table<'a>(item: &'a Item, key: &str) -> Result<&'a dyn TableLike, String> {
+    item.as_table_like()
+        .ok_or_else(|| format!("`{}` must be a table", key))
+}
+
+fn string_value(item: &Item, key: &str) -> Result<String, String> {
+    item.as_str()
+        .map(str::to_string)
+        .ok_or_else(|| format!("`{}` must be a string", key))
+}
+
+fn string_array(item: &Item, key: &str) -> Result<Vec<String>, String> {
+    let error = || format!("`{}` must be an array of strings", key);
+    item.as_array()
+        .ok_or_else(error)?
+        .iter()
+        .map(|value| value.as_str().map(str::to_string).ok_or_else(error))
+        .collect()
+}
+
+/// Project by id as a number or by name as a string, a numeric string is also an id
+fn project_value(item: &Item) -> Result<ProjectRef, String> {
+    let error = || "`project` must be a project id or name".to_string();
+    match (item.as_integer(), item.as_str()) {
+        (Some(id), _) => u32::try_from(id)
+            .ok()
+            .filter(|id| *id > 0)
+            .map(ProjectRef::Id)
+            .ok_or_else(error),
+        (_, Some(name)) => parse_project(name).map_err(|_| error()),
+        _ => Err(error()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    /// Относительные пути считаются от директории файла
+    fn test_parse_defaults() {
+        let content = r#"
+project = "Mobile"
+
+[report]
+directories = ["allure-results", "/tmp/ui-results"]
+launch_name = "Nightly {branch}"
+tags = ["nightly", "mobile"]
+
+[testcase]
+mark = "regression"
+output_dir = "tests/generated"
+"#;
+        let defaults = RepoDefaults::parse(content, Path::new("/repo/.wot.toml")).unwrap();
+        assert_eq!(
+            defaults,
+            RepoDefaults {
+                path: Some(PathBuf::from("/repo/.wot.toml")),
+                project: Some(ProjectRef::Name("Mobile".to_string())),
+                directories: vec![
+                    "/repo/allure-results".to_string(),
+                    "/tmp/ui-results".to_string()
+                ],
+                launch_name: Some("Nightly {branch}".to_string()),
+                tags: vec!["nightly".to_string(), "mobile".to_string()],
+                testcase_mark: Some("regression".to_string()),
+                testcase_output_dir: Some("/repo/tests/generated".to_string()),
+            }
+        );
+    }
+
+    #[rstest]
+    #[case("project = 777", ProjectRef::Id(777))]
+    #[case("project = \"12\"", ProjectRef::Id(12))]
+    #[case("project = \"Web\"", ProjectRef::Name("Web".to_string()))]
+    fn test_parse_project(#[case] content: &str, #[case] expected: ProjectRef) {
+        let defaults = RepoDefaults::parse(content, Path::new(".wot.toml")).unwrap();
+        assert_eq!(defaults.project, Some(expected));
+    }
+
+    #[rstest]
+    #[case("project = 0", "`project` must be a project id or name")]
+    #[case("projet = 1", "unknown key `projet`")]
+    #[case("[report]\ntag = [\"a\"]", "unknown key `report.tag`")]
+    #[case("[report]\ntags = \"a\"", "`report.tags` must be an array of strings")]
+    #[case(
+        "[report]\ndirectories = [1]",
+        "`report.directories` must be an array of strings"
+    )]
+    #[case("testcase = 1", "`testcase` must be a table")]
+    #[case("[testcase]\nmark = true", "`testcase.mark` must be a string")]
+    fn test_parse_invalid(#[case] content: &str, #[case] reason: &str) {
+        let error = RepoDefaults::parse(content, Path::new(".wot.toml")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("Invalid <.wot.toml>: {}", reason)
+        );
+    }
+
+    #[test]
+    /// Ближайший к директории файл важнее файлов выше по дереву
+    fn test_discover() {
+        let root = std::env::temp_dir().join(format!("wot_repo_{}", uuid::Uuid::new_v4()));
+        let nested = root.join("service").join("tests");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(root.join(REPO_DEFAULTS_FILE), "project = 1").unwrap();
+        let from_root = RepoDefaults::discover(&nested).unwrap();
+        std::fs::write(root.join("service").join(REPO_DEFAULTS_FILE), "project = 2").unwrap();
+        let from_service = RepoDefaults::discover(&nested).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(from_root.project, Some(ProjectRef::Id(1)));
+        assert_eq!(from_root.path, Some(root.join(REPO_DEFAULTS_FILE)));
+        assert_eq!(from_service.project, Some(ProjectRef::Id(2)));
+    }
+
+    #[test]
+    fn test_describe() {
+        let defaults = RepoDefaults::parse(
+            "project = 7\n[report]\ntags = [\"a\", \"b\"]",
+            Path::new("/repo/.wot.toml"),
+        )
+        .unwrap();
+        assert_eq!(
+            defaults.describe(&RepoDefaults::default(), &RepoDefaults::default()),
+            vec![
+                vec!["project", "7", "/repo/.wot.toml"],
+                vec!["report.directories", "", "not set"],
+                vec!["report.launch_name", DEFAULT_LAUNCH_NAME, "default"],
+                vec!["report.tags", "a, b", "/repo/.wot.toml"],
+                vec!["testcase.mark", DEFAULT_TESTCASE_MARK, "default"],
+                vec!["testcase.output_dir", ".", "default"],
+            ]
+        );
+    }
+
+    #[test]
+    /// Флаг важнее переменной окружения, переменная важнее файла
+    fn test_describe_overrides() {
+        let defaults = RepoDefaults::parse(
+            "project = 7\n[report]\ntags = [\"a\"]\nlaunch_name = \"Nightly\"",
+            Path::new("/repo/.wot.toml"),
+        )
+        .unwrap();
+        let flags = RepoDefaults {
+            tags: vec!["smoke".to_string()],
+            testcase_mark: Some("regression".to_string()),
+            ..RepoDefaults::default()
+        };
+        let env = RepoDefaults::from_env(|name| (name == ENV_PROJECT).then(|| "Web".to_string()))
+            .unwrap();
+        assert_eq!(
+            defaults.describe(&flags, &env),
+            vec![
+                vec!["project", "Web", "env"],
+                vec!["report.directories", "", "not set"],
+                vec!["report.launch_name", "Nightly", "/repo/.wot.toml"],
+                vec!["report.tags", "smoke", "flag"],
+                vec!["testcase.mark", "regression", "flag"],
+                vec!["testcase.output_dir", ".", "default"],
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(None, None)]
+    #[case(Some("12"), Some(ProjectRef::Id(12)))]
+    #[case(Some("Mobile"), Some(ProjectRef::Name("Mobile".to_string())))]
+    fn test_from_env(#[case] value: Option<&str>, #[case] expected: Option<ProjectRef>) {
+        let defaults = RepoDefaults::from_env(|name| {
+            value.filter(|_| name == ENV_PROJECT).map(str::to_string)
+        })
+        .unwrap();
+        assert_eq!(defaults.project, expected);
+    }
+
+    #[test]
+    fn test_from_env_invalid_project() {
+        let error = RepoDefaults::from_env(|_| Some("0".to_string())).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid <WOT_PROJECT>: must be a project id or name"
+        );
+    }
+}
@@ -1,50 +1,28 @@
-use std::io::Cursor;
 use std::path::Path;
-use tokio::{
-    fs::File,
-    io::{AsyncReadExt, AsyncWriteExt},
-};
-use zip::ZipArchive;
+use tokio::{fs::File, io::AsyncWriteExt, sync::mpsc};
 
 use super::external_api::ApiError;
 
 use super::constants::CONFIG_DIR;
 use directories::UserDirs;
+use futures_util::Stream;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use regex::Regex;
 
-use super::external_api::testops_api::TestopsApi;
-use std::collections::HashSet;
+use super::allure_results::{
+    attachment_sources, is_container_file, is_result_file, replace_attachment_sources,
+    CONTAINER_FILE_SUFFIX, RESULT_FILE_SUFFIX,
+};
+use std::collections::HashMap;
 use std::fs::{self, read_dir};
-use std::io::Write;
+use std::io::{self, BufReader, Read, Write};
 use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
 use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
 
-pub async fn read_file_to_buffer(path: &Path) -> Result<Vec<u8>, ApiError> {
-    let mut file = File::open(path).await?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer).await?;
-    Ok(buffer)
-}
-
-/// Get file name with extension
-pub fn get_file_name(path: &Path) -> Result<String, ApiError> {
-    if path.is_dir() {
-        return Err(ApiError::InvalidFileName);
-    }
-    Ok(path
-        .file_name()
-        .and_then(|name| name.to_str())
-        .ok_or(ApiError::InvalidFileName)?
-        .to_string())
-}
-
-pub fn validate_zip_archive(buffer: &Vec<u8>) -> Result<(), ApiError> {
-    let cursor = Cursor::new(buffer);
-    if ZipArchive::new(cursor).is_err() {
-        return Err(ApiError::InvalidFileFormat);
-    }
-    Ok(())
-}
+/// Size of the chunk sent to the upload body
+const ARCHIVE_CHUNK_SIZE: usize = 64 * 1024;
+/// How many chunks can wait for sending, limits memory usage
+const ARCHIVE_CHANNEL_CAPACITY: usize = 16;
 
 /// Create file in current directory
 ///
@@ -59,71 +37,567 @@ pub async fn save_file_in_current_directory(
     file.write_all(content)
         .await
         .map_err(|_| ApiError::CouldNotCreateFile)?;
+    // tokio пишет в фоне, без flush содержимое может не попасть в файл к возврату
+    file.flush()
+        .await
+        .map_err(|_| ApiError::CouldNotCreateFile)?;
     let mut path = std::env::current_dir().map_err(|_| ApiError::CouldNotCreateFile)?;
     path.push(file_name);
     Ok(path.display().to_string())
 }
 
-/// Get path directory with report tests
-fn get_dir_archive() -> Result<PathBuf, ApiError> {
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map_err(|_| ApiError::InvalidSystemTime)?
-        .as_secs();
-
-    UserDirs::new()
-        .ok_or(ApiError::NotFoundUserDir)
-        .map(|user_dirs| {
-            user_dirs
-                .home_dir()
-                .join(CONFIG_DIR)
-                .join(format!("testops_results_report_{timestamp}.zip"))
+/// Filter of files that get into the report archive
+///
+/// Patterns are matched against the path relative to the report directory
+#[derive(Default)]
+pub struct ArchiveFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl ArchiveFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, ApiError> {
+        Ok(Self {
+            include: build_glob_set(include)?,
+            exclude: build_glob_set(exclude)?,
+        })
+    }
+
+    /// File is packed when it matches any include pattern (or there are none)
+    /// and does not match any exclude pattern
+    pub fn is_match(&self, relative_path: &Path) -> bool {
+        let is_included = self
+            .include
+            .as_ref()
+            .is_none_or(|include| include.is_match(relative_path));
+        let is_excluded = self
+            .exclude
+            .as_ref()
+            .is_some_and(|exclude| exclude.is_match(relative_path));
+        is_included && !is_excluded
+    }
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<Option<GlobSet>, ApiError> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| ApiError::InvalidGlobPattern(e.to_string()))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| ApiError::InvalidGlobPattern(e.to_string()))
+}
+
+/// File from the report directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportFile {
+    /// Full path to file on disk
+    pub path: PathBuf,
+    /// Path inside the archive, always separated by '/'
+    pub name_in_archive: String,
+    /// File size in bytes
+    pub size: u64,
+    /// Content that replaces the file on disk, e.g. result with renamed attachments
+    pub content: Option<Vec<u8>>,
+}
+
+impl ReportFile {
+    pub fn read(&self) -> io::Result<Vec<u8>> {
+        match &self.content {
+            Some(content) => Ok(content.clone()),
+            None => fs::read(&self.path),
+        }
+    }
+
+    /// Reader of the content, the file on disk is not loaded into memory
+    pub fn reader(&self) -> io::Result<Box<dyn Read + '_>> {
+        Ok(match &self.content {
+            Some(content) => Box::new(content.as_slice()),
+            None => Box::new(BufReader::new(fs::File::open(&self.path)?)),
+        })
+    }
+}
+
+/// Recursively collect files from the report directory
+///
+/// Result is sorted by the path inside the archive
+pub fn collect_report_files(
+    path_to_report_dir: &Path,
+    filter: &ArchiveFilter,
+) -> Result<Vec<ReportFile>, ApiError> {
+    if !path_to_report_dir.is_dir() {
+        return Err(ApiError::NotFoundDirByPath(
+            path_to_report_dir.display().to_string(),
+        ));
+    }
+    let mut report_files = Vec::new();
+    let mut dirs = vec![path_to_report_dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            // Не ходим по симлинкам на директории, чтобы не зациклиться
+            if entry.file_type()?.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            if !path.is_file() {
+                continue;
+            }
+            let relative_path = path
+                .strip_prefix(path_to_report_dir)
+                .map_err(|_| ApiError::InvalidFileName)?;
+            if !filter.is_match(relative_path) {
+                continue;
+            }
+            let name_in_archive = relative_path
+                .components()
+                .map(|component| component.as_os_str().to_str())
+                .collect::<Option<Vec<&str>>>()
+                .ok_or(ApiError::InvalidFileName)?
+                .join("/");
+            report_files.push(ReportFile {
+                name_in_archive,
+                size: entry.metadata()?.len(),
+                path,
+                content: None,
+            });
+        }
+    }
+    report_files.sort_by(|a, b| a.name_in_archive.cmp(&b.name_in_archive));
+    Ok(report_files)
+}
+
+/// Collect files of several report directories into one report
+///
+/// Equal files with the same name are sent once. Other name collisions are renamed,
+/// references to renamed attachments are rewritten in results and containers
+pub fn collect_report_dirs(
+    paths_to_report_dirs: &[String],
+    filter: &ArchiveFilter,
+) -> Result<Vec<ReportFile>, ApiError> {
+    let mut report_files: Vec<ReportFile> = Vec::new();
+    let mut index_by_name: HashMap<String, usize> = HashMap::new();
+    for path_to_report_dir in paths_to_report_dirs {
+        let first_dir_file = report_files.len();
+        let mut renamed_files: HashMap<String, String> = HashMap::new();
+        for mut report_file in collect_report_files(Path::new(path_to_report_dir), filter)? {
+            if let Some(&index) = index_by_name.get(&report_file.name_in_archive) {
+                if is_same_content(&report_files[index], &report_file)? {
+                    continue;
+                }
+                let new_name = unique_name_in_archive(&report_file.name_in_archive, &index_by_name);
+                renamed_files.insert(report_file.name_in_archive.clone(), new_name.clone());
+                report_file.name_in_archive = new_name;
+            }
+            index_by_name.insert(report_file.name_in_archive.clone(), report_files.len());
+            report_files.push(report_file);
+        }
+        if !renamed_files.is_empty() {
+            for report_file in &mut report_files[first_dir_file..] {
+                rewrite_renamed_attachments(report_file, &renamed_files)?;
+            }
+        }
+    }
+    report_files.sort_by(|a, b| a.name_in_archive.cmp(&b.name_in_archive));
+    Ok(report_files)
+}
+
+/// Compare the files chunk by chunk, so colliding videos and traces are not loaded into memory
+fn is_same_content(first: &ReportFile, second: &ReportFile) -> Result<bool, ApiError> {
+    if first.size != second.size {
+        return Ok(false);
+    }
+    let (mut first, mut second) = (first.reader()?, second.reader()?);
+    let mut first_chunk = vec![0; ARCHIVE_CHUNK_SIZE];
+    let mut second_chunk = vec![0; ARCHIVE_CHUNK_SIZE];
+    loop {
+        let read = read_chunk(&mut first, &mut first_chunk)?;
+        if read != read_chunk(&mut second, &mut second_chunk)?
+            || first_chunk[..read] != second_chunk[..read]
+        {
+            return Ok(false);
+        }
+        if read == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+/// Fill the buffer, it is filled partially only at the end of the content
+fn read_chunk(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..])? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    Ok(filled)
+}
+
+/// Add a number to the file name, keeping allure suffixes and extension
+///
+/// `1-result.json` -> `1-1-result.json`, `log-attachment.txt` -> `log-attachment-1.txt`
+fn unique_name_in_archive(name: &str, taken_names: &HashMap<String, usize>) -> String {
+    let (dir, file_name) = match name.rsplit_once('/') {
+        Some((dir, file_name)) => (format!("{dir}/"), file_name),
+        None => (String::new(), name),
+    };
+    let (stem, suffix) = [RESULT_FILE_SUFFIX, CONTAINER_FILE_SUFFIX]
+        .iter()
+        .find_map(|suffix| {
+            file_name
+                .strip_suffix(suffix)
+                .map(|stem| (stem, suffix.to_string()))
+        })
+        .or_else(|| {
+            file_name
+                .rsplit_once('.')
+                .filter(|(stem, _)| !stem.is_empty())
+                .map(|(stem, extension)| (stem, format!(".{extension}")))
         })
+        .unwrap_or((file_name, String::new()));
+    (1..)
+        .map(|number| format!("{dir}{stem}-{number}{suffix}"))
+        .find(|new_name| !taken_names.contains_key(new_name))
+        .expect("infinite iterator always finds a free name")
 }
 
-/// Validate project id. Check project_id is in project ids list testops
-pub async fn validate_project_id(
-    project_id: u32,
-    testops_api_client: &TestopsApi,
+/// Point attachment sources of result or container to renamed files
+fn rewrite_renamed_attachments(
+    report_file: &mut ReportFile,
+    renamed_files: &HashMap<String, String>,
 ) -> Result<(), ApiError> {
-    let set_project_ids: HashSet<u32> = testops_api_client.get_all_project_ids().await?;
-    if !set_project_ids.contains(&project_id) {
-        return Err(ApiError::ProjectIdNotFound(project_id));
+    let name = report_file.name_in_archive.as_str();
+    if !is_result_file(name) && !is_container_file(name) {
+        return Ok(());
+    }
+    // Битые файлы не трогаем, их найдет валидация отчета
+    let Ok(mut value) = serde_json::from_slice::<serde_json::Value>(&report_file.read()?) else {
+        return Ok(());
+    };
+    let dir_prefix = name
+        .rsplit_once('/')
+        .map(|(dir, _)| format!("{dir}/"))
+        .unwrap_or_default();
+    let replace = |source: &str| {
+        renamed_files
+            .get(&format!("{dir_prefix}{source}"))
+            .and_then(|new_name| new_name.strip_prefix(&dir_prefix))
+            .map(String::from)
+    };
+    if replace_attachment_sources(&mut value, &replace) {
+        let content = serde_json::to_vec(&value)?;
+        report_file.size = content.len() as u64;
+        report_file.content = Some(content);
     }
     Ok(())
 }
 
-/// Directory archive to *.zip
-pub async fn zip_directory(path_to_report_dir: &str) -> Result<PathBuf, ApiError> {
-    let dir_archive = get_dir_archive()?;
-    if let Some(parent) = &dir_archive.parent() {
-        fs::create_dir_all(parent)?;
+/// Split report files into batches with limited total size
+///
+/// Result or container is kept in one batch with its attachments. Files that don't
+/// belong to any result (executor.json, categories.json, ...) go to the first batch.
+/// There is always at least one batch, even for an empty report
+pub fn split_into_batches(
+    report_files: Vec<ReportFile>,
+    max_batch_size: u64,
+) -> Vec<Vec<ReportFile>> {
+    let total_size: u64 = report_files
+        .iter()
+        .map(|report_file| report_file.size)
+        .sum();
+    if total_size <= max_batch_size {
+        return vec![report_files];
     }
-    let mut zip = ZipWriter::new(std::fs::File::create(&dir_archive)?);
+
+    let index_by_name: HashMap<&str, usize> = report_files
+        .iter()
+        .enumerate()
+        .map(|(index, report_file)| (report_file.name_in_archive.as_str(), index))
+        .collect();
+    let mut is_grouped = vec![false; report_files.len()];
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for (index, report_file) in report_files.iter().enumerate() {
+        let name = report_file.name_in_archive.as_str();
+        if !is_result_file(name) && !is_container_file(name) {
+            continue;
+        }
+        let mut group = vec![index];
+        is_grouped[index] = true;
+        // Битые файлы не разбираем, их найдет валидация отчета
+        let sources = report_file
+            .read()
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .map(|value| attachment_sources(&value))
+            .unwrap_or_default();
+        let dir_in_archive = name.rsplit_once('/').map(|(dir, _)| dir);
+        for source in sources {
+            let attachment_name = match dir_in_archive {
+                Some(dir) => format!("{dir}/{source}"),
+                None => source,
+            };
+            if let Some(&attachment_index) = index_by_name.get(attachment_name.as_str()) {
+                if !is_grouped[attachment_index] {
+                    is_grouped[attachment_index] = true;
+                    group.push(attachment_index);
+                }
+            }
+        }
+        groups.push(group);
+    }
+    let common_files: Vec<usize> = (0..report_files.len())
+        .filter(|&index| !is_grouped[index])
+        .collect();
+    groups.insert(0, common_files);
+
+    let mut batches: Vec<Vec<usize>> = vec![Vec::new()];
+    let mut batch_size: u64 = 0;
+    for group in groups {
+        let group_size: u64 = group.iter().map(|&index| report_files[index].size).sum();
+        let is_batch_empty = batches.last().is_none_or(Vec::is_empty);
+        if !is_batch_empty && batch_size + group_size > max_batch_size {
+            batches.push(Vec::new());
+            batch_size = 0;
+        }
+        batch_size += group_size;
+        if let Some(batch) = batches.last_mut() {
+            batch.extend(group);
+        }
+    }
+
+    let mut report_files: Vec<Option<ReportFile>> = report_files.into_iter().map(Some).collect();
+    batches
+        .into_iter()
+        .map(|batch| {
+            batch
+                .into_iter()
+                .filter_map(|index| report_files[index].take())
+                .collect()
+        })
+        .collect()
+}
+
+/// Parse size like `512MB`, `1GB`, `100KB` or number of bytes
+pub fn parse_byte_size(value: &str) -> Result<u64, ApiError> {
+    let value = value.trim();
+    let upper_value = value.to_ascii_uppercase();
+    let (number, multiplier) = [
+        ("GB", 1024 * 1024 * 1024),
+        ("MB", 1024 * 1024),
+        ("KB", 1024),
+        ("B", 1),
+    ]
+    .iter()
+    .find_map(|(suffix, multiplier)| {
+        upper_value
+            .strip_suffix(suffix)
+            .map(|number| (number.trim().to_string(), *multiplier))
+    })
+    .unwrap_or((upper_value.clone(), 1));
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(multiplier))
+        .filter(|size| *size > 0)
+        .ok_or_else(|| ApiError::InvalidSize(value.to_string()))
+}
+
+/// Format size in bytes like `512 B`, `1.5 KB` or `200.0 MB`
+pub fn format_byte_size(size: u64) -> String {
+    let units = [
+        ("GB", 1024 * 1024 * 1024),
+        ("MB", 1024 * 1024),
+        ("KB", 1024),
+    ];
+    match units.iter().find(|(_, multiplier)| size >= *multiplier) {
+        Some((unit, multiplier)) => format!("{:.1} {}", size as f64 / *multiplier as f64, unit),
+        None => format!("{} B", size),
+    }
+}
+
+/// Writer that only counts written bytes
+#[derive(Default)]
+struct ByteCounter(u64);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Size of the zip archive with report files, the archive itself is not kept
+pub fn report_archive_size(report_files: &[ReportFile]) -> Result<u64, ApiError> {
+    Ok(write_report_archive(report_files, ByteCounter::default())?.0)
+}
+
+/// Write report files to zip archive
+///
+/// Archive is written sequentially, so the writer doesn't need to support seeking
+pub fn write_report_archive<W: Write>(
+    report_files: &[ReportFile],
+    writer: W,
+) -> Result<W, ApiError> {
+    let mut zip = ZipWriter::new_stream(writer);
 
     // Настройки для файла в архиве
     let options = SimpleFileOptions::default()
         .compression_method(CompressionMethod::Deflated)
-        .unix_permissions(0o755);
-    let entries = read_dir(path_to_report_dir)
-        .map_err(|_| ApiError::NotFoundDirByPath(path_to_report_dir.to_string()))?;
-
-    for entry in entries {
-        let entry = entry?;
-        let path = entry.path();
-        if !path.is_file() {
-            continue;
+        .unix_permissions(0o755)
+        .large_file(true);
+
+    for report_file in report_files {
+        zip.start_file(report_file.name_in_archive.as_str(), options)?;
+        match &report_file.content {
+            Some(content) => zip.write_all(content)?,
+            None => {
+                let mut file = fs::File::open(&report_file.path)?;
+                io::copy(&mut file, &mut zip)?;
+            }
         }
+    }
+    // Завершаем запись архива
+    Ok(zip.finish()?.into_inner())
+}
 
-        let buffer = read_file_to_buffer(&path).await?;
-        let file_name = get_file_name(&path)?;
+/// Writer that sends archive chunks to the upload body
+struct ChannelWriter {
+    sender: mpsc::Sender<io::Result<Vec<u8>>>,
+    buffer: Vec<u8>,
+}
 
-        zip.start_file(file_name, options)?;
-        let _ = zip.write_all(&buffer);
+impl ChannelWriter {
+    fn new(sender: mpsc::Sender<io::Result<Vec<u8>>>) -> Self {
+        Self {
+            sender,
+            buffer: Vec::with_capacity(ARCHIVE_CHUNK_SIZE),
+        }
+    }
+
+    fn send_buffer(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(ARCHIVE_CHUNK_SIZE));
+        // Получатель закрывается, если запрос упал или был отменен
+        self.sender
+            .blocking_send(Ok(chunk))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "upload was interrupted"))
     }
-    // Завершаем запись архива
-    zip.finish()?;
-    Ok(dir_archive)
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= ARCHIVE_CHUNK_SIZE {
+            self.send_buffer()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send_buffer()
+    }
+}
+
+/// Archive report files on the fly into a request body
+///
+/// Nothing is written to disk and only a few chunks are kept in memory
+pub fn stream_report_archive(report_files: Vec<ReportFile>) -> reqwest::Body {
+    reqwest::Body::wrap_stream(archive_chunks(report_files))
+}
+
+fn archive_chunks(report_files: Vec<ReportFile>) -> impl Stream<Item = io::Result<Vec<u8>>> {
+    let (sender, mut receiver) = mpsc::channel(ARCHIVE_CHANNEL_CAPACITY);
+    tokio::task::spawn_blocking(move || {
+        let result = write_report_archive(&report_files, ChannelWriter::new(sender.clone()))
+            .and_then(|mut writer| writer.flush().map_err(ApiError::from));
+        if let Err(e) = result {
+            let _ = sender.blocking_send(Err(io::Error::other(e.to_string())));
+        }
+    });
+    futures_util::stream::poll_fn(move |cx| receiver.poll_recv(cx))
+}
+
+/// Remove report archives left in the config directory by previous versions
+pub fn remove_stale_report_archives() {
+    let Some(user_dirs) = UserDirs::new() else {
+        return;
+    };
+    let Ok(entries) = read_dir(user_dirs.home_dir().join(CONFIG_DIR)) else {
+        return;
+    };
+    let re = Regex::new(r"^testops_results_report_\d+\.zip$").unwrap();
+    entries
+        .flatten()
+        .filter(|entry| re.is_match(&entry.file_name().to_string_lossy()))
+        .for_each(|entry| {
+            let _ = fs::remove_file(entry.path());
+        });
+}
+
+/// Format duration in milliseconds like `1h 2m 3s`
+pub fn format_duration(duration_ms: i64) -> String {
+    let total_seconds = duration_ms.max(0) / 1000;
+    let (hours, minutes, seconds) = (
+        total_seconds / 3600,
+        total_seconds % 3600 / 60,
+        total_seconds % 60,
+    );
+    match (hours, minutes) {
+        (0, 0) => format!("{seconds}s"),
+        (0, _) => format!("{minutes}m {seconds}s"),
+        _ => format!("{hours}h {minutes}m {seconds}s"),
+    }
+}
+
+/// Text table with columns aligned by the widest value
+///
+/// Header line is omitted when `headers` is empty
+pub fn format_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let header_row: Vec<String> = headers.iter().map(|header| header.to_string()).collect();
+    let lines: Vec<&Vec<String>> = std::iter::once(&header_row)
+        .filter(|header_row| !header_row.is_empty())
+        .chain(rows)
+        .collect();
+    let mut widths: Vec<usize> = Vec::new();
+    for line in &lines {
+        for (column, cell) in line.iter().enumerate() {
+            let width = cell.chars().count();
+            match widths.get_mut(column) {
+                Some(max_width) => *max_width = (*max_width).max(width),
+                None => widths.push(width),
+            }
+        }
+    }
+    lines
+        .iter()
+        .map(|line| {
+            line.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<String>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
 }
 
 /// Convert to PascalCase
@@ -147,224 +621,444 @@ pub fn convert_to_pascal_case(input: &str) -> String {
 mod tests {
 
     use super::*;
-    use crate::external_api::testops_api::models::launch_info::LaunchInfo;
     use crate::external_api::testops_api::models::test_case_scenario::Scenario;
-    use crate::external_api::testops_api::TestopsApi;
-    use regex::Regex;
+    use futures_util::TryStreamExt;
     use rstest::rstest;
-    use std::{env, fs::Permissions, os::unix::fs::PermissionsExt};
+    use std::env;
+    use std::io::Cursor;
     use tokio::fs;
+    use zip::ZipArchive;
 
-    const CARGO_MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
-
-    fn assert_error_get_file_name(path: &Path) {
-        let result = get_file_name(path);
-        assert!(result.is_err());
-        let error = result.unwrap_err();
-        assert!(matches!(&error, ApiError::InvalidFileName));
-        assert_eq!(error.to_string(), "Invalid file name");
+    #[rstest]
+    #[case("test_some_one", "TestSomeOne")]
+    #[case("", "")]
+    #[case("test_one.py", "TestOne.py")]
+    fn test_convert_to_pascal_case(#[case] filename: String, #[case] exp_pascal_case: String) {
+        assert_eq!(exp_pascal_case, convert_to_pascal_case(&filename))
     }
 
-    fn assert_file_name(path: &Path, exp_file_name: &str) {
-        let result = get_file_name(path);
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), exp_file_name);
+    /// Создаем во временной директории отчет с вложенными папками
+    fn create_nested_report_dir() -> PathBuf {
+        let report_dir =
+            env::temp_dir().join(format!("wot_nested_report_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(report_dir.join("attachments/screenshots")).unwrap();
+        std::fs::create_dir_all(report_dir.join("worker_1")).unwrap();
+        std::fs::write(report_dir.join("1-result.json"), "{}").unwrap();
+        std::fs::write(report_dir.join("worker_1/2-result.json"), "{}").unwrap();
+        std::fs::write(report_dir.join("attachments/log.txt"), "log").unwrap();
+        std::fs::write(report_dir.join("attachments/screenshots/1.png"), "png").unwrap();
+        report_dir
     }
 
-    fn assert_io_error(result: Result<Vec<u8>, ApiError>) {
-        assert!(result.is_err());
-        let error = result.unwrap_err();
-        assert!(matches!(&error, ApiError::Io(_)));
-        assert!(error.to_string().starts_with("IO error: "));
+    fn collect_names(report_dir: &Path, filter: &ArchiveFilter) -> Vec<String> {
+        collect_report_files(report_dir, filter)
+            .unwrap()
+            .into_iter()
+            .map(|report_file| report_file.name_in_archive)
+            .collect()
     }
 
-    #[tokio::test]
-    async fn test_valid_zip_archive() {
-        let path = &Path::new(CARGO_MANIFEST_DIR).join("test_files/test_upload_launch_report.zip");
-        let buffer = read_file_to_buffer(path).await.unwrap();
-        let is_zip_file = validate_zip_archive(&buffer);
-        assert!(is_zip_file.is_ok());
-    }
-    // Invalid file format: File is not a valid ZIP archive
-
-    #[tokio::test]
-    async fn test_invalid_zip_archive() {
-        let path = &Path::new(CARGO_MANIFEST_DIR).join("test_files/file.json");
-        let buffer = read_file_to_buffer(path).await.unwrap();
-        let is_zip_file = validate_zip_archive(&buffer);
-        assert!(is_zip_file.is_err());
+    #[test]
+    /// Проверяем, что файлы из вложенных директорий попадают в архив с относительными путями
+    fn test_collect_report_files_recursive() {
+        let report_dir = create_nested_report_dir();
+        let names = collect_names(&report_dir, &ArchiveFilter::default());
+        std::fs::remove_dir_all(&report_dir).unwrap();
         assert_eq!(
-            is_zip_file.unwrap_err().to_string(),
-            "Invalid file format: File is not a valid ZIP archive".to_string()
+            names,
+            vec![
+                "1-result.json",
+                "attachments/log.txt",
+                "attachments/screenshots/1.png",
+                "worker_1/2-result.json",
+            ]
         );
     }
 
     #[rstest]
-    #[case("test_some_one", "TestSomeOne")]
-    #[case("", "")]
-    #[case("test_one.py", "TestOne.py")]
-    fn test_convert_to_pascal_case(#[case] filename: String, #[case] exp_pascal_case: String) {
-        assert_eq!(exp_pascal_case, convert_to_pascal_case(&filename))
+    #[case(vec!["**/*.json"], vec![], vec!["1-result.json", "worker_1/2-result.json"])]
+    #[case(vec!["*.json"], vec![], vec!["1-result.json"])]
+    #[case(vec![], vec!["attachments/**"], vec!["1-result.json", "worker_1/2-result.json"])]
+    #[case(vec!["attachments/**"], vec!["**/*.png"], vec!["attachments/log.txt"])]
+    fn test_collect_report_files_with_filter(
+        #[case] include: Vec<&str>,
+        #[case] exclude: Vec<&str>,
+        #[case] exp_names: Vec<&str>,
+    ) {
+        let to_strings =
+            |values: Vec<&str>| values.into_iter().map(String::from).collect::<Vec<_>>();
+        let filter = ArchiveFilter::new(&to_strings(include), &to_strings(exclude)).unwrap();
+        let report_dir = create_nested_report_dir();
+        let names = collect_names(&report_dir, &filter);
+        std::fs::remove_dir_all(&report_dir).unwrap();
+        assert_eq!(names, exp_names);
     }
 
     #[test]
-    fn test_get_file_name() {
-        let path = &Path::new(CARGO_MANIFEST_DIR).join("test_files/file.json");
-        assert_file_name(path, "file.json");
+    fn test_archive_filter_invalid_glob() {
+        let result = ArchiveFilter::new(&["a[".to_string()], &[]);
+        assert!(matches!(result, Err(ApiError::InvalidGlobPattern(_))));
     }
 
     #[test]
-    fn test_get_file_name_directory_path() {
-        let path = &Path::new(CARGO_MANIFEST_DIR).join("test_files/");
-        assert_error_get_file_name(path);
+    fn test_collect_report_files_not_found_dir() {
+        let result =
+            collect_report_files(Path::new("non/existent/path"), &ArchiveFilter::default());
+        assert!(matches!(result, Err(ApiError::NotFoundDirByPath(_))));
+    }
+
+    fn read_archive_names(buffer: Vec<u8>) -> Vec<String> {
+        let archive = ZipArchive::new(Cursor::new(buffer)).unwrap();
+        let mut names: Vec<String> = archive.file_names().map(String::from).collect();
+        names.sort();
+        names
     }
 
     #[test]
-    fn test_get_file_name_file_without_extension() {
-        let path = &Path::new(CARGO_MANIFEST_DIR).join("test_files/file_without_extension");
-        assert_file_name(path, "file_without_extension");
+    /// Проверяем, что вложенные файлы сохраняются в архиве
+    fn test_write_report_archive_nested_directory() {
+        let report_dir = create_nested_report_dir();
+        let report_files = collect_report_files(&report_dir, &ArchiveFilter::default()).unwrap();
+        let buffer = write_report_archive(&report_files, Vec::new()).unwrap();
+        std::fs::remove_dir_all(&report_dir).unwrap();
+        assert_eq!(
+            read_archive_names(buffer),
+            vec![
+                "1-result.json",
+                "attachments/log.txt",
+                "attachments/screenshots/1.png",
+                "worker_1/2-result.json",
+            ]
+        );
     }
 
     #[test]
-    fn test_get_file_name_epmty_path() {
-        let path = Path::new("");
-        assert_error_get_file_name(path);
+    /// Проверяем архивацию пустой папки
+    fn test_write_report_archive_empty() {
+        let buffer = write_report_archive(&[], Vec::new()).unwrap();
+        assert!(read_archive_names(buffer).is_empty());
     }
 
     #[tokio::test]
-    async fn test_read_file_to_buffer() {
-        let path = &Path::new(CARGO_MANIFEST_DIR).join("test_files/file.json");
-        let result = read_file_to_buffer(path).await;
-        assert!(result.is_ok());
-        let buffer = result.unwrap();
-        assert!(!buffer.is_empty());
+    /// Проверяем, что архив из потока совпадает по содержимому с файлами отчета
+    async fn test_stream_report_archive() {
+        let report_dir = create_nested_report_dir();
+        let report_files = collect_report_files(&report_dir, &ArchiveFilter::default()).unwrap();
+        let chunks: Vec<Vec<u8>> = archive_chunks(report_files).try_collect().await.unwrap();
+        std::fs::remove_dir_all(&report_dir).unwrap();
+        let buffer: Vec<u8> = chunks.into_iter().flatten().collect();
+        let mut archive = ZipArchive::new(Cursor::new(buffer)).unwrap();
+        let mut content = String::new();
+        std::io::Read::read_to_string(
+            &mut archive.by_name("attachments/log.txt").unwrap(),
+            &mut content,
+        )
+        .unwrap();
+        assert_eq!(content, "log");
     }
 
     #[tokio::test]
-    async fn test_read_nonexistens_file() {
-        let path = Path::new("/123qwe1123.json");
-        let result = read_file_to_buffer(path).await;
-        assert_io_error(result);
+    /// Проверяем, что поток возвращает ошибку, если файл пропал во время архивации
+    async fn test_stream_report_archive_missing_file() {
+        let report_files = vec![ReportFile {
+            path: PathBuf::from("non/existent/file.json"),
+            name_in_archive: "file.json".to_string(),
+            size: 10,
+            content: None,
+        }];
+        let result: io::Result<Vec<Vec<u8>>> = archive_chunks(report_files).try_collect().await;
+        assert!(result.is_err());
     }
 
-    #[tokio::test]
-    async fn test_empty_file() {
-        let path = &Path::new(CARGO_MANIFEST_DIR).join("empty_file.py");
-        let result = read_file_to_buffer(path).await;
-        assert_io_error(result);
+    fn create_report_dir(files: &[(&str, &str)]) -> PathBuf {
+        let report_dir = env::temp_dir().join(format!("wot_merge_{}", uuid::Uuid::new_v4()));
+        for (name, content) in files {
+            let path = report_dir.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        report_dir
     }
 
-    #[tokio::test]
-    async fn test_access_denied() {
-        let path = &Path::new(CARGO_MANIFEST_DIR).join("test_files/file_access_denied.txt");
-        fs::set_permissions(path, Permissions::from_mode(0o200))
-            .await
+    #[test]
+    /// Одинаковые файлы отправляются один раз, разные с одним именем переименовываются
+    fn test_collect_report_dirs_collisions() {
+        let api_dir = create_report_dir(&[
+            (
+                "1-result.json",
+                r#"{"uuid":"1","attachments":[{"source":"log-attachment.txt"}]}"#,
+            ),
+            ("log-attachment.txt", "api log"),
+            ("environment.properties", "os=linux"),
+        ]);
+        let ui_dir = create_report_dir(&[
+            (
+                "2-result.json",
+                r#"{"uuid":"2","steps":[{"attachments":[{"source":"log-attachment.txt"}]}]}"#,
+            ),
+            ("log-attachment.txt", "ui log"),
+            ("environment.properties", "os=linux"),
+        ]);
+        let paths = [
+            api_dir.to_str().unwrap().to_string(),
+            ui_dir.to_str().unwrap().to_string(),
+        ];
+
+        let report_files = collect_report_dirs(&paths, &ArchiveFilter::default()).unwrap();
+
+        let names: Vec<&str> = report_files
+            .iter()
+            .map(|report_file| report_file.name_in_archive.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "1-result.json",
+                "2-result.json",
+                "environment.properties",
+                "log-attachment-1.txt",
+                "log-attachment.txt",
+            ]
+        );
+        assert_eq!(report_files[3].read().unwrap(), b"ui log");
+        // Ссылка во втором отчете указывает на переименованное вложение
+        let ui_result: serde_json::Value =
+            serde_json::from_slice(&report_files[1].read().unwrap()).unwrap();
+        assert_eq!(attachment_sources(&ui_result), vec!["log-attachment-1.txt"]);
+        assert_eq!(
+            report_files[1].size,
+            report_files[1].read().unwrap().len() as u64
+        );
+        assert!(report_files[0].content.is_none());
+        std::fs::remove_dir_all(&api_dir).unwrap();
+        std::fs::remove_dir_all(&ui_dir).unwrap();
+    }
+
+    #[rstest]
+    #[case(b"same", b"same", true)]
+    #[case(b"first", b"other", false)]
+    #[case(b"short", b"longer", false)]
+    fn test_is_same_content(#[case] first: &[u8], #[case] second: &[u8], #[case] expected: bool) {
+        let report_file = |content: &[u8]| ReportFile {
+            path: PathBuf::from("missing"),
+            name_in_archive: "log-attachment.txt".to_string(),
+            size: content.len() as u64,
+            content: Some(content.to_vec()),
+        };
+        assert_eq!(
+            is_same_content(&report_file(first), &report_file(second)).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    /// Большие файлы сравниваются по частям, отличие в последней части тоже находится
+    fn test_is_same_content_large_files() {
+        let mut content = vec![7u8; ARCHIVE_CHUNK_SIZE * 3 + 10];
+        let report_dir = create_report_dir(&[("1-result.json", "{}")]);
+        let report_file = |name: &str, content: &[u8]| {
+            let path = report_dir.join(name);
+            std::fs::write(&path, content).unwrap();
+            ReportFile {
+                path,
+                name_in_archive: "video-attachment.mp4".to_string(),
+                size: content.len() as u64,
+                content: None,
+            }
+        };
+        let first = report_file("first.mp4", &content);
+        let same = report_file("same.mp4", &content);
+        *content.last_mut().unwrap() = 8;
+        let other = report_file("other.mp4", &content);
+
+        let results = (
+            is_same_content(&first, &same).unwrap(),
+            is_same_content(&first, &other).unwrap(),
+        );
+        std::fs::remove_dir_all(&report_dir).unwrap();
+        assert_eq!(results, (true, false));
+    }
+
+    #[rstest]
+    #[case("1-result.json", "1-1-result.json")]
+    #[case("worker/1-container.json", "worker/1-1-container.json")]
+    #[case("log-attachment.txt", "log-attachment-2.txt")]
+    #[case("history/history.json", "history/history-1.json")]
+    #[case("CATEGORIES", "CATEGORIES-1")]
+    #[case(".hidden", ".hidden-1")]
+    fn test_unique_name_in_archive(#[case] name: &str, #[case] expected: &str) {
+        let taken_names: HashMap<String, usize> = [name, "log-attachment-1.txt"]
+            .into_iter()
+            .enumerate()
+            .map(|(index, name)| (name.to_string(), index))
+            .collect();
+        assert_eq!(unique_name_in_archive(name, &taken_names), expected);
+    }
+
+    #[test]
+    fn test_write_report_archive_content_from_memory() {
+        let report_files = vec![ReportFile {
+            path: PathBuf::from("non/existent/1-result.json"),
+            name_in_archive: "1-result.json".to_string(),
+            size: 2,
+            content: Some(b"{}".to_vec()),
+        }];
+        let buffer = write_report_archive(&report_files, Cursor::new(Vec::new()))
+            .unwrap()
+            .into_inner();
+        let mut archive = ZipArchive::new(Cursor::new(buffer)).unwrap();
+        let mut content = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("1-result.json").unwrap(), &mut content)
             .unwrap();
+        assert_eq!(content, "{}");
+    }
 
-        let result = read_file_to_buffer(path).await;
-        assert_io_error(result);
+    fn batch_names(batches: &[Vec<ReportFile>]) -> Vec<Vec<&str>> {
+        batches
+            .iter()
+            .map(|batch| {
+                batch
+                    .iter()
+                    .map(|report_file| report_file.name_in_archive.as_str())
+                    .collect()
+            })
+            .collect()
     }
 
-    #[tokio::test]
-    async fn test_validate_project_id_exist() {
-        let testops_api = TestopsApi::default_test();
-        let project_id_exist: u32 = 2;
-        let res = validate_project_id(project_id_exist, &testops_api).await;
-        assert!(res.is_ok());
+    #[test]
+    /// Отчет меньше лимита отправляется одной пачкой
+    fn test_split_into_batches_single() {
+        let report_dir = create_nested_report_dir();
+        let report_files = collect_report_files(&report_dir, &ArchiveFilter::default()).unwrap();
+        let batches = split_into_batches(report_files, 1024);
+        std::fs::remove_dir_all(&report_dir).unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].len(), 4);
     }
 
-    #[tokio::test]
-    async fn test_validate_project_id_nonexist() {
-        let testops_api = TestopsApi::default_test();
-        let project_id_nonexist: u32 = 28888;
-        let res = validate_project_id(project_id_nonexist, &testops_api).await;
-        assert!(res.is_err());
+    #[test]
+    fn test_split_into_batches_empty() {
+        let batches = split_into_batches(vec![], 1024);
+        assert_eq!(batches.len(), 1);
+        assert!(batches[0].is_empty());
+    }
+
+    #[test]
+    /// Результат попадает в одну пачку со своими вложениями
+    fn test_split_into_batches_keeps_attachments_with_result() {
+        let report_dir = env::temp_dir().join(format!("wot_batch_report_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(report_dir.join("worker")).unwrap();
+        std::fs::write(report_dir.join("executor.json"), "{}").unwrap();
+        std::fs::write(
+            report_dir.join("1-result.json"),
+            r#"{"uuid": "1", "attachments": [{"source": "1-attachment.txt"}]}"#,
+        )
+        .unwrap();
+        std::fs::write(report_dir.join("1-attachment.txt"), "a".repeat(100)).unwrap();
+        std::fs::write(
+            report_dir.join("worker/2-result.json"),
+            r#"{"uuid": "2", "steps": [{"attachments": [{"source": "2-attachment.txt"}]}]}"#,
+        )
+        .unwrap();
+        std::fs::write(report_dir.join("worker/2-attachment.txt"), "b".repeat(100)).unwrap();
+        let report_files = collect_report_files(&report_dir, &ArchiveFilter::default()).unwrap();
+        let batches = split_into_batches(report_files, 200);
+        std::fs::remove_dir_all(&report_dir).unwrap();
         assert_eq!(
-            res.unwrap_err().to_string(),
-            format!("Project with ID == {} not found", project_id_nonexist)
+            batch_names(&batches),
+            vec![
+                vec!["executor.json", "1-result.json", "1-attachment.txt"],
+                vec!["worker/2-result.json", "worker/2-attachment.txt"],
+            ]
         );
     }
 
+    #[rstest]
+    #[case(0, "0s")]
+    #[case(999, "0s")]
+    #[case(5_000, "5s")]
+    #[case(65_000, "1m 5s")]
+    #[case(3_723_000, "1h 2m 3s")]
+    #[case(-1, "0s")]
+    fn test_format_duration(#[case] duration_ms: i64, #[case] expected: &str) {
+        assert_eq!(format_duration(duration_ms), expected);
+    }
+
     #[test]
-    /// Получение директории до файла с архивом результатов
-    fn test_get_dir_archive_path() {
-        let dir_path = get_dir_archive().unwrap_or(PathBuf::new());
-        let re = Regex::new(r"testops_results_report_\d+\.zip").unwrap();
-        let archive_file_name = dir_path
-            .file_name()
-            .unwrap_or_default()
-            .to_str()
-            .unwrap_or_default();
-        if !re.is_match(archive_file_name) {
-            assert!(
-                false,
-                "Получили dir_path == \"{}\". Ожидали что имя файла будет соответствовать шаблону testops_results_report_\\d+\\.zip",
-                archive_file_name
-            );
-        }
+    fn test_format_table() {
+        let rows = vec![
+            vec!["1".to_string(), "Web".to_string()],
+            vec!["120".to_string(), "Мобильное приложение".to_string()],
+        ];
+        assert_eq!(
+            format_table(&["ID", "NAME"], &rows),
+            "ID   NAME\n1    Web\n120  Мобильное приложение"
+        );
+        assert_eq!(
+            format_table(&[], &rows),
+            "1    Web\n120  Мобильное приложение"
+        );
     }
 
-    #[tokio::test]
-    /// Проверяем архивацию пустой папки
-    async fn test_zip_empty_directory() {
-        // Получаем полный путь до папки для архивирования и создаем ее(пустая без файлов)
-        let binding = UserDirs::new().unwrap();
-        let desktop_dir = binding.desktop_dir().unwrap();
-        let full_path: PathBuf = desktop_dir.join("dir_for_test_zip_directory");
-        let _ = fs::create_dir_all(&full_path);
-        // Используем функцию zip_directory и проверяем полученный путь
-        let zip_dir = zip_directory(&full_path.to_str().unwrap())
-            .await
-            .unwrap_or_default();
-        let re = Regex::new(r"testops_results_report_\d+\.zip").unwrap();
-        if !re.is_match(&zip_dir.to_str().unwrap_or_default()) {
-            assert!(
-                false,
-                "Получили dir_path == <{:?}>. Ожидали что имя файла будет соответствовать шаблону testops_results_report_\\d+\\.zip",
-                &zip_dir.to_str().unwrap_or_default()
-            );
-        }
+    #[rstest]
+    #[case("100", 100)]
+    #[case("100B", 100)]
+    #[case("2KB", 2048)]
+    #[case("512MB", 512 * 1024 * 1024)]
+    #[case("1gb", 1024 * 1024 * 1024)]
+    #[case(" 10 MB ", 10 * 1024 * 1024)]
+    fn test_parse_byte_size(#[case] value: &str, #[case] expected: u64) {
+        assert_eq!(parse_byte_size(value).unwrap(), expected);
     }
 
-    #[tokio::test]
-    /// Проверяем архивацию директории, в которой есть файл
-    async fn test_zip_dir_with_one_file() {
-        // Получаем полный путь до папки для архивирования и создаем ее(пустая без файлов)
-        let binding = UserDirs::new().unwrap();
-        let desktop_dir = binding.desktop_dir().unwrap();
-        let full_path: PathBuf = desktop_dir.join("dir_for_test_zip_directory_with_one_file");
-        let _ = fs::create_dir_all(&full_path);
-        // Создаем файл в директории
-        let _ = File::create(full_path.join("some_file.json"))
-            .await
-            .unwrap();
-        // Используем функцию zip_directory и проверяем полученный путь
-        let zip_dir = zip_directory(&full_path.to_str().unwrap())
-            .await
-            .unwrap_or_default();
-        let re = Regex::new(r"testops_results_report_\d+\.zip").unwrap();
-        if !re.is_match(&zip_dir.to_str().unwrap_or_default()) {
-            assert!(
-                false,
-                "Получили dir_path == <{:?}>. Ожидали что имя файла будет соответствовать шаблону testops_results_report_\\d+\\.zip",
-                &zip_dir.to_str().unwrap_or_default()
-            );
-        }
+    #[rstest]
+    #[case(0, "0 B")]
+    #[case(1023, "1023 B")]
+    #[case(1536, "1.5 KB")]
+    #[case(200 * 1024 * 1024, "200.0 MB")]
+    #[case(3 * 1024 * 1024 * 1024, "3.0 GB")]
+    fn test_format_byte_size(#[case] size: u64, #[case] expected: &str) {
+        assert_eq!(format_byte_size(size), expected);
     }
 
-    #[tokio::test]
-    /// Проверяем загрузку лаунча
-    async fn test_upload_launch() {
-        let testops_api_client = TestopsApi::default_test();
-        let launch_info = LaunchInfo::default();
-        let path_archive = PathBuf::from(format!(
-            "{}/test_files/testops_results_report_1735389182.zip",
-            env!("CARGO_MANIFEST_DIR")
+    #[test]
+    fn test_report_archive_size() {
+        let report_files = vec![ReportFile {
+            path: PathBuf::from("1-result.json"),
+            name_in_archive: "1-result.json".to_string(),
+            size: 2,
+            content: Some(b"{}".to_vec()),
+        }];
+        let archive = write_report_archive(&report_files, Vec::new()).unwrap();
+        assert_eq!(
+            report_archive_size(&report_files).unwrap(),
+            archive.len() as u64
+        );
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("0")]
+    #[case("MB")]
+    #[case("-1MB")]
+    #[case("1TB")]
+    #[case("99999999999GB")]
+    fn test_parse_byte_size_invalid(#[case] value: &str) {
+        assert!(matches!(
+            parse_byte_size(value),
+            Err(ApiError::InvalidSize(_))
         ));
-        let _ = testops_api_client
-            .post_upload_report(&path_archive, &launch_info)
-            .await
-            .unwrap();
+    }
+
+    #[test]
+    /// Проверяем удаление архивов, оставшихся от прошлых версий
+    fn test_remove_stale_report_archives() {
+        let config_dir = UserDirs::new().unwrap().home_dir().join(CONFIG_DIR);
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let stale_archive = config_dir.join("testops_results_report_1735389182.zip");
+        std::fs::write(&stale_archive, b"zip").unwrap();
+        remove_stale_report_archives();
+        assert!(!stale_archive.exists());
     }
 
     #[tokio::test]